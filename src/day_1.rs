@@ -1,10 +1,10 @@
-
 // https://adventofcode.com/2022/day/1
 // Calorie counter.
 // Given an input list of elves and sequential integers of how many 'calories' each is carrying, return the highest total calorie count.
 // In part 2, return the sum of the top 3 highest calorie totals.
 
 use super::*;
+use std::fmt;
 
 // CalorieCounter struct for accumulating over list of calorie counts
 struct CalorieCount {
@@ -12,18 +12,54 @@ struct CalorieCount {
     top_calorie_records: [i32; 3], // highest 3 calorie counts found so far, unordered
 }
 
+// Summary of the elf list, built up while parsing and shown in verbose mode
+// eg: "312 elves, 98,421 total calories, richest elf #87 with 68,467"
+#[derive(Debug, PartialEq)]
+struct Report {
+    elf_count: usize, // number of elves in the list
+    total_calories: i32, // sum of every elf's calories
+    richest_elf: usize, // one-indexed position of the elf carrying the most calories (0 if no elves)
+    richest_elf_calories: i32, // calories carried by that elf
+}
+
 // Run challenge.
 // Main entry point to day 1 challenge.
-pub fn run(part_2: bool) -> Result<(), Box<dyn error::Error>>{
+pub fn run(part_2: bool, options: &Options) -> Result<(), Box<dyn error::Error>>{
 
     let f = File::open("input/day1input.txt")?;
     let reader = BufReader::new(f);
 
+    let (answer, report) = solve(reader, part_2)?;
+
+    // For part 1, prints highest collected calorie count
+    // For part 2, prints total of calorie counts being collected
+    let part = if part_2 {2} else {1};
+    println!("Result for day 1-{part} = {answer}");
+    // The report covers the whole list, so it is only printed once, alongside part 1
+    if options.verbose && !part_2 {
+        println!("{report}");
+    }
+    Ok(())
+}
+
+// Reads the elf calorie list from 'reader' and returns the answer for the given part, alongside a Report of the list.
+// Each elf is a group of integer lines, and groups are separated by blank lines.
+fn solve<R: BufRead>(reader: R, part_2: bool) -> Result<(i32, Report), Box<dyn error::Error>> {
+
     // Create a new calorie counter
     let mut calorie_counter = CalorieCount {
         current_calorie_count: 0,
         top_calorie_records: [0, 0, 0],
     };
+    let mut report = Report {
+        elf_count: 0,
+        total_calories: 0,
+        richest_elf: 0,
+        richest_elf_calories: 0,
+    };
+
+    // Whether the current elf has any calorie lines yet (so repeated blank lines don't count as elves)
+    let mut current_elf_started = false;
 
     // Iterate through each line of the input
     for line in reader.lines(){
@@ -33,24 +69,33 @@ pub fn run(part_2: bool) -> Result<(), Box<dyn error::Error>>{
         // If line is a newline or empty, this marks the end of calorie list for this elf
         // Store current value (if high enough) and reset current counter
         if line.trim().is_empty() {
-            calorie_counter.store_current_if_top_record();
+            if current_elf_started {
+                report.record_elf(calorie_counter.current_calorie_count);
+                calorie_counter.store_current_if_top_record();
+            }
             calorie_counter.current_calorie_count = 0;
-        } else { 
+            current_elf_started = false;
+        } else {
             // Attempts to read calorie count as an integer, adds to calorie counter if so
             let calories = line.trim().parse::<i32>().expect("Cannot read text file, contains non-numeric value."); // panics if cannot read (some non-newline/numeric value)
             calorie_counter.current_calorie_count += calories;
+            current_elf_started = true;
         }
 
     }
 
-    // For part 1, prints highest collected calorie count
-    // For part 2, prints total of calorie counts being collected
-    if part_2 {
-        println!("Result for day 1-2 = {}",calorie_counter.records_sum());
-    } else {
-        println!("Result for day 1-1 = {}",calorie_counter.records_max());
+    // The final elf is not followed by a blank line, so store it as well
+    if current_elf_started {
+        report.record_elf(calorie_counter.current_calorie_count);
+        calorie_counter.store_current_if_top_record();
     }
-    Ok(())
+
+    let answer = if part_2 {
+        calorie_counter.records_sum()
+    } else {
+        calorie_counter.records_max()
+    };
+    Ok((answer, report))
 }
 
 impl CalorieCount {
@@ -75,10 +120,95 @@ impl CalorieCount {
     fn records_max(&self) -> i32 {
         self.top_calorie_records.iter().max().copied().unwrap_or(0)
     }
-    
+
     // Sum of top_calorie_records array
     // Returns 0 if array is empty
     fn records_sum(&self) -> i32 {
         self.top_calorie_records.iter().sum()
     }
 }
+
+impl Report {
+    // Adds a completed elf carrying 'calories' to the report
+    // Ties keep the earliest elf as the richest
+    fn record_elf(&mut self, calories: i32) {
+        self.elf_count += 1;
+        self.total_calories += calories;
+        if self.richest_elf == 0 || calories > self.richest_elf_calories {
+            self.richest_elf = self.elf_count;
+            self.richest_elf_calories = calories;
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} elves, {} total calories, richest elf #{} with {}",
+            self.elf_count,
+            with_thousands_separators(self.total_calories),
+            self.richest_elf,
+            with_thousands_separators(self.richest_elf_calories))
+    }
+}
+
+// Formats an integer with commas between each group of three digits
+// eg: 98421 -> "98,421"
+fn with_thousands_separators(n: i32) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut s = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            s.push(',');
+        }
+        s.push(c);
+    }
+    if n < 0 {
+        s.insert(0, '-');
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Advent of Code challenge-provided example
+    const EXAMPLE_INPUT: &str = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000";
+
+    #[test]
+    fn example_report() {
+        let (answer, report) = solve(EXAMPLE_INPUT.as_bytes(), false).unwrap();
+        assert_eq!(answer, 24000);
+        assert_eq!(report, Report {
+            elf_count: 5,
+            total_calories: 55000,
+            richest_elf: 4,
+            richest_elf_calories: 24000,
+        });
+
+        // Part 2 includes the final elf, which has no trailing blank line
+        let (answer, _) = solve(EXAMPLE_INPUT.as_bytes(), true).unwrap();
+        assert_eq!(answer, 45000);
+
+        // Repeated and trailing blank lines do not create extra elves
+        let (_, report) = solve("1000\n\n\n2000\n\n".as_bytes(), false).unwrap();
+        assert_eq!(report.elf_count, 2);
+        assert_eq!(report.richest_elf, 2);
+    }
+
+    #[test]
+    fn report_display() {
+        let report = Report {
+            elf_count: 312,
+            total_calories: 98421,
+            richest_elf: 87,
+            richest_elf_calories: 68467,
+        };
+        assert_eq!(report.to_string(), "312 elves, 98,421 total calories, richest elf #87 with 68,467");
+        assert_eq!(with_thousands_separators(0), "0");
+        assert_eq!(with_thousands_separators(999), "999");
+        assert_eq!(with_thousands_separators(1234567), "1,234,567");
+        assert_eq!(with_thousands_separators(-1000), "-1,000");
+    }
+}
@@ -11,7 +11,7 @@ use lazy_static::lazy_static;
 
 // Run challenge.
 // Main entry point to day 10 challenge.
-pub fn run (part_2 : bool) -> Result<(),Box<dyn error::Error>> {
+pub fn run (part_2 : bool, _options: &Options) -> Result<(),Box<dyn error::Error>> {
 
     // Load input text into file buffer
     let f = File::open("input/day10input.txt").unwrap();
@@ -6,7 +6,7 @@
 // sum the total number of points earned for RPS games played (weighted by the RPS choice you made).
 
 // Inputs can be ABC or XYZ,and must resemble:
-// A X 
+// A X
 // B Y
 // A Z
 // ...
@@ -14,9 +14,12 @@
 
 
 use super::*;
+use std::{borrow::Borrow, fmt, io, path::Path, str::FromStr};
+#[cfg(test)]
+use lazy_static::lazy_static;
 
 // Rock Paper Scissors choice
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Debug)]
 enum RPSChoice {
     Rock,
     Paper,
@@ -24,125 +27,1169 @@ enum RPSChoice {
 }
 
 // Result of a Rock Paper Scissors game
+#[derive(PartialEq, Copy, Clone, Debug)]
 enum RPSResult {
     Win,
     Draw,
     Loss
 }
 
+// A game of 'n' gestures arranged in a cycle, generalizing Rock Paper Scissors (which is n = 3)
+// Gestures are numbered 0..n, and each gesture beats the (n-1)/2 gestures preceding it in the cycle
+// (so gesture i always beats gesture (i-1) mod n) and loses to the (n-1)/2 following it.
+#[derive(PartialEq, Copy, Clone, Debug)]
+struct CyclicGame {
+    n: usize, // number of gestures
+}
+
+// Rock Paper Scissors as a CyclicGame, with Rock/Paper/Scissors as gestures 0/1/2
+const RPS_GAME: CyclicGame = CyclicGame::new(3);
+
+// The raw second column of the strategy guide.
+// Its meaning depends on the part: in part 1 it is the player's choice, in part 2 the intended result.
+#[derive(PartialEq, Copy, Clone, Debug)]
+enum Hint {
+    X,
+    Y,
+    Z,
+}
+
+// A single line of the strategy guide: the opponent's choice and the hint for how to respond
+#[derive(PartialEq, Copy, Clone, Debug)]
+struct Round {
+    opponent: RPSChoice,
+    hint: Hint,
+}
+
+// Points awarded for each choice made by the player, and for each result of a round
+// The default is the puzzle's scoring, but other values can be used for house rules (eg: a loss costing a point)
+#[derive(PartialEq, Copy, Clone, Debug)]
+struct ScoreTable {
+    rock: i32,
+    paper: i32,
+    scissors: i32,
+    win: i32,
+    draw: i32,
+    loss: i32,
+}
+
+// The outcome of a single scored round
+#[derive(PartialEq, Copy, Clone, Debug)]
+struct RoundResult {
+    player: RPSChoice, // choice made by the player (derived from the hint in part 2)
+    opponent: RPSChoice,
+    result: RPSResult, // result for the player
+    points: i32, // points earned by the player
+}
+
+// Tally of results and of the choices the player made across a set of rounds
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+struct Stats {
+    wins: usize,
+    draws: usize,
+    losses: usize,
+    rock: usize, // number of times the player threw Rock
+    paper: usize,
+    scissors: usize,
+}
+
+// How malformed lines in the strategy guide are handled
+#[derive(PartialEq, Copy, Clone, Debug)]
+enum ParseMode {
+    Strict, // fail on the first malformed line
+    Lenient, // skip malformed lines, recording each one
+}
+
+// Totals for every interpretation of a strategy guide
+#[derive(PartialEq, Copy, Clone, Debug)]
+struct Analysis {
+    part1: i32, // score reading the hint as the player's choice
+    part2: i32, // score reading the hint as the intended result
+    best_possible: i32, // score if the player won every round
+}
+
+// A fixed way of choosing what to throw, ignoring the guide's hints
+#[derive(PartialEq, Copy, Clone, Debug)]
+enum Strategy {
+    AlwaysRock,
+    AlwaysPaper,
+    AlwaysScissors,
+    CopyOpponent, // throw the same choice as the opponent
+    CounterOpponent, // throw the choice that beats the opponent
+}
+
+// Rounds read from a strategy guide, along with any malformed lines skipped in lenient mode
+#[derive(PartialEq, Debug)]
+struct ParsedGuide {
+    rounds: Vec<Round>,
+    skipped: Vec<GuideLineError>,
+}
+
+// Iterator over the Rounds of a strategy guide, parsed lazily from any BufRead
+// Blank lines are skipped. Malformed lines are yielded as a GuideLineError (with its line number),
+// after which iteration stops, as it does after a read error.
+#[cfg(test)] // the puzzle input is small enough for parse_guide_with_mode, this is for large generated guides
+struct Rounds<R: BufRead> {
+    lines: io::Lines<R>,
+    line_number: usize, // one-indexed number of the last line read
+    finished: bool, // whether an error has ended iteration
+}
+
 // Run challenge.
 // Main entry point to day 2 challenge.
-pub fn run(part_2: bool) -> Result<(),Box<dyn error::Error>> {
-
-    let mut score = 0;
+pub fn run(options: &Options) -> Result<Answers, Box<dyn error::Error>> {
 
-    // Load data to buffer and iterate over lines
+    // Load data from file
     let f = File::open("input/day2input.txt")?;
-    let buf = BufReader::new(f);
+    let mut buf = BufReader::new(f);
 
-    for line in buf.lines() {
-        let line = line.expect("Could not read line.");
-        let mut c = line.split_whitespace();
+    let mut input = String::new();
+    buf.read_to_string(&mut input)?;
+    solve(&input, options)
+}
 
-        // Opponent's choice depends on A,B,C in file.
-        let opp_choice  = get_rps_choice(c.next().unwrap_or(" "));
+// Parses the strategy guide 'input' once and scores it for both parts
+// Malformed lines fail the guide, unless 'options.lenient' is set, where they are skipped instead, and lowercase letters and
+// full gesture words are accepted too.
+// In verbose mode, details include any skipped lines, the breakdown of every round, statistics for each part, and a comparison
+// against fixed strategies.
+// If 'options.dump' is set, the per-round breakdown is also written there as CSV, with one file per part
+// eg: "--dump day2-rounds.csv" writes "day2-rounds-part1.csv" and "day2-rounds-part2.csv"
+fn solve(input: &str, options: &Options) -> Result<Answers, Box<dyn error::Error>> {
+    let mode = if options.lenient { ParseMode::Lenient } else { ParseMode::Strict };
+    let ParsedGuide { rounds, skipped } = parse_guide_with_mode(input, mode, options.lenient)?;
+    let table = ScoreTable::default();
 
-        // Player choice depends on 'XYZ', which is semantically different for part_1 or part_2.
-        let player_choice;
-        if part_2 {
-            // Part 2 - XYZ is the intended result (X/Y/Z => LOSS/DRAW/WIN), get player choice such that that result occurrs.
-            player_choice = get_choice_for_desired_outcome(c.next().unwrap_or(" "), opp_choice);
+    if let Some(path) = &options.dump {
+        for part_2 in [false, true] {
+            let f = File::create(dump_path_for_part(path, part_2))?;
+            write_csv(&score_rounds_detailed(&rounds, part_2, &table), io::BufWriter::new(f))?;
+        }
+    }
 
-        } else {
-            // Part 1 - 'XYZ' values represent player choice (X/Y/Z => ROCK/PAPER/SCISSORS).
-            player_choice = get_rps_choice(c.next().unwrap_or(" "));
+    // Both parts are scored from the same parsed rounds
+    let analysis = analyze(&rounds);
+    let mut details = Vec::new();
+    if options.verbose {
+        if options.lenient {
+            details.push(format!("Skipped {} malformed line(s)", skipped.len()));
+            details.extend(skipped.iter().map(|e| format!("  {e}")));
+        }
+        for part_2 in [false, true] {
+            let part = if part_2 {2} else {1};
+            details.push(format!("Part {part} rounds:"));
+            for (i, round_result) in score_rounds_detailed(&rounds, part_2, &table).iter().enumerate() {
+                details.push(format!("#{}: {round_result}", i + 1));
+            }
+            details.push(format!("Part {part} stats: {}", stats(&rounds, part_2)));
+        }
+
+        // Compare both parts against the fixed strategies
+        details.push(format!("{:<18}{:>8}", "Strategy", "Score"));
+        details.push(format!("{:<18}{:>8}", "Guide (part 1)", analysis.part1));
+        details.push(format!("{:<18}{:>8}", "Guide (part 2)", analysis.part2));
+        for strategy in Strategy::ALL {
+            details.push(format!("{:<18}{:>8}", strategy.to_string(), simulate_strategy(&rounds, strategy)));
+        }
+    }
+
+    Ok(Answers {
+        part_1: analysis.part1.to_string(),
+        part_2: analysis.part2.to_string(),
+        notes: Vec::new(),
+        details,
+    })
+}
+
+// Parses a strategy guide into a list of Rounds, one per line, failing on the first malformed line
+// Each line must be an opponent choice and a hint separated by whitespace (eg: "A Y")
+// Blank lines are ignored, and errors carry the (one-indexed) line number they occurred on.
+#[cfg(test)] // the runner picks its ParseMode with parse_guide_with_mode
+fn parse_guide(input: &str) -> Result<Vec<Round>, GuideLineError> {
+    Ok(parse_guide_with_mode(input, ParseMode::Strict, false)?.rounds)
+}
+
+// Parses a strategy guide into a list of Rounds, one per line, handling malformed lines according to 'mode'
+// Blank lines are ignored in either mode.
+// In strict mode, the first malformed line is returned as an error.
+// In lenient mode, malformed lines are skipped and returned alongside the rounds that could be read.
+// If 'lenient_tokens', lowercase letters and full gesture words are also accepted (see Round::parse_line).
+fn parse_guide_with_mode(input: &str, mode: ParseMode, lenient_tokens: bool) -> Result<ParsedGuide, GuideLineError> {
+    let mut guide = ParsedGuide { rounds: Vec::new(), skipped: Vec::new() };
+
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
         }
+        match Round::parse_line(line, lenient_tokens) {
+            Ok(round) => guide.rounds.push(round),
+            Err(error) => {
+                let error = GuideLineError { line: i + 1, content: line.to_string(), error };
+                match mode {
+                    ParseMode::Strict => return Err(error),
+                    ParseMode::Lenient => guide.skipped.push(error),
+                }
+            }
+        }
+    }
+    Ok(guide)
+}
+
+// Sums the score of every round, using points from 'table'
+// In part 1, the hint is the player's choice.
+// In part 2, the hint is the intended result, and the player's choice is derived from it.
+// 'rounds' can be a slice of Rounds, or a stream of them (see Rounds).
+fn score<I>(rounds: I, part_2: bool, table: &ScoreTable) -> i32
+where I: IntoIterator, I::Item: Borrow<Round> {
+    rounds.into_iter().map(|round| round.borrow().result(part_2, table).points).sum()
+}
 
-        // Score round and append to running total
-        score += score_round(player_choice, opp_choice);
+// Scores a strategy guide as it is read from 'reader', without keeping its rounds in memory
+// Stops at, and returns, the first malformed line or read error.
+#[cfg(test)]
+fn score_reader<R: BufRead>(reader: R, part_2: bool, table: &ScoreTable) -> Result<i32, Box<dyn error::Error>> {
+    let mut error = None;
+    let total = score(Rounds::new(reader).map_while(|round| round.map_err(|e| error = Some(e)).ok()), part_2, table);
+    match error {
+        Some(e) => Err(e),
+        None => Ok(total),
     }
+}
 
+// Scores the rounds under both interpretations of the hint, as well as the best possible score
+// (where the player always throws the choice that beats the opponent)
+fn analyze(rounds: &[Round]) -> Analysis {
+    let table = ScoreTable::default();
+    Analysis {
+        part1: score(rounds, false, &table),
+        part2: score(rounds, true, &table),
+        best_possible: simulate_strategy(rounds, Strategy::CounterOpponent),
+    }
+}
+
+// Scores the rounds as if the player had followed 'strategy', using only the opponent's choices
+fn simulate_strategy(rounds: &[Round], strategy: Strategy) -> i32 {
+    let table = ScoreTable::default();
+    rounds.iter().map(|round| table.score_round(strategy.choice_against(round.opponent), round.opponent)).sum()
+}
+
+// Scores a strategy guide directly from its bytes, using SCORE_LOOKUP rather than constructing Rounds
+// Lines must be exactly an opponent letter (A/B/C), a space, and a hint letter (X/Y/Z), eg: "A Y"
+// Blank lines are ignored. Returns None if any other line is found.
+#[cfg(test)] // faster alternative to parse_guide + score, compared against it in tests and benchmarks
+fn score_fast(input: &str, part_2: bool) -> Option<i32> {
+    let lookup = &SCORE_LOOKUP[part_2 as usize];
+    let mut total = 0;
+    for line in input.lines() {
+        match line.trim().as_bytes() {
+            [opponent @ b'A'..=b'C', b' ', hint @ b'X'..=b'Z'] => {
+                total += lookup[((opponent - b'A') * 3 + (hint - b'X')) as usize];
+            },
+            [] => continue,
+            _ => return None,
+        }
+    }
+    Some(total)
+}
+
+// Builds the score for every (opponent, hint) pair under the default table, for both parts
+// Indexed by [part_2][opponent * 3 + hint], where A/B/C and X/Y/Z are 0/1/2
+#[cfg(test)]
+fn build_score_lookup() -> [[i32; 9]; 2] {
+    let table = ScoreTable::default();
+    let mut lookup = [[0; 9]; 2];
+    for (part, scores) in lookup.iter_mut().enumerate() {
+        for (opponent_index, opponent) in [RPSChoice::Rock, RPSChoice::Paper, RPSChoice::Scissors].into_iter().enumerate() {
+            for (hint_index, hint) in [Hint::X, Hint::Y, Hint::Z].into_iter().enumerate() {
+                let round = Round { opponent, hint };
+                scores[opponent_index * 3 + hint_index] = table.score_round(round.player_choice(part == 1), opponent);
+            }
+        }
+    }
+    lookup
+}
+
+#[cfg(test)]
+lazy_static! {
+    static ref SCORE_LOOKUP: [[i32; 9]; 2] = build_score_lookup();
+}
+
+// Scores every round, keeping the choices, result and points of each
+fn score_rounds_detailed(rounds: &[Round], part_2: bool, table: &ScoreTable) -> Vec<RoundResult> {
+    rounds.iter().map(|round| round.result(part_2, table)).collect()
+}
+
+// Writes rounds to 'writer' as a strategy guide that parse_guide reads back to the same rounds
+// Every line, including the last, ends with a newline.
+#[cfg(test)] // for generating test fixtures
+fn write_guide<W: Write>(rounds: &[Round], mut writer: W) -> io::Result<()> {
+    for round in rounds {
+        writeln!(writer, "{}", round.to_line())?;
+    }
+    writer.flush()
+}
+
+// Writes round results as CSV to 'writer', with a header and one row per round
+// eg: "round,opponent,player,outcome,points\n1,Rock,Paper,Win,8\n"
+fn write_csv<W: Write>(round_results: &[RoundResult], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "round,opponent,player,outcome,points")?;
+    for (i, r) in round_results.iter().enumerate() {
+        writeln!(writer, "{},{},{},{},{}", i + 1, r.opponent, r.player, r.result, r.points)?;
+    }
+    writer.flush()
+}
+
+// Inserts the part number before the extension of a dump path
+// eg: "day2-rounds.csv" -> "day2-rounds-part1.csv"
+fn dump_path_for_part(path: &str, part_2: bool) -> String {
     let part = if part_2 {2} else {1};
-    println!("Result for day 2-{part} = {score}");
-    Ok(())
+    let path = Path::new(path);
+    let stem = path.with_extension("");
+    match path.extension() {
+        Some(ext) => format!("{}-part{part}.{}", stem.display(), ext.to_string_lossy()),
+        None => format!("{}-part{part}", stem.display()),
+    }
+}
+
+// Counts the results and the player's choices over every round
+// In part 2 the counted choice is the one derived from the hint, not the hint letter itself.
+fn stats(rounds: &[Round], part_2: bool) -> Stats {
+    let mut stats = Stats::default();
+    for round_result in score_rounds_detailed(rounds, part_2, &ScoreTable::default()) {
+        stats.add(&round_result);
+    }
+    stats
 }
 
 impl RPSChoice {
 
-    // What this RPS choice loses to
-    fn loses_to(&self) -> RPSChoice {
-        match *self {
-            RPSChoice::Rock => RPSChoice::Paper,
-            RPSChoice::Paper => RPSChoice::Scissors,
-            RPSChoice::Scissors => RPSChoice::Rock,
+    // Parses a token of the opponent column, as FromStr does (X/A - Rock, Y/B - Paper, Z/C - Scissors)
+    // If 'lenient', letters may be in either case, and the full gesture names (in any case) are accepted too.
+    // Errors report the token as it was given.
+    fn parse_token(token: &str, lenient: bool) -> Result<RPSChoice, ParseRPSError> {
+        let normalized = if lenient { token.to_ascii_uppercase() } else { token.to_string() };
+        match normalized.as_str() {
+            "X" | "A" => Ok(RPSChoice::Rock),
+            "Y" | "B" => Ok(RPSChoice::Paper),
+            "Z" | "C" => Ok(RPSChoice::Scissors),
+            "ROCK" if lenient => Ok(RPSChoice::Rock),
+            "PAPER" if lenient => Ok(RPSChoice::Paper),
+            "SCISSORS" if lenient => Ok(RPSChoice::Scissors),
+            _ => Err(ParseRPSError::InvalidChoice(token.to_string())),
         }
     }
 
-    // What this RPS choice beats
-    fn beats(&self) -> RPSChoice {
+    // Position of this choice in RPS_GAME
+    fn index(&self) -> usize {
         match *self {
-            RPSChoice::Scissors => RPSChoice::Paper,
-            RPSChoice::Rock => RPSChoice::Scissors,
-            RPSChoice::Paper => RPSChoice::Rock,
+            RPSChoice::Rock => 0,
+            RPSChoice::Paper => 1,
+            RPSChoice::Scissors => 2,
         }
     }
 
+    // Choice at position 'i' in RPS_GAME (which must be less than 3)
+    fn from_index(i: usize) -> RPSChoice {
+        [RPSChoice::Rock, RPSChoice::Paper, RPSChoice::Scissors][i]
+    }
+
+    // What this RPS choice loses to
+    fn loses_to(&self) -> RPSChoice {
+        self.choice_for_result(RPSResult::Win)
+    }
+
     // Result of a game played by this choice against opp_choice as an RPSResult
     fn play_against(&self, opp_choice: RPSChoice) -> RPSResult {
-        let beats_opp = opp_choice.loses_to() ;        
-        if *self == beats_opp {
-            RPSResult::Win
-        } else if *self == opp_choice { 
+        RPS_GAME.play(self.index(), opp_choice.index())
+    }
+
+    // The choice to play against this choice to achieve 'result'
+    fn choice_for_result(&self, result: RPSResult) -> RPSChoice {
+        RPSChoice::from_index(RPS_GAME.gesture_for_result(self.index(), result))
+    }
+
+}
+
+impl CyclicGame {
+
+    // Creates a game of 'n' gestures
+    // 'n' must be odd and at least 3, so that every pair of different gestures has a winner
+    const fn new(n: usize) -> CyclicGame {
+        assert!(n >= 3 && n % 2 == 1, "cyclic game must have an odd number of gestures, at least 3");
+        CyclicGame { n }
+    }
+
+    // Result of a game played by 'gesture' against 'opponent'
+    fn play(&self, gesture: usize, opponent: usize) -> RPSResult {
+        // How many places 'gesture' is ahead of 'opponent' in the cycle
+        let distance = (gesture + self.n - opponent) % self.n;
+        if distance == 0 {
             RPSResult::Draw
+        } else if distance <= (self.n - 1) / 2 {
+            RPSResult::Win
         } else {
             RPSResult::Loss
         }
     }
 
+    // A gesture to play against 'opponent' to achieve 'result'
+    // (Winning plays the gesture after 'opponent', and losing plays the one before it)
+    fn gesture_for_result(&self, opponent: usize, result: RPSResult) -> usize {
+        match result {
+            RPSResult::Win => (opponent + 1) % self.n,
+            RPSResult::Draw => opponent,
+            RPSResult::Loss => (opponent + self.n - 1) % self.n,
+        }
+    }
+
+    // Tallys the score for a round, based on:
+    // - the gesture made by the player (gestures 0..n given scores of 1..=n)
+    // - the game result based on the gestures made (Win/Draw/Loss given scores of 6/3/0)
+    #[cfg(test)] // the puzzle uses ScoreTable, this is for experimenting with larger games
+    fn score_round(&self, gesture: usize, opponent: usize) -> i32 {
+        let result_score = match self.play(gesture, opponent) {
+            RPSResult::Win => 6,
+            RPSResult::Draw => 3,
+            RPSResult::Loss => 0,
+        };
+        gesture as i32 + 1 + result_score
+    }
+}
+
+impl Hint {
+
+    // Parses a token of the hint column, as FromStr does
+    // If 'lenient', letters may be in either case, and the full gesture names (in any case) stand for the letter
+    // of that gesture in part 1 (Rock - X, Paper - Y, Scissors - Z). Errors report the token as it was given.
+    fn parse_token(token: &str, lenient: bool) -> Result<Hint, ParseRPSError> {
+        let normalized = if lenient { token.to_ascii_uppercase() } else { token.to_string() };
+        match normalized.as_str() {
+            "X" => Ok(Hint::X),
+            "Y" => Ok(Hint::Y),
+            "Z" => Ok(Hint::Z),
+            "ROCK" if lenient => Ok(Hint::X),
+            "PAPER" if lenient => Ok(Hint::Y),
+            "SCISSORS" if lenient => Ok(Hint::Z),
+            _ => Err(ParseRPSError::InvalidHint(token.to_string())),
+        }
+    }
+
+    // The hint read as the player's choice (part 1)
+    // X - Rock
+    // Y - Paper
+    // Z - Scissors
+    fn as_choice(&self) -> RPSChoice {
+        match *self {
+            Hint::X => RPSChoice::Rock,
+            Hint::Y => RPSChoice::Paper,
+            Hint::Z => RPSChoice::Scissors,
+        }
+    }
+
+    // The hint read as the intended result of the round (part 2)
+    // X - Loss
+    // Y - Draw
+    // Z - Win
+    fn as_result(&self) -> RPSResult {
+        match *self {
+            Hint::X => RPSResult::Loss,
+            Hint::Y => RPSResult::Draw,
+            Hint::Z => RPSResult::Win,
+        }
+    }
+}
+
+impl Round {
+
+    // Converts this Round back into a line of the strategy guide, in the strict puzzle format
+    // eg: "A Y"
+    #[cfg(test)]
+    fn to_line(self) -> String {
+        let opponent = match self.opponent {
+            RPSChoice::Rock => 'A',
+            RPSChoice::Paper => 'B',
+            RPSChoice::Scissors => 'C',
+        };
+        let hint = match self.hint {
+            Hint::X => 'X',
+            Hint::Y => 'Y',
+            Hint::Z => 'Z',
+        };
+        format!("{opponent} {hint}")
+    }
+
+    // Converts a line of the strategy guide into a Round
+    // eg: "A Y"
+    // If 'lenient_tokens', letters may be lowercase and either column may be a full gesture word in any case
+    // eg: "a y", "Rock paper", "rock Y"
+    fn parse_line(s: &str, lenient_tokens: bool) -> Result<Round, ParseRPSError> {
+        let mut c = s.split_whitespace();
+
+        // Opponent's choice depends on A,B,C in file, and the hint on X,Y,Z
+        let opponent = RPSChoice::parse_token(c.next().ok_or(ParseRPSError::MissingColumn)?, lenient_tokens)?;
+        let hint = Hint::parse_token(c.next().ok_or(ParseRPSError::MissingColumn)?, lenient_tokens)?;
+
+        if let Some(extra) = c.next() {
+            return Err(ParseRPSError::ExtraColumn(extra.to_string()));
+        }
+        Ok(Round { opponent, hint })
+    }
+
+    // Scores this round, keeping the choices, result and points
+    fn result(&self, part_2: bool, table: &ScoreTable) -> RoundResult {
+        let player = self.player_choice(part_2);
+        RoundResult {
+            player,
+            opponent: self.opponent,
+            result: player.play_against(self.opponent),
+            points: table.score_round(player, self.opponent),
+        }
+    }
+
+    // The choice the player makes this round, depending on how the hint is interpreted
+    fn player_choice(&self, part_2: bool) -> RPSChoice {
+        if part_2 {
+            self.opponent.choice_for_result(self.hint.as_result())
+        } else {
+            self.hint.as_choice()
+        }
+    }
 }
 
-// Converts string slice 'o' to RPS choice
+// Converts string slice to RPS choice
 // X/A - Rock
 // Y/B - Paper
 // Z/C - Scissors
-fn get_rps_choice(c: &str) -> RPSChoice {
-    match c {
-        "X" | "A" => RPSChoice::Rock,
-        "Y" | "B" => RPSChoice::Paper,
-        "Z"| "C" => RPSChoice::Scissors,
-        _ => panic!("Line contained an invalid value {c}"),
-    }
-}
-
-// Converts string slice 'o' to RPS choice, where string slice 'o' represents the intended result. 
-// X - the choice that 'opp_choice' can beat
-// Y - the choice that 'opp_choice'
-// Z - the choice that 'opp_choice' loses to
-
-fn get_choice_for_desired_outcome(o: &str, opp_choice: RPSChoice) -> RPSChoice {
-    match o {
-        "X" => opp_choice.beats(), // intended LOSS
-        "Y" => opp_choice, // intended DRAW
-        "Z" => opp_choice.loses_to(), // intended WIN
-        _ => panic!("Line contained an invalid value {o}"),
-    }
-}
-
-// Tallys the score for the round, based on:
-// - the choice made by the player (R/P/S given scores of 1/2/3)
-// - the game result based on the choices made (Win/Draw/Loss given scores of 6/3/0)
-fn score_round(user_choice: RPSChoice, opp_choice: RPSChoice) -> i32 {
-    let mut base_score = match user_choice {
-        RPSChoice::Rock => 1,
-        RPSChoice::Paper => 2,
-        RPSChoice::Scissors => 3,
-    };
-
-    base_score += match user_choice.play_against(opp_choice) {
-        RPSResult::Win => 6,
-        RPSResult::Draw => 3,
-        RPSResult::Loss => 0,
-    };
-
-    base_score
-}
\ No newline at end of file
+impl FromStr for RPSChoice {
+    type Err = ParseRPSError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RPSChoice::parse_token(s, false)
+    }
+}
+
+// Converts string slice to the raw hint column
+impl FromStr for Hint {
+    type Err = ParseRPSError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Hint::parse_token(s, false)
+    }
+}
+
+// Converts a line of the strategy guide in the strict puzzle format into a Round
+// eg: "A Y"
+impl FromStr for Round {
+    type Err = ParseRPSError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Round::parse_line(s, false)
+    }
+}
+
+#[cfg(test)]
+impl<R: BufRead> Rounds<R> {
+    // Creates an iterator over the rounds read from 'reader'
+    fn new(reader: R) -> Rounds<R> {
+        Rounds { lines: reader.lines(), line_number: 0, finished: false }
+    }
+}
+
+#[cfg(test)]
+impl<R: BufRead> Iterator for Rounds<R> {
+    type Item = Result<Round, Box<dyn error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        for line in self.lines.by_ref() {
+            self.line_number += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(Box::new(e)));
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return match line.parse() {
+                Ok(round) => Some(Ok(round)),
+                Err(error) => {
+                    self.finished = true;
+                    Some(Err(Box::new(GuideLineError { line: self.line_number, content: line, error })))
+                }
+            };
+        }
+        None
+    }
+}
+
+impl Strategy {
+    // Every strategy, in the order they are compared
+    const ALL: [Strategy; 5] = [
+        Strategy::AlwaysRock,
+        Strategy::AlwaysPaper,
+        Strategy::AlwaysScissors,
+        Strategy::CopyOpponent,
+        Strategy::CounterOpponent,
+    ];
+
+    // The choice this strategy throws against 'opponent'
+    fn choice_against(&self, opponent: RPSChoice) -> RPSChoice {
+        match *self {
+            Strategy::AlwaysRock => RPSChoice::Rock,
+            Strategy::AlwaysPaper => RPSChoice::Paper,
+            Strategy::AlwaysScissors => RPSChoice::Scissors,
+            Strategy::CopyOpponent => opponent,
+            Strategy::CounterOpponent => opponent.loses_to(),
+        }
+    }
+}
+
+impl ScoreTable {
+
+    // Tallys the score for the round, based on:
+    // - the choice made by the player (R/P/S given scores of 1/2/3 by default)
+    // - the game result based on the choices made (Win/Draw/Loss given scores of 6/3/0 by default)
+    fn score_round(&self, user_choice: RPSChoice, opp_choice: RPSChoice) -> i32 {
+        let mut base_score = match user_choice {
+            RPSChoice::Rock => self.rock,
+            RPSChoice::Paper => self.paper,
+            RPSChoice::Scissors => self.scissors,
+        };
+
+        base_score += match user_choice.play_against(opp_choice) {
+            RPSResult::Win => self.win,
+            RPSResult::Draw => self.draw,
+            RPSResult::Loss => self.loss,
+        };
+
+        base_score
+    }
+}
+
+// The puzzle's scoring
+impl Default for ScoreTable {
+    fn default() -> Self {
+        ScoreTable { rock: 1, paper: 2, scissors: 3, win: 6, draw: 3, loss: 0 }
+    }
+}
+
+impl Stats {
+
+    // Adds a single round's result and choice to the tally
+    fn add(&mut self, round_result: &RoundResult) {
+        match round_result.result {
+            RPSResult::Win => self.wins += 1,
+            RPSResult::Draw => self.draws += 1,
+            RPSResult::Loss => self.losses += 1,
+        }
+        match round_result.player {
+            RPSChoice::Rock => self.rock += 1,
+            RPSChoice::Paper => self.paper += 1,
+            RPSChoice::Scissors => self.scissors += 1,
+        }
+    }
+}
+
+impl fmt::Display for RPSChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rock => write!(f, "Rock"),
+            Self::Paper => write!(f, "Paper"),
+            Self::Scissors => write!(f, "Scissors"),
+        }
+    }
+}
+
+impl fmt::Display for RPSResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Win => write!(f, "Win"),
+            Self::Draw => write!(f, "Draw"),
+            Self::Loss => write!(f, "Loss"),
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlwaysRock => write!(f, "Always Rock"),
+            Self::AlwaysPaper => write!(f, "Always Paper"),
+            Self::AlwaysScissors => write!(f, "Always Scissors"),
+            Self::CopyOpponent => write!(f, "Copy opponent"),
+            Self::CounterOpponent => write!(f, "Counter opponent"),
+        }
+    }
+}
+
+// eg: "Opponent Paper vs You Scissors -> Win (+9)"
+impl fmt::Display for RoundResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Opponent {} vs You {} -> {} ({:+})", self.opponent, self.player, self.result, self.points)
+    }
+}
+
+// eg: "Wins 1, Draws 1, Losses 1 | Rock 1, Paper 1, Scissors 1"
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Wins {}, Draws {}, Losses {} | Rock {}, Paper {}, Scissors {}",
+            self.wins, self.draws, self.losses, self.rock, self.paper, self.scissors)
+    }
+}
+
+// Error for a strategy guide line that could not be read as a Round
+#[derive(Debug, PartialEq)]
+enum ParseRPSError {
+    InvalidChoice(String), // token could not be read as a Rock/Paper/Scissors choice
+    InvalidHint(String), // token could not be read as an X/Y/Z hint
+    MissingColumn, // line had fewer than two columns
+    ExtraColumn(String), // line had more than two columns
+}
+
+impl error::Error for ParseRPSError {}
+impl fmt::Display for ParseRPSError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChoice(s) => write!(f, "invalid choice '{s}'"),
+            Self::InvalidHint(s) => write!(f, "invalid hint '{s}'"),
+            Self::MissingColumn => write!(f, "expected two columns"),
+            Self::ExtraColumn(s) => write!(f, "unexpected extra column '{s}'"),
+        }
+    }
+}
+
+// A ParseRPSError along with the (one-indexed) line of the strategy guide it occurred on
+#[derive(Debug, PartialEq)]
+struct GuideLineError {
+    line: usize,
+    content: String, // the malformed line as it appeared in the guide
+    error: ParseRPSError,
+}
+
+impl error::Error for GuideLineError {}
+impl fmt::Display for GuideLineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day 2: line {}: {}", self.line, self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Advent of Code challenge-provided example
+    const EXAMPLE_INPUT: &str = "A Y\nB X\nC Z";
+
+    #[test]
+    fn parse_choices() {
+        // Opponent and player letters both map to choices
+        assert_eq!("A".parse(), Ok(RPSChoice::Rock));
+        assert_eq!("B".parse(), Ok(RPSChoice::Paper));
+        assert_eq!("C".parse(), Ok(RPSChoice::Scissors));
+        assert_eq!("X".parse(), Ok(RPSChoice::Rock));
+        assert_eq!("Y".parse(), Ok(RPSChoice::Paper));
+        assert_eq!("Z".parse(), Ok(RPSChoice::Scissors));
+
+        // Anything else is rejected, carrying the offending token
+        for bad in ["W", "D", "a", "x", "AX", "", " "] {
+            assert_eq!(bad.parse::<RPSChoice>(), Err(ParseRPSError::InvalidChoice(bad.to_string())));
+        }
+    }
+
+    #[test]
+    fn parse_hints() {
+        assert_eq!("X".parse(), Ok(Hint::X));
+        assert_eq!("Y".parse(), Ok(Hint::Y));
+        assert_eq!("Z".parse(), Ok(Hint::Z));
+        assert_eq!(Hint::X.as_result(), RPSResult::Loss);
+        assert_eq!(Hint::Y.as_result(), RPSResult::Draw);
+        assert_eq!(Hint::Z.as_result(), RPSResult::Win);
+
+        // Opponent letters are not valid hints
+        for bad in ["A", "B", "C", "W", "z", ""] {
+            assert_eq!(bad.parse::<Hint>(), Err(ParseRPSError::InvalidHint(bad.to_string())));
+        }
+    }
+
+    #[test]
+    fn line_error_message() {
+        let error = parse_guide("A Y\nW X").unwrap_err();
+        assert_eq!(error, GuideLineError { line: 2, content: "W X".to_string(), error: ParseRPSError::InvalidChoice("W".to_string()) });
+
+        let error = "W X".parse::<Round>().unwrap_err();
+        assert_eq!(GuideLineError { line: 57, content: "W X".to_string(), error }.to_string(), "day 2: line 57: invalid choice 'W'");
+    }
+
+    #[test]
+    fn example_scores() {
+        let rounds = parse_guide(EXAMPLE_INPUT).unwrap();
+        assert_eq!(rounds[0], Round { opponent: RPSChoice::Rock, hint: Hint::Y });
+        assert_eq!(score(&rounds, false, &ScoreTable::default()), 15);
+        assert_eq!(score(&rounds, true, &ScoreTable::default()), 12);
+    }
+
+    #[test]
+    fn malformed_guides() {
+        // Empty input has no rounds, and so scores nothing
+        let rounds = parse_guide("").unwrap();
+        assert!(rounds.is_empty());
+        assert_eq!(score(&rounds, false, &ScoreTable::default()), 0);
+
+        // A line with only one column is rejected
+        assert_eq!(parse_guide("A Y\nB"), Err(GuideLineError { line: 2, content: "B".to_string(), error: ParseRPSError::MissingColumn }));
+        assert_eq!(parse_guide("A Y Z"), Err(GuideLineError { line: 1, content: "A Y Z".to_string(), error: ParseRPSError::ExtraColumn("Z".to_string()) }));
+    }
+
+    #[test]
+    fn custom_score_table() {
+        let table = ScoreTable::default();
+        assert_eq!(table.score_round(RPSChoice::Paper, RPSChoice::Rock), 2 + 6);
+        assert_eq!(table.score_round(RPSChoice::Rock, RPSChoice::Paper), 1);
+        assert_eq!(table.score_round(RPSChoice::Scissors, RPSChoice::Scissors), 3 + 3);
+
+        // House rules: shapes are worth 10/20/30 and a loss costs a point
+        let table = ScoreTable { rock: 10, paper: 20, scissors: 30, win: 5, draw: 0, loss: -1 };
+        assert_eq!(table.score_round(RPSChoice::Paper, RPSChoice::Rock), 20 + 5);
+        assert_eq!(table.score_round(RPSChoice::Rock, RPSChoice::Paper), 10 - 1);
+        assert_eq!(table.score_round(RPSChoice::Scissors, RPSChoice::Scissors), 30);
+
+        // Example part 1 plays Paper (win), Rock (loss), Scissors (draw)
+        // Example part 2 plays Rock (draw), Rock (loss), Rock (win)
+        let rounds = parse_guide(EXAMPLE_INPUT).unwrap();
+        assert_eq!(score(&rounds, false, &table), 25 + 9 + 30);
+        assert_eq!(score(&rounds, true, &table), 10 + 9 + 15);
+
+        // A table where only losses count can go negative
+        let table = ScoreTable { rock: 0, paper: 0, scissors: 0, win: 0, draw: 0, loss: -1 };
+        assert_eq!(score(&rounds, false, &table), -1);
+    }
+
+    #[test]
+    fn detailed_round_results() {
+        let rounds = parse_guide(EXAMPLE_INPUT).unwrap();
+
+        let results = score_rounds_detailed(&rounds, false, &ScoreTable::default());
+        assert_eq!(results, vec![
+            RoundResult { player: RPSChoice::Paper, opponent: RPSChoice::Rock, result: RPSResult::Win, points: 8 },
+            RoundResult { player: RPSChoice::Rock, opponent: RPSChoice::Paper, result: RPSResult::Loss, points: 1 },
+            RoundResult { player: RPSChoice::Scissors, opponent: RPSChoice::Scissors, result: RPSResult::Draw, points: 6 },
+        ]);
+        assert_eq!(results[0].to_string(), "Opponent Rock vs You Paper -> Win (+8)");
+
+        // In part 2, the player's choice is derived from the hint
+        let results = score_rounds_detailed(&rounds, true, &ScoreTable::default());
+        assert_eq!(results[1].to_string(), "Opponent Paper vs You Rock -> Loss (+1)");
+        assert_eq!(results[2].to_string(), "Opponent Scissors vs You Rock -> Win (+7)");
+
+        // Negative points keep their sign
+        let table = ScoreTable { loss: -2, ..ScoreTable::default() };
+        let results = score_rounds_detailed(&rounds, false, &table);
+        assert_eq!(results[1].to_string(), "Opponent Paper vs You Rock -> Loss (-1)");
+    }
+
+    #[test]
+    fn example_stats() {
+        let rounds = parse_guide(EXAMPLE_INPUT).unwrap();
+
+        // Part 1 throws Paper, Rock, Scissors for a win, loss and draw
+        assert_eq!(stats(&rounds, false), Stats { wins: 1, draws: 1, losses: 1, rock: 1, paper: 1, scissors: 1 });
+
+        // Part 2 derives Rock every round, for a draw, loss and win
+        assert_eq!(stats(&rounds, true), Stats { wins: 1, draws: 1, losses: 1, rock: 3, paper: 0, scissors: 0 });
+
+        assert_eq!(stats(&[], true), Stats::default());
+    }
+
+    #[test]
+    fn strict_and_lenient_parsing() {
+        // Example rounds interleaved with blank lines and garbage
+        let input = "\nA Y\n\nQ Q\nB X\n   \nC\nC Z\n\n";
+
+        // Strict mode stops at the first malformed line
+        assert_eq!(parse_guide_with_mode(input, ParseMode::Strict, false), Err(GuideLineError {
+            line: 4,
+            content: "Q Q".to_string(),
+            error: ParseRPSError::InvalidChoice("Q".to_string()),
+        }));
+
+        // Lenient mode keeps the valid rounds and records each skipped line
+        let guide = parse_guide_with_mode(input, ParseMode::Lenient, false).unwrap();
+        assert_eq!(guide.rounds, parse_guide(EXAMPLE_INPUT).unwrap());
+        assert_eq!(score(&guide.rounds, false, &ScoreTable::default()), 15);
+        assert_eq!(score(&guide.rounds, true, &ScoreTable::default()), 12);
+        assert_eq!(guide.skipped, vec![
+            GuideLineError { line: 4, content: "Q Q".to_string(), error: ParseRPSError::InvalidChoice("Q".to_string()) },
+            GuideLineError { line: 7, content: "C".to_string(), error: ParseRPSError::MissingColumn },
+        ]);
+
+        // Blank lines alone are not errors in either mode
+        let blanks = "A Y\n\nB X\nC Z\n\n";
+        assert_eq!(parse_guide_with_mode(blanks, ParseMode::Strict, false).unwrap().rounds.len(), 3);
+        assert!(parse_guide_with_mode(blanks, ParseMode::Lenient, false).unwrap().skipped.is_empty());
+    }
+
+    #[test]
+    fn example_analysis() {
+        let rounds = parse_guide(EXAMPLE_INPUT).unwrap();
+
+        // Best possible throws Paper, Scissors, Rock for 8 + 9 + 7
+        assert_eq!(analyze(&rounds), Analysis { part1: 15, part2: 12, best_possible: 24 });
+        assert_eq!(analyze(&[]), Analysis { part1: 0, part2: 0, best_possible: 0 });
+    }
+
+    // Generates a well-formed strategy guide of 'length' rounds from 'seed'
+    // (simple xorshift, to avoid pulling in a random number crate for tests)
+    fn random_guide(length: usize, mut seed: u64) -> String {
+        let mut lines = Vec::with_capacity(length);
+        for _ in 0..length {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let opponent = ["A", "B", "C"][(seed % 3) as usize];
+            let hint = ["X", "Y", "Z"][((seed / 3) % 3) as usize];
+            lines.push(format!("{opponent} {hint}"));
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn fast_scorer_agrees() {
+        assert_eq!(score_fast(EXAMPLE_INPUT, false), Some(15));
+        assert_eq!(score_fast(EXAMPLE_INPUT, true), Some(12));
+        assert_eq!(score_fast("", false), Some(0));
+        assert_eq!(score_fast("A Y\nB W", false), None);
+        assert_eq!(score_fast("A Y\nB", false), None);
+
+        for seed in 1..50 {
+            let guide = random_guide(200, seed);
+            let analysis = analyze(&parse_guide(&guide).unwrap());
+            assert_eq!(score_fast(&guide, false), Some(analysis.part1));
+            assert_eq!(score_fast(&guide, true), Some(analysis.part2));
+        }
+    }
+
+    // Times the enum-based and lookup-based scorers over a million-round guide
+    // Run with 'cargo test --release bench_scorers -- --ignored --nocapture'
+    #[test]
+    #[ignore]
+    fn bench_scorers() {
+        use std::time::Instant;
+
+        let guide = random_guide(1_000_000, 0x2022);
+
+        let start = Instant::now();
+        let rounds = parse_guide(&guide).unwrap();
+        let slow = (score(&rounds, false, &ScoreTable::default()), score(&rounds, true, &ScoreTable::default()));
+        let slow_time = start.elapsed();
+
+        let start = Instant::now();
+        let fast = (score_fast(&guide, false).unwrap(), score_fast(&guide, true).unwrap());
+        let fast_time = start.elapsed();
+
+        assert_eq!(slow, fast);
+        println!("enum-based: {slow_time:?}, lookup-based: {fast_time:?}");
+    }
+
+    #[test]
+    fn lenient_tokens() {
+        let canonical = analyze(&parse_guide(EXAMPLE_INPUT).unwrap());
+
+        // Mixed-case letters and full words give the same rounds as the canonical letters
+        for input in ["a y\nB x\nc Z", "rock paper\nPAPER Rock\nScissors sCiSsOrS", "Rock y\nb ROCK\nC scissors"] {
+            // Strict tokens reject them
+            assert!(parse_guide_with_mode(input, ParseMode::Strict, false).is_err());
+
+            let guide = parse_guide_with_mode(input, ParseMode::Strict, true).unwrap();
+            assert_eq!(analyze(&guide.rounds), canonical);
+        }
+
+        // Errors still report the token as written
+        assert_eq!(Round::parse_line("stone x", true), Err(ParseRPSError::InvalidChoice("stone".to_string())));
+        assert_eq!(Round::parse_line("a w", true), Err(ParseRPSError::InvalidHint("w".to_string())));
+
+        // Opponent letters are not accepted as hints, whatever their case
+        assert_eq!(Round::parse_line("a b", true), Err(ParseRPSError::InvalidHint("b".to_string())));
+    }
+
+    #[test]
+    fn rps_as_cyclic_game() {
+        let choices = [RPSChoice::Rock, RPSChoice::Paper, RPSChoice::Scissors];
+        assert_eq!(RPSChoice::Rock.choice_for_result(RPSResult::Loss), RPSChoice::Scissors);
+        assert_eq!(RPSChoice::Rock.loses_to(), RPSChoice::Paper);
+        assert_eq!(RPSChoice::Paper.choice_for_result(RPSResult::Loss), RPSChoice::Rock);
+        assert_eq!(RPSChoice::Scissors.choice_for_result(RPSResult::Loss), RPSChoice::Paper);
+        for choice in choices {
+            assert_eq!(choice.play_against(choice.choice_for_result(RPSResult::Loss)), RPSResult::Win);
+            assert_eq!(choice.play_against(choice.loses_to()), RPSResult::Loss);
+            assert_eq!(choice.play_against(choice), RPSResult::Draw);
+            assert_eq!(RPS_GAME.score_round(choice.index(), choice.index()), ScoreTable::default().score_round(choice, choice));
+        }
+    }
+
+    #[test]
+    fn seven_gesture_game() {
+        let game = CyclicGame::new(7);
+        for a in 0..7 {
+            // Gesture i beats gesture i - 1
+            assert_eq!(game.play(a, (a + 6) % 7), RPSResult::Win);
+
+            let mut wins = 0;
+            for b in 0..7 {
+                // Exactly one result holds for each pair, and it is the reverse of the other way around
+                let result = game.play(a, b);
+                let reverse = game.play(b, a);
+                match result {
+                    RPSResult::Win => assert_eq!(reverse, RPSResult::Loss),
+                    RPSResult::Loss => assert_eq!(reverse, RPSResult::Win),
+                    RPSResult::Draw => assert_eq!(a, b),
+                }
+                if result == RPSResult::Win {
+                    wins += 1;
+                }
+
+                // Part 2 outcome forcing works for every opponent and result
+                for wanted in [RPSResult::Win, RPSResult::Draw, RPSResult::Loss] {
+                    assert_eq!(game.play(game.gesture_for_result(b, wanted), b), wanted);
+                }
+            }
+            // Every gesture beats as many gestures as it loses to
+            assert_eq!(wins, 3);
+        }
+        assert_eq!(game.score_round(6, 5), 7 + 6);
+        assert_eq!(game.score_round(0, 4), 1 + 6);
+        assert_eq!(game.score_round(0, 3), 1);
+        assert_eq!(game.score_round(2, 2), 3 + 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn even_cyclic_game() {
+        CyclicGame::new(4);
+    }
+
+    #[test]
+    fn streaming_rounds() {
+        use std::io::Cursor;
+
+        // Streamed rounds match the parsed ones, skipping blank lines
+        let rounds: Vec<Round> = Rounds::new(Cursor::new("A Y\n\nB X\nC Z\n")).collect::<Result<_, _>>().unwrap();
+        assert_eq!(rounds, parse_guide(EXAMPLE_INPUT).unwrap());
+        assert_eq!(score_reader(Cursor::new(EXAMPLE_INPUT), false, &ScoreTable::default()).unwrap(), 15);
+        assert_eq!(score_reader(Cursor::new(EXAMPLE_INPUT), true, &ScoreTable::default()).unwrap(), 12);
+
+        // The scorer takes owned rounds as well as borrowed ones
+        assert_eq!(score(rounds.into_iter().rev(), false, &ScoreTable::default()), 15);
+
+        // An error mid-stream is yielded with its line number, and iteration stops after it
+        let mut stream = Rounds::new(Cursor::new("A Y\n\nB Q\nC Z\n"));
+        assert_eq!(stream.next().unwrap().unwrap(), Round { opponent: RPSChoice::Rock, hint: Hint::Y });
+        let error = stream.next().unwrap().unwrap_err();
+        assert_eq!(error.downcast_ref::<GuideLineError>(), Some(&GuideLineError {
+            line: 3,
+            content: "B Q".to_string(),
+            error: ParseRPSError::InvalidHint("Q".to_string()),
+        }));
+        assert!(stream.next().is_none());
+        assert!(stream.next().is_none());
+
+        let error = score_reader(Cursor::new("A Y\nB Q\nC Z"), false, &ScoreTable::default()).unwrap_err();
+        assert_eq!(error.to_string(), "day 2: line 2: invalid hint 'Q'");
+    }
+
+    #[test]
+    fn csv_export() {
+        let rounds = parse_guide(EXAMPLE_INPUT).unwrap();
+
+        let mut csv = Vec::new();
+        write_csv(&score_rounds_detailed(&rounds, false, &ScoreTable::default()), &mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "round,opponent,player,outcome,points\n\
+            1,Rock,Paper,Win,8\n\
+            2,Paper,Rock,Loss,1\n\
+            3,Scissors,Scissors,Draw,6\n");
+
+        let mut csv = Vec::new();
+        write_csv(&score_rounds_detailed(&rounds, true, &ScoreTable::default()), &mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "round,opponent,player,outcome,points\n\
+            1,Rock,Rock,Draw,4\n\
+            2,Paper,Rock,Loss,1\n\
+            3,Scissors,Rock,Win,7\n");
+
+        // No rounds writes just the header
+        let mut csv = Vec::new();
+        write_csv(&[], &mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "round,opponent,player,outcome,points\n");
+
+        assert_eq!(dump_path_for_part("day2-rounds.csv", false), "day2-rounds-part1.csv");
+        assert_eq!(dump_path_for_part("out/rounds", true), "out/rounds-part2");
+    }
+
+    #[test]
+    fn example_strategies() {
+        // Opponent throws Rock, Paper, Scissors
+        let rounds = parse_guide(EXAMPLE_INPUT).unwrap();
+        assert_eq!(simulate_strategy(&rounds, Strategy::AlwaysRock), 4 + 1 + 7);
+        assert_eq!(simulate_strategy(&rounds, Strategy::AlwaysPaper), 8 + 5 + 2);
+        assert_eq!(simulate_strategy(&rounds, Strategy::AlwaysScissors), 3 + 9 + 6);
+        assert_eq!(simulate_strategy(&rounds, Strategy::CopyOpponent), 4 + 5 + 6);
+        assert_eq!(simulate_strategy(&rounds, Strategy::CounterOpponent), 8 + 9 + 7);
+
+        // Strategies ignore the hint column
+        let rounds = parse_guide("A X\nB X\nC X").unwrap();
+        assert_eq!(simulate_strategy(&rounds, Strategy::AlwaysScissors), 18);
+    }
+
+    #[test]
+    fn guide_round_trip() {
+        assert_eq!(Round { opponent: RPSChoice::Paper, hint: Hint::Z }.to_line(), "B Z");
+
+        // Serializing parsed guides gives back the same bytes (plus a trailing newline)
+        let guides = [EXAMPLE_INPUT.to_string(), random_guide(500, 7), random_guide(500, 99), String::new()];
+        for guide in guides {
+            let rounds = parse_guide(&guide).unwrap();
+            let mut written = Vec::new();
+            write_guide(&rounds, &mut written).unwrap();
+            let written = String::from_utf8(written).unwrap();
+
+            let expected = if guide.is_empty() { guide.clone() } else { format!("{guide}\n") };
+            assert_eq!(written, expected);
+            assert_eq!(parse_guide(&written).unwrap(), rounds);
+        }
+    }
+
+    #[test]
+    fn example_answers() {
+        let answers = solve(EXAMPLE_INPUT, &Options::default()).unwrap();
+        assert_eq!(answers.part_1, "15");
+        assert_eq!(answers.part_2, "12");
+        assert!(answers.details.is_empty());
+
+        // Verbose details cover each round of both parts, their stats, and the strategy table
+        let answers = solve(EXAMPLE_INPUT, &Options { verbose: true, ..Options::default() }).unwrap();
+        assert_eq!(answers.details.len(), 2 * (1 + 3 + 1) + 3 + Strategy::ALL.len());
+        assert_eq!(answers.details[1], "#1: Opponent Rock vs You Paper -> Win (+8)");
+
+        assert_eq!(solve("A Y\nB Q", &Options::default()).unwrap_err().to_string(), "day 2: line 2: invalid hint 'Q'");
+
+        // With --lenient, malformed lines are skipped, and listed beneath the count of them in verbose mode
+        let input = "\nA Y\n\nQ Q\nB X\n   \nC\nC Z\n\n";
+        let answers = solve(input, &Options { lenient: true, ..Options::default() }).unwrap();
+        assert_eq!((answers.part_1.as_str(), answers.part_2.as_str()), ("15", "12"));
+        let answers = solve(input, &Options { lenient: true, verbose: true, ..Options::default() }).unwrap();
+        assert_eq!(answers.details[..3], [
+            "Skipped 2 malformed line(s)",
+            "  day 2: line 4: invalid choice 'Q'",
+            "  day 2: line 7: expected two columns",
+        ]);
+
+        // It also accepts lowercase letters and gesture words
+        assert!(solve("a y\nROCK paper", &Options::default()).is_err());
+        let answers = solve("a y\nROCK paper", &Options { lenient: true, ..Options::default() }).unwrap();
+        assert_eq!((answers.part_1.as_str(), answers.part_2.as_str()), ("16", "8"));
+    }
+}
@@ -6,114 +6,1149 @@
 
 
 use super::*;
+use std::{borrow::Cow, fmt, io, ops::RangeInclusive, str::FromStr};
+
+// Number of rucksacks in each group of elves, as per specification of puzzle (part 2)
+const BADGE_GROUP_SIZE: usize = 3;
 
 // Rucksack 
 // A sack of items (as letter characters a-zA-Z) in either the left or right comparment of the sack.
 // The two compartments are valid and even in length. To split evenly, the total number of items must even.
-struct Rucksack {
-    left_compartment : String,
-    right_compartment : String,
+// The items either borrow from the input the rucksack was read from, or own the line read for it.
+#[derive(Debug)]
+struct Rucksack<'a> {
+    items : Cow<'a, str>, // every item, left compartment first
+    left_end : usize, // byte index just past the last item of the left compartment
+    right_start : usize, // byte index of the first item of the right compartment
+    line : usize, // one-indexed line of input the rucksack was read from
+}
+
+// The misplaced item found in a rucksack (for verbose output)
+#[derive(Debug, PartialEq)]
+struct RucksackReport {
+    line: usize, // one-indexed line of the rucksack
+    item: Option<char>, // item in both compartments, if any
+    priority: u32, // priority of 'item', or 0 if there is none
+}
+
+// The badge found for a group of rucksacks
+#[derive(Debug, PartialEq)]
+struct Badge {
+    group_index: usize, // zero-indexed position of the group in the input
+    item: char, // item common to every rucksack in the group
+    priority: u32,
+    line_range: RangeInclusive<usize>, // one-indexed lines of the first and last rucksacks in the group
+}
+
+// Every rucksack's misplaced item and every group's badge, alongside the answers to both parts
+#[derive(Debug, PartialEq)]
+struct Analysis {
+    rucksacks: Vec<RucksackReport>,
+    badges: Vec<Badge>,
+    part_1: u32, // sum of misplaced item priorities
+    part_2: u32, // sum of badge priorities
+}
+
+// How a rucksack with an odd number of items is split into compartments (chosen with --split)
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub enum SplitPolicy {
+    #[default]
+    Strict, // fail with a Day3Error::OddLengthRucksack, as the puzzle never has odd rucksacks
+    DropMiddle, // leave the middle item out of both compartments
+    LeftBiased, // put the middle item in the left compartment
+}
+
+// How the items common to several strings are found
+// Groups always use bitmasks, so they can be built up one rucksack at a time.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+enum Intersection {
+    #[default]
+    Bitmask, // OR each string's items into a bitmask, then AND the bitmasks (faster at every length in bench_intersections)
+    #[cfg(test)] // only compared against Bitmask in tests
+    SortedMerge, // sort and deduplicate each string's priorities, then merge-intersect the sorted lists
+}
+
+// Rucksacks being collected into a group, to find the badge item they have in common
+// Only the items common to the rucksacks so far are kept, so a group takes the same memory however long its rucksacks are.
+struct Group<P: Priority> {
+    size: usize, // number of rucksacks in a complete group
+    collected: usize, // number of rucksacks collected so far
+    common: P::Set, // items common to every rucksack collected so far
+    starting_line: usize, // one-indexed line of the first rucksack collected
+    index: usize, // zero-indexed position of the group in the input
+}
+
+// Iterator over the Rucksacks in any BufRead, one per line
+// Blank lines are skipped. Errors are yielded with their line number, after which iteration stops.
+struct Rucksacks<R: BufRead> {
+    lines: io::Lines<R>,
+    policy: SplitPolicy, // how odd-length rucksacks are split
+    line_number: usize, // one-indexed number of the last line read
+    finished: bool, // whether an error has ended iteration
 }
 
 
 // Run challenge.
 // Main entry point to day 3 challenge.
-pub fn run(part_2: bool) -> Result<(),Box<dyn error::Error>> {
-
-    let mut priority_sum = 0;
-
-    // Load data from file into buffer and iterate over lines
-    let f = File::open("input/day3input.txt")?;
-    let buf = BufReader::new(f);
-
-    if part_2 {
-        // Part 2
-        // Read in rucksacks as groups of 3, and find the one letters that all three of those rucksacks have in common.
-        // Create a sum of the priorities of those letters.
-        // TODO: not possible for challenge input, but add error handling for if number of rucksacks%3 != 0
-        let mut lines = buf.lines();
-        while let (Some(a), Some(b), Some(c)) = (lines.next(), lines.next(), lines.next()){
-            let (a,b,c) = (a?, b?, c?);
-
-            // Get first identifiable common character between the 3 rucksacks
-            let common = get_alphabet_chars_in_common(&a, &b);
-            let common = get_alphabet_chars_in_common(&common, &c);
-
-            priority_sum += match common.chars().next() {// According to specification, there should only be one, so we return the 1st
-                Some(i) => get_priority(i),
-                None => 0,
-            }
+// Both parts are answered from a single read of the input.
+pub(crate) fn run(options: &Options) -> Result<Answers, Box<dyn error::Error>> {
+
+    // Rucksacks are read as groups of 3 (or --group-size) for part 2, and odd-length ones are split by --split
+    let group_size = options.group_size.unwrap_or(BADGE_GROUP_SIZE);
+    let policy = match &options.split {
+        Some(name) => name.parse::<SplitPolicy>()?,
+        None => SplitPolicy::default(),
+    };
+
+    // Load data from file once, for every mode to share
+    let mut input = String::new();
+    File::open("input/day3input.txt")?.read_to_string(&mut input)?;
+
+    // In validate mode, check the whole input first so every problem is reported at once
+    if options.validate {
+        validate::<Letters>(&input, group_size, policy).map_err(Day3Errors)?;
+    }
+
+    // In verbose mode, keep every rucksack's result to show a breakdown beneath the answers
+    if options.verbose {
+        let analysis = analyze::<Letters>(&input, group_size, policy)?;
+        let mut details = analysis.rucksack_table();
+        details.extend(analysis.group_table());
+        return Ok(Answers { part_1: analysis.part_1.to_string(), part_2: analysis.part_2.to_string(), notes: Vec::new(), details });
+    }
+
+    // Otherwise iterate over its rucksacks without keeping them
+    let (part_1, part_2) = solve_with_policy(input.as_bytes(), group_size, policy)?;
+    Ok(Answers { part_1: part_1.to_string(), part_2: part_2.to_string(), notes: Vec::new(), details: Vec::new() })
+}
+
+impl<'a> Rucksack<'a> {
+
+    // Create a new Rucksack 
+    // 'item_string' a list of items as a string of upper and lower case letters, either borrowed or owned.
+    // 'item_string' should have an even number of characters to split in half, otherwise 'policy' decides how it is split.
+    // It is split by character count rather than byte length, so a multibyte character is never cut in two
+    // (and is then reported as an invalid item rather than panicking).
+    // 'line' is the one-indexed line 'item_string' was read from, used in errors.
+    fn new(item_string: impl Into<Cow<'a, str>>, line: usize, policy: SplitPolicy) -> Result<Rucksack<'a>, Day3Error> {
+        let items = item_string.into();
+
+        // Puzzle input is all ASCII, where characters and bytes are the same, so counting characters can be skipped
+        let ascii = items.is_ascii();
+        let len = if ascii { items.len() } else { items.chars().count() };
+
+        // Number of characters before the end of the left compartment, and before the start of the right
+        let half = len / 2;
+        let (left_end, right_start) = match (len.is_multiple_of(2), policy) {
+            (true, _) => (half, half),
+            (false, SplitPolicy::Strict) => return Err(Day3Error::OddLengthRucksack { line, len }),
+            (false, SplitPolicy::DropMiddle) => (half, half + 1),
+            (false, SplitPolicy::LeftBiased) => (half + 1, half + 1),
+        };
+
+        // Converts a number of characters into a byte index
+        let byte_index = |chars: usize| if ascii {
+            chars
+        } else {
+            items.char_indices().nth(chars).map_or(items.len(), |(i, _)| i)
+        };
+        let (left_end, right_start) = (byte_index(left_end), byte_index(right_start));
+        Ok(Rucksack { items, left_end, right_start, line })
+    }
+
+    // Items in the left compartment
+    fn left(&self) -> &str {
+        &self.items[..self.left_end]
+    }
+
+    // Items in the right compartment
+    fn right(&self) -> &str {
+        &self.items[self.right_start..]
+    }
+
+    // Find every item type in common between the two compartments, in priority order
+    // Returns a Day3Error::InvalidItem if either compartment contains a non-alphabetic character
+    fn common_items<P: Priority>(&self) -> Result<Vec<char>, Day3Error> {
+        Intersection::default().common_items::<P>(&[self.left(), self.right()]).map_err(|e| e.on_line(self.line))
+    }
+
+}
+
+impl FromStr for SplitPolicy {
+    type Err = Day3Error;
+
+    // Parses a policy by its --split name
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(SplitPolicy::Strict),
+            "drop-middle" => Ok(SplitPolicy::DropMiddle),
+            "left-biased" => Ok(SplitPolicy::LeftBiased),
+            _ => Err(Day3Error::UnknownSplitPolicy { name: s.to_string() }),
         }
-    } else {
-        // Part 1
-        // For each rucksack, find the unique char that the two halves have in common.
-        // Get sum of the priorities of items in common between halves
-            for line in buf.lines() {
-            let rucksack = Rucksack::new(line?)?;
-            priority_sum += match rucksack.get_misplaced_item() {
-                Some(i) => get_priority(i),
-                None => 0,
+    }
+}
+
+impl<R: BufRead> Rucksacks<R> {
+    // Creates an iterator over the rucksacks read from 'reader', split strictly
+    fn new(reader: R) -> Rucksacks<R> {
+        Rucksacks::with_policy(reader, SplitPolicy::default())
+    }
+
+    // Creates an iterator over the rucksacks read from 'reader', split according to 'policy'
+    fn with_policy(reader: R, policy: SplitPolicy) -> Rucksacks<R> {
+        Rucksacks { lines: reader.lines(), policy, line_number: 0, finished: false }
+    }
+}
+
+impl<R: BufRead> Iterator for Rucksacks<R> {
+    type Item = Result<Rucksack<'static>, Box<dyn error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        for line in self.lines.by_ref() {
+            self.line_number += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(Box::new(e)));
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return match Rucksack::new(line, self.line_number, self.policy) {
+                Ok(rucksack) => Some(Ok(rucksack)),
+                Err(e) => {
+                    self.finished = true;
+                    Some(Err(Box::new(e)))
+                }
             };
         }
+        None
     }
-    let part = if part_2 {2} else {1};
-    println!("Result for day 3-{part} = {priority_sum}");
-    Ok(())
 }
 
-impl  Rucksack {
+// Iterates over the Rucksacks in the lines of 'input', borrowing their items from it
+// Blank lines are skipped, as with Rucksacks, and odd-length rucksacks are split according to 'policy'.
+fn parse_rucksacks(input: &str, policy: SplitPolicy) -> impl Iterator<Item = Result<Rucksack<'_>, Box<dyn error::Error>>> {
+    input.lines().enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(move |(i, line)| Rucksack::new(line, i + 1, policy).map_err(|e| e.into()))
+}
 
-    // Create a new Rucksack 
-    // 'item_string' a list of items as a String of upper and lower case letters.
-    // 'item_string' must have an even number of characters to split in half.
-    fn new(mut item_string: String) -> Result<Rucksack, Error> {
-        // TODO: tidy
-        let len = item_string.len();
-        if len % 2 != 0 {
-            Err(std::io::Error::new(ErrorKind::Other,"Rucksack has uneven length of numbers and cannot be equally split."))
-        } else {
-            let right_compartment = item_string.split_off(len / 2);
+// Finds the item in both compartments of 'rucksack'
+// According to specification, there is exactly one such item, so a rucksack with more returns a Day3Error::MultipleCommonItems
+fn misplaced_item<P: Priority>(rucksack: &Rucksack) -> Result<RucksackReport, Day3Error> {
+    let common = rucksack.common_items::<P>()?;
+    if common.len() > 1 {
+        return Err(Day3Error::MultipleCommonItems { line: rucksack.line, items: common });
+    }
+    let item = common.first().copied();
+    Ok(RucksackReport {
+        line: rucksack.line,
+        item,
+        priority: match item {
+            Some(c) => get_priority::<P>(c)?,
+            None => 0,
+        },
+    })
+}
 
-            Ok(Rucksack {
-                left_compartment: item_string,
-                right_compartment,
-            })
+impl<P: Priority> Group<P> {
+    // Creates an empty group, which is complete once it has 'size' rucksacks
+    // Groups of fewer than 2 rucksacks are a Day3Error::InvalidGroupSize.
+    fn new(size: usize) -> Result<Group<P>, Day3Error> {
+        if size < 2 {
+            return Err(Day3Error::InvalidGroupSize { group_size: size });
         }
+        Ok(Group { size, collected: 0, common: P::Set::ALL, starting_line: 0, index: 0 })
     }
 
-    // Find character in common between two compartments
-    fn get_misplaced_item(&self) -> Option<char> {
-        let common = get_alphabet_chars_in_common(&self.left_compartment, &self.right_compartment);
-        common.chars().next() // According to specification, there should only be one, so we return the 1st
+    // Adds 'rucksack' to the group, and if that completes it, returns its badge and starts the next group
+    // A complete group with no item in common is a Day3Error::NoCommonItem.
+    fn add(&mut self, rucksack: &Rucksack) -> Result<Option<Badge>, Day3Error> {
+        let set = item_set::<P>(&rucksack.items).map_err(|e| e.on_line(rucksack.line))?;
+        self.add_set(set, rucksack.line)
     }
 
+    // Adds the rucksack with items 'set' on (one-indexed) line 'line' to the group, as with 'add'
+    // The next group is started whether or not this one has a badge.
+    fn add_set(&mut self, set: P::Set, line: usize) -> Result<Option<Badge>, Day3Error> {
+        if self.collected == 0 {
+            self.starting_line = line;
+        }
+        self.common = self.common.and(set);
+        self.collected += 1;
+        if self.collected < self.size {
+            return Ok(None);
+        }
+
+        // Get first identifiable common character between the rucksacks of the group
+        // According to specification, there should only be one, so we use the 1st
+        let item = items::<P>(self.common).next();
+        let group_index = self.index;
+        self.collected = 0;
+        self.common = P::Set::ALL;
+        self.index += 1;
+
+        let item = item.ok_or(Day3Error::NoCommonItem { starting_line: self.starting_line, group_size: self.size })?;
+        Ok(Some(Badge {
+            group_index,
+            item,
+            priority: get_priority::<P>(item)?,
+            line_range: self.starting_line..=line,
+        }))
+    }
+
+    // Checks that no rucksacks are left over, which would be a Day3Error::IncompleteGroup
+    fn finish(&self) -> Result<(), Day3Error> {
+        if self.collected > 0 {
+            return Err(Day3Error::IncompleteGroup { starting_line: self.starting_line, got: self.collected });
+        }
+        Ok(())
+    }
 }
 
-// Returns the 'priority' of a item character (as per specification of puzzle)
+// Finds the item in both compartments of each of 'rucksacks'
+fn misplaced_items<'a, P: Priority, I>(rucksacks: I) -> Result<Vec<RucksackReport>, Box<dyn error::Error>>
+where I: IntoIterator<Item = Result<Rucksack<'a>, Box<dyn error::Error>>> {
+    let mut reports = Vec::new();
+    for rucksack in rucksacks {
+        reports.push(misplaced_item::<P>(&rucksack?)?);
+    }
+    Ok(reports)
+}
+
+// Takes 'rucksacks' in groups of 'group_size', and finds the one item each group has in common
+fn badges<'a, P: Priority, I>(rucksacks: I, group_size: usize) -> Result<Vec<Badge>, Box<dyn error::Error>>
+where I: IntoIterator<Item = Result<Rucksack<'a>, Box<dyn error::Error>>> {
+    let mut group = Group::<P>::new(group_size)?;
+    let mut reports = Vec::new();
+    for rucksack in rucksacks {
+        reports.extend(group.add(&rucksack?)?);
+    }
+    group.finish()?;
+    Ok(reports)
+}
+
+// Answers both parts for the rucksack list read from 'reader', in a single pass (see 'sum_priorities')
+// Part 1 is the sum of the priorities of each rucksack's misplaced item, and part 2 of each badge, in groups of 'group_size'.
+pub fn solve<R: BufRead>(reader: R, group_size: usize) -> Result<(u32, u32), Box<dyn error::Error>> {
+    sum_priorities::<Letters, _>(Rucksacks::new(reader), group_size)
+}
+
+// Answers both parts as 'solve' does, splitting odd-length rucksacks according to 'policy'
+pub fn solve_with_policy<R: BufRead>(reader: R, group_size: usize, policy: SplitPolicy) -> Result<(u32, u32), Box<dyn error::Error>> {
+    sum_priorities::<Letters, _>(Rucksacks::with_policy(reader, policy), group_size)
+}
+
+// Answers part 1 alone for the rucksack list 'input': the sum of the priorities of each rucksack's misplaced item
+pub fn part1(input: &str) -> Result<u32, Box<dyn error::Error>> {
+    Ok(misplaced_items::<Letters, _>(parse_rucksacks(input, SplitPolicy::default()))?.iter().map(|r| r.priority).sum())
+}
+
+// Answers part 2 alone for the rucksack list 'input': the sum of the priorities of each group of 3's badge
+pub fn part2(input: &str) -> Result<u32, Box<dyn error::Error>> {
+    Ok(badges::<Letters, _>(parse_rucksacks(input, SplitPolicy::default()), BADGE_GROUP_SIZE)?.iter().map(|b| b.priority).sum())
+}
+
+// Sums the priorities of each of 'rucksacks' misplaced items (part 1), and of the badges of each group of 'group_size' (part 2),
+// in a single pass over 'rucksacks'
+fn sum_priorities<'a, P: Priority, I>(rucksacks: I, group_size: usize) -> Result<(u32, u32), Box<dyn error::Error>>
+where I: IntoIterator<Item = Result<Rucksack<'a>, Box<dyn error::Error>>> {
+    let mut group = Group::<P>::new(group_size)?;
+    let (mut part_1, mut part_2) = (0, 0);
+    for rucksack in rucksacks {
+        let rucksack = rucksack?;
+        part_1 += misplaced_item::<P>(&rucksack)?.priority;
+        if let Some(badge) = group.add(&rucksack)? {
+            part_2 += badge.priority;
+        }
+    }
+    group.finish()?;
+    Ok((part_1, part_2))
+}
+
+// Checks every line of 'input', returning its rucksacks if they are all valid, or every problem found if not
+// Unlike the solvers, this carries on past the first problem, so a line can report several. Lines with invalid items or an
+// odd length still count towards their group, where they are treated as sharing every item. Odd lengths are only a problem
+// when 'policy' is SplitPolicy::Strict.
+fn validate<P: Priority>(input: &str, group_size: usize, policy: SplitPolicy) -> Result<Vec<Rucksack<'_>>, Vec<Day3Error>> {
+    let mut group = Group::<P>::new(group_size).map_err(|e| vec![e])?;
+    let mut rucksacks = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let set = item_set::<P>(line).map_err(|e| e.on_line(i + 1));
+        match Rucksack::new(line, i + 1, policy) {
+            Ok(rucksack) if set.is_ok() => match misplaced_item::<P>(&rucksack) {
+                Ok(_) => rucksacks.push(rucksack),
+                Err(e) => errors.push(e),
+            },
+            Ok(_) => (),
+            Err(e) => errors.push(e),
+        }
+        let set = set.unwrap_or_else(|e| {
+            errors.push(e);
+            P::Set::ALL
+        });
+        if let Err(e) = group.add_set(set, i + 1) {
+            errors.push(e);
+        }
+    }
+    if let Err(e) = group.finish() {
+        errors.push(e);
+    }
+
+    if errors.is_empty() {
+        Ok(rucksacks)
+    } else {
+        Err(errors)
+    }
+}
+
+// Finds the misplaced item of every rucksack in 'input', and the badge of every group of 'group_size' rucksacks
+// Odd-length rucksacks are split according to 'policy'.
+fn analyze<P: Priority>(input: &str, group_size: usize, policy: SplitPolicy) -> Result<Analysis, Box<dyn error::Error>> {
+    // Rucksacks borrow from 'input', so reading them again for each part is cheap
+    let rucksacks = misplaced_items::<P, _>(parse_rucksacks(input, policy))?;
+    let badges = badges::<P, _>(parse_rucksacks(input, policy), group_size)?;
+    Ok(Analysis {
+        part_1: rucksacks.iter().map(|r| r.priority).sum(),
+        part_2: badges.iter().map(|b| b.priority).sum(),
+        rucksacks,
+        badges,
+    })
+}
+
+impl Analysis {
+    // Table of the misplaced item of each rucksack, one row per line
+    fn rucksack_table(&self) -> Vec<String> {
+        let mut table = vec!["Line | Item | Priority".to_string()];
+        for report in &self.rucksacks {
+            let item = report.item.map_or("-".to_string(), |c| c.to_string());
+            table.push(format!("{:>4} | {item:>4} | {:>8}", report.line, report.priority));
+        }
+        table
+    }
+
+    // Table of the badge of each group, one row per group
+    fn group_table(&self) -> Vec<String> {
+        let mut table = vec!["Group | Lines   | Badge | Priority".to_string()];
+        for badge in &self.badges {
+            let lines = format!("{}-{}", badge.line_range.start(), badge.line_range.end());
+            table.push(format!("{:>5} | {lines:>7} | {:>5} | {:>8}", badge.group_index, badge.item, badge.priority));
+        }
+        table
+    }
+}
+
+// Mapping between item characters and their priorities, from 1 up to ITEM_TYPES
+// Item sets are bitmasks with bit (priority - 1) set for each item present, so 'Set' needs at least ITEM_TYPES bits
+trait Priority {
+    type Set: ItemSet;
+    const ITEM_TYPES: u32; // number of item types, and so the highest priority
+
+    // Returns the priority of item 'c', or None if it is not an item
+    fn priority(c: char) -> Option<u32>;
+
+    // Returns the item with the given 'priority' (the inverse of 'priority')
+    fn item(priority: u32) -> char;
+}
+
+// Bitmask of item types, with bit (priority - 1) set for each item present
+trait ItemSet: Copy {
+    const EMPTY: Self;
+    const ALL: Self; // every bit set
+    const BITS: u32;
+
+    fn with(self, bit: u32) -> Self;
+    fn and(self, other: Self) -> Self;
+    fn contains(self, bit: u32) -> bool;
+}
+
+impl ItemSet for u64 {
+    const EMPTY: u64 = 0;
+    const ALL: u64 = u64::MAX;
+    const BITS: u32 = u64::BITS;
+
+    fn with(self, bit: u32) -> u64 { self | 1 << bit }
+    fn and(self, other: u64) -> u64 { self & other }
+    fn contains(self, bit: u32) -> bool { self & (1 << bit) != 0 }
+}
+
+// For mappings of more than 64 item types
+impl ItemSet for u128 {
+    const EMPTY: u128 = 0;
+    const ALL: u128 = u128::MAX;
+    const BITS: u32 = u128::BITS;
+
+    fn with(self, bit: u32) -> u128 { self | 1 << bit }
+    fn and(self, other: u128) -> u128 { self & other }
+    fn contains(self, bit: u32) -> bool { self & (1 << bit) != 0 }
+}
+
+// The puzzle's mapping of letter items to priorities
 // a-z ~ 1-26
 // A-Z ~ 27-52
-fn get_priority(c: char) -> u32 {
-    match  c {
-        'a'..='z' => (c as u32 - 'a' as u32) + 1,
-        'A'..='Z' => (c as u32 - 'A' as u32) + 27,
-        _ => panic!("Non-English alphabetical character {c} found in input string."),
+struct Letters;
+
+impl Priority for Letters {
+    type Set = u64;
+    const ITEM_TYPES: u32 = 52;
+
+    fn priority(c: char) -> Option<u32> {
+        match  c {
+            'a'..='z' => Some((c as u32 - 'a' as u32) + 1),
+            'A'..='Z' => Some((c as u32 - 'A' as u32) + 27),
+            _ => None,
+        }
+    }
+
+    fn item(priority: u32) -> char {
+        match priority {
+            1..=26 => char::from(b'a' + (priority - 1) as u8),
+            27..=52 => char::from(b'A' + (priority - 27) as u8),
+            _ => panic!("Priority {priority} does not correspond to an item."),
+        }
     }
 }
 
-// For two strings, return all characters those strings have in common as another String
-// Stores integer as indices in a boolean array to keep time ~ O(n)
-fn get_alphabet_chars_in_common(s1: &String, s2: &String) -> String {
+// Returns the 'priority' of a item character, according to mapping 'P'
+// Any other character is a Day3Error::InvalidItem
+fn get_priority<P: Priority>(c: char) -> Result<u32, Day3Error> {
+    P::priority(c).ok_or(Day3Error::InvalidItem { line: None, ch: c })
+}
+
+// Returns the set of item types in string 's', according to mapping 'P'
+fn item_set<P: Priority>(s: &str) -> Result<P::Set, Day3Error> {
+    debug_assert!(P::ITEM_TYPES <= P::Set::BITS, "item set is too small for every item type");
+    s.chars().try_fold(P::Set::EMPTY, |set, c| Ok(set.with(get_priority::<P>(c)? - 1)))
+}
 
-    let mut existing_items = [false; 52];
-    let mut common = String::new(); 
+// Returns the items common to every set in 'sets' (all items if 'sets' is empty)
+fn common<S: ItemSet>(sets: &[S]) -> S {
+    sets.iter().fold(S::ALL, |acc, set| acc.and(*set))
+}
 
-    for c in s1.chars() {
-        existing_items[get_priority(c) as usize - 1] = true;
+// Iterates over the item characters in the bitmask 'set', in priority order according to mapping 'P'
+fn items<P: Priority>(set: P::Set) -> impl Iterator<Item = char> {
+    (0..P::ITEM_TYPES).filter(move |bit| set.contains(*bit)).map(|bit| P::item(bit + 1))
+}
+
+impl Intersection {
+    // Returns every item common to all of 'parts', in priority order (all items if 'parts' is empty)
+    // Returns a Day3Error::InvalidItem for the first character that is not an item, whichever strategy is used
+    fn common_items<P: Priority>(self, parts: &[&str]) -> Result<Vec<char>, Day3Error> {
+        match self {
+            Intersection::Bitmask => bitmask_intersection::<P>(parts),
+            #[cfg(test)]
+            Intersection::SortedMerge => sorted_merge_intersection::<P>(parts),
+        }
     }
-    for c in s2.chars() {
-        if existing_items[get_priority(c) as usize - 1] {
-            common.push(c);
+}
+
+// Intersection::Bitmask
+fn bitmask_intersection<P: Priority>(parts: &[&str]) -> Result<Vec<char>, Day3Error> {
+    let sets = parts.iter().map(|part| item_set::<P>(part)).collect::<Result<Vec<P::Set>, Day3Error>>()?;
+    Ok(items::<P>(common(&sets)).collect())
+}
+
+// Intersection::SortedMerge
+#[cfg(test)]
+fn sorted_merge_intersection<P: Priority>(parts: &[&str]) -> Result<Vec<char>, Day3Error> {
+    let mut common: Vec<u32> = (1..=P::ITEM_TYPES).collect();
+    for part in parts {
+        let mut priorities = part.chars().map(get_priority::<P>).collect::<Result<Vec<u32>, Day3Error>>()?;
+        priorities.sort_unstable();
+        priorities.dedup();
+
+        // Keep only the priorities in both sorted lists, walking through them together
+        let (mut i, mut j) = (0, 0);
+        let mut merged = Vec::with_capacity(common.len().min(priorities.len()));
+        while i < common.len() && j < priorities.len() {
+            match common[i].cmp(&priorities[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    merged.push(common[i]);
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        common = merged;
+    }
+    Ok(common.into_iter().map(P::item).collect())
+}
+
+// Error for day 3 input that does not describe valid rucksacks or groups
+#[derive(Debug, PartialEq)]
+pub enum Day3Error {
+    OddLengthRucksack { line: usize, len: usize }, // rucksack cannot be split into two equal compartments
+    InvalidItem { line: Option<usize>, ch: char }, // character is not an item type (line is None until known)
+    MultipleCommonItems { line: usize, items: Vec<char> }, // rucksack has more than one item type in both compartments
+    IncompleteGroup { starting_line: usize, got: usize }, // rucksacks left over after splitting the input into groups
+    NoCommonItem { starting_line: usize, group_size: usize }, // group of rucksacks has no item in common
+    InvalidGroupSize { group_size: usize }, // group size that cannot have a single common item
+    UnknownSplitPolicy { name: String }, // --split named no SplitPolicy
+}
+
+impl Day3Error {
+    // The same error, located on (one-indexed) line 'line' if it is an InvalidItem
+    fn on_line(self, line: usize) -> Day3Error {
+        match self {
+            Self::InvalidItem { ch, .. } => Self::InvalidItem { line: Some(line), ch },
+            e => e,
+        }
+    }
+}
+
+impl error::Error for Day3Error {}
+
+// Every problem found in day 3 input by 'validate'
+#[derive(Debug, PartialEq)]
+struct Day3Errors(Vec<Day3Error>);
+
+impl error::Error for Day3Errors {}
+impl fmt::Display for Day3Errors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} problem(s) found in day 3 input:", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n  {error}")?;
+        }
+        Ok(())
+    }
+}
+impl fmt::Display for Day3Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OddLengthRucksack { line, len } =>
+                write!(f, "rucksack on line {line} has an odd number of items ({len}) and cannot be equally split"),
+            Self::InvalidItem { line: Some(line), ch } => write!(f, "invalid item '{ch}' found on line {line}"),
+            Self::InvalidItem { line: None, ch } => write!(f, "invalid item '{ch}' found"),
+            Self::MultipleCommonItems { line, items } => {
+                let items: Vec<String> = items.iter().map(|c| format!("'{c}'")).collect();
+                write!(f, "rucksack on line {line} has {} items in both compartments, expected 1: {}", items.len(), items.join(", "))
+            },
+            Self::IncompleteGroup { starting_line, got } =>
+                write!(f, "{got} rucksack(s) left over in an incomplete group starting at line {starting_line}"),
+            Self::NoCommonItem { starting_line, group_size } =>
+                write!(f, "no item in common for the group of {group_size} rucksacks starting at line {starting_line}"),
+            Self::InvalidGroupSize { group_size } => write!(f, "rucksack groups must have at least 2 rucksacks, not {group_size}"),
+            Self::UnknownSplitPolicy { name } =>
+                write!(f, "unknown split policy '{name}', expected 'strict', 'drop-middle' or 'left-biased'"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Finds the badge of each group of 'group_size' rucksacks in 'input'
+    // eg: to find which group had the badge 'Z'
+    fn find_badges(input: &str, group_size: usize) -> Result<Vec<Badge>, Box<dyn error::Error>> {
+        badges::<Letters, _>(parse_rucksacks(input, SplitPolicy::Strict), group_size)
+    }
+
+    // Advent of Code challenge-provided example
+    const EXAMPLE_INPUT: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+
+    // Sums the priorities of the misplaced items of 'rucksacks' alone (part 1)
+    fn sum_misplaced_priorities<'a, I>(rucksacks: I) -> Result<u32, Box<dyn error::Error>>
+    where I: IntoIterator<Item = Result<Rucksack<'a>, Box<dyn error::Error>>> {
+        Ok(misplaced_items::<Letters, _>(rucksacks)?.iter().map(|r| r.priority).sum())
+    }
+
+    // Sums the priorities of the badges of 'rucksacks' alone (part 2)
+    fn badge_priorities<'a, I>(rucksacks: I, group_size: usize) -> Result<u32, Box<dyn error::Error>>
+    where I: IntoIterator<Item = Result<Rucksack<'a>, Box<dyn error::Error>>> {
+        Ok(badges::<Letters, _>(rucksacks, group_size)?.iter().map(|g| g.priority).sum())
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(validate::<Letters>(EXAMPLE_INPUT, 3, SplitPolicy::Strict).map(|r| r.len()), Ok(6));
+
+        // An odd-length line, a bad character and an incomplete final group are all reported
+        let input = "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSLx\nPmmdzqPrVvPwwTWBwg\n\nwMqvLMZHhHMvwLHjbvcjnn-BnvTQFn";
+        let errors = validate::<Letters>(input, 3, SplitPolicy::Strict).unwrap_err();
+        assert_eq!(errors, [
+            Day3Error::OddLengthRucksack { line: 2, len: 33 },
+            Day3Error::InvalidItem { line: Some(5), ch: '-' },
+            Day3Error::IncompleteGroup { starting_line: 5, got: 1 },
+        ]);
+        assert_eq!(Day3Errors(errors).to_string(), "3 problem(s) found in day 3 input:
+  rucksack on line 2 has an odd number of items (33) and cannot be equally split
+  invalid item '-' found on line 5
+  1 rucksack(s) left over in an incomplete group starting at line 5");
+
+        // Groups without a badge, and rucksacks with several misplaced items, do not stop validation
+        let errors = validate::<Letters>("abcd\nefgh\nabab\ncdcd", 2, SplitPolicy::Strict).unwrap_err();
+        assert_eq!(errors, [
+            Day3Error::NoCommonItem { starting_line: 1, group_size: 2 },
+            Day3Error::MultipleCommonItems { line: 3, items: vec!['a', 'b'] },
+            Day3Error::MultipleCommonItems { line: 4, items: vec!['c', 'd'] },
+            Day3Error::NoCommonItem { starting_line: 3, group_size: 2 },
+        ]);
+        assert_eq!(validate::<Letters>(EXAMPLE_INPUT, 1, SplitPolicy::Strict).unwrap_err(), [Day3Error::InvalidGroupSize { group_size: 1 }]);
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE_INPUT.as_bytes(), BADGE_GROUP_SIZE).unwrap(), (157, 70));
+
+        // Both parts are answered together, so a list with an incomplete group answers neither
+        let first_four: Vec<&str> = EXAMPLE_INPUT.lines().take(4).collect();
+        assert!(solve(first_four.join("\n").as_bytes(), BADGE_GROUP_SIZE).is_err());
+        assert_eq!(solve(first_four.join("\n").as_bytes(), 2).unwrap().0, 16 + 38 + 42 + 22);
+
+        // Each part can also be answered on its own, so the incomplete group only fails part 2
+        assert_eq!(part1(EXAMPLE_INPUT).unwrap(), 157);
+        assert_eq!(part2(EXAMPLE_INPUT).unwrap(), 70);
+        assert_eq!(part1(&first_four.join("\n")).unwrap(), 16 + 38 + 42 + 22);
+        assert!(part2(&first_four.join("\n")).is_err());
+    }
+
+    #[test]
+    fn single_pass() {
+        assert_eq!(sum_priorities::<Letters, _>(Rucksacks::new(EXAMPLE_INPUT.as_bytes()), BADGE_GROUP_SIZE).unwrap(), (157, 70));
+        assert_eq!(sum_priorities::<Letters, _>(parse_rucksacks(EXAMPLE_INPUT, SplitPolicy::Strict), BADGE_GROUP_SIZE).unwrap(), (157, 70));
+
+        // An error in either part fails both
+        let error = sum_priorities::<Letters, _>(Rucksacks::new(EXAMPLE_INPUT.lines().take(4).collect::<Vec<_>>().join("\n").as_bytes()), 3).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&Day3Error::IncompleteGroup { starting_line: 4, got: 1 }));
+        let error = sum_priorities::<Letters, _>(Rucksacks::new("abab\nabcd".as_bytes()), 2).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&Day3Error::MultipleCommonItems { line: 1, items: vec!['a', 'b'] }));
+    }
+
+    #[test]
+    fn incomplete_groups() {
+        let rucksacks: Vec<&str> = EXAMPLE_INPUT.lines().collect();
+
+        // 3 rucksacks make one full group, and trailing blank lines are ignored
+        assert_eq!(badge_priorities(Rucksacks::new(rucksacks[..3].join("\n").as_bytes()), 3).unwrap(), 18);
+        assert_eq!(badge_priorities(Rucksacks::new(format!("{}\n\n\n", rucksacks[..3].join("\n")).as_bytes()), 3).unwrap(), 18);
+        assert_eq!(badge_priorities(Rucksacks::new(EXAMPLE_INPUT.as_bytes()), 3).unwrap(), 70);
+
+        // 4 and 5 rucksacks leave an incomplete group starting on line 4
+        let error = badge_priorities(Rucksacks::new(rucksacks[..4].join("\n").as_bytes()), 3).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&Day3Error::IncompleteGroup { starting_line: 4, got: 1 }));
+        let error = badge_priorities(Rucksacks::new(rucksacks[..5].join("\n").as_bytes()), 3).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&Day3Error::IncompleteGroup { starting_line: 4, got: 2 }));
+        assert_eq!(error.to_string(), "2 rucksack(s) left over in an incomplete group starting at line 4");
+    }
+
+    #[test]
+    fn long_groups() {
+        // Three 100,000 item rucksacks which only share 'Q', each otherwise made of its own items repeated over and over
+        let filler = |items: &str| items.repeat(100_000 / items.len() - 1);
+        let group = [
+            filler("abcdefghij") + "QabcdefghQ",
+            filler("klmnopqrst") + "QklmnopqrQ",
+            filler("uvwxyzABCD") + "QuvwxyzABQ",
+        ];
+        assert!(group.iter().all(|r| r.len() == 100_000));
+        let input = group.join("\n");
+
+        assert_eq!(find_badges(&input, 3).unwrap(), [Badge { group_index: 0, item: 'Q', priority: 43, line_range: 1..=3 }]);
+        assert_eq!(badge_priorities(Rucksacks::new(input.as_bytes()), 3).unwrap(), 43);
+
+        // Repeating the group many times over only ever keeps one group's common items
+        let input = [input.as_str(); 20].join("\n");
+        assert_eq!(find_badges(&input, 3).unwrap().len(), 20);
+        assert_eq!(badge_priorities(Rucksacks::new(input.as_bytes()), 3).unwrap(), 20 * 43);
+    }
+
+    #[test]
+    fn group_sizes() {
+        // Groups of 2 each share one badge, but all 4 rucksacks together share nothing
+        let input = "abcX\nXdef\nghYi\njkYl";
+        assert_eq!(badge_priorities(Rucksacks::new(input.as_bytes()), 2).unwrap(), 50 + 51);
+        assert_eq!(badge_priorities(Rucksacks::new(input.as_bytes()), 4).unwrap_err().downcast_ref(),
+            Some(&Day3Error::NoCommonItem { starting_line: 1, group_size: 4 }));
+
+        // The whole example as one group of 6 has no common item, while groups of 4 leave 2 rucksacks over
+        let error = badge_priorities(Rucksacks::new(EXAMPLE_INPUT.as_bytes()), 6).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&Day3Error::NoCommonItem { starting_line: 1, group_size: 6 }));
+        assert_eq!(error.to_string(), "no item in common for the group of 6 rucksacks starting at line 1");
+        let error = badge_priorities(Rucksacks::new("aA\nab\nac\nad\naA\nAb".as_bytes()), 4).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&Day3Error::IncompleteGroup { starting_line: 5, got: 2 }));
+
+        // Groups of fewer than 2 rucksacks are rejected
+        for group_size in [0, 1] {
+            let error = badge_priorities(Rucksacks::new(EXAMPLE_INPUT.as_bytes()), group_size).unwrap_err();
+            assert_eq!(error.downcast_ref(), Some(&Day3Error::InvalidGroupSize { group_size }));
+        }
+    }
+
+    #[test]
+    fn common_items() {
+        // Each example rucksack has exactly one item in both compartments
+        let misplaced: Vec<Vec<char>> = EXAMPLE_INPUT.lines()
+            .map(|line| Rucksack::new(line, 1, SplitPolicy::Strict).unwrap().common_items::<Letters>().unwrap())
+            .collect();
+        assert_eq!(misplaced, [['p'], ['L'], ['P'], ['v'], ['t'], ['s']]);
+        assert_eq!(sum_misplaced_priorities(Rucksacks::new(EXAMPLE_INPUT.as_bytes())).unwrap(), 157);
+
+        // Repeated items are only reported once, and a rucksack with no common item is allowed
+        let rucksack = Rucksack::new("aBaCbaBa", 1, SplitPolicy::Strict).unwrap();
+        assert_eq!(rucksack.common_items::<Letters>(), Ok(vec!['a', 'B']));
+        assert_eq!(sum_misplaced_priorities(Rucksacks::new("abcd\nabca".as_bytes())).unwrap(), 1);
+
+        // Two shared item types is malformed input
+        let error = sum_misplaced_priorities(Rucksacks::new("abcd\naBaCbaBa".as_bytes())).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&Day3Error::MultipleCommonItems { line: 2, items: vec!['a', 'B'] }));
+        assert_eq!(error.to_string(), "rucksack on line 2 has 2 items in both compartments, expected 1: 'a', 'B'");
+    }
+
+    // Reference implementation that item sets are checked against:
+    // returns every character of 's2' that also appears in 's1'
+    fn chars_in_common(s1: &str, s2: &str) -> String {
+        s2.chars().filter(|c| s1.contains(*c)).collect()
+    }
+
+    #[test]
+    fn item_set_intersections() {
+        // Item sets agree with a direct comparison, for each rucksack's compartments and for each group
+        let rucksacks: Vec<&str> = EXAMPLE_INPUT.lines().collect();
+        for rucksack in &rucksacks {
+            let (left, right) = rucksack.split_at(rucksack.len() / 2);
+            let expected = chars_in_common(left, right).chars().next();
+            assert_eq!(items::<Letters>(common(&[item_set::<Letters>(left).unwrap(), item_set::<Letters>(right).unwrap()])).next(), expected);
+        }
+        for group in rucksacks.chunks(3) {
+            let expected = chars_in_common(&chars_in_common(group[0], group[1]), group[2]).chars().next();
+            let sets: Vec<u64> = group.iter().map(|r| item_set::<Letters>(r).unwrap()).collect();
+            assert_eq!(items::<Letters>(common(&sets)).next(), expected);
+        }
+
+        // Items at both ends of the priority range
+        assert_eq!(item_set::<Letters>("a"), Ok(1));
+        assert_eq!(item_set::<Letters>("Z"), Ok(1 << 51));
+        assert_eq!(items::<Letters>(item_set::<Letters>("ZzaA").unwrap()).collect::<String>(), "azAZ");
+        let sets = [item_set::<Letters>("aZb").unwrap(), item_set::<Letters>("Zac").unwrap(), item_set::<Letters>("dZa").unwrap()];
+        assert_eq!(items::<Letters>(common(&sets)).collect::<String>(), "aZ");
+        assert_eq!(items::<Letters>(common(&[item_set::<Letters>("abc").unwrap(), item_set::<Letters>("ABC").unwrap()])).next(), None);
+        for priority in 1..=52 {
+            assert_eq!(get_priority::<Letters>(Letters::item(priority)), Ok(priority));
+        }
+    }
+
+    #[test]
+    fn invalid_items() {
+        assert_eq!(get_priority::<Letters>('5'), Err(Day3Error::InvalidItem { line: None, ch: '5' }));
+
+        // A digit, a space and a non-ASCII character are each reported with their line, in both parts
+        let cases = [("abcd\nab3b", '3', 2), ("a  b\nabcd", ' ', 1), ("abcb\ncdce\néabcde", 'é', 3)];
+        for (input, item, line) in cases {
+            let error = sum_misplaced_priorities(Rucksacks::new(input.as_bytes())).unwrap_err();
+            assert_eq!(error.downcast_ref(), Some(&Day3Error::InvalidItem { line: Some(line), ch: item }));
+
+            let group = format!("{input}\nab\nab\nab");
+            let group: Vec<&str> = group.lines().take(3).collect();
+            let error = badge_priorities(Rucksacks::new(group.join("\n").as_bytes()), 3).unwrap_err();
+            assert_eq!(error.downcast_ref(), Some(&Day3Error::InvalidItem { line: Some(line), ch: item }));
+        }
+
+        let error = sum_misplaced_priorities(Rucksacks::new("vJrwpWtwJgWrhcsFMMfFFhFp\nab3b".as_bytes())).unwrap_err();
+        assert_eq!(error.to_string(), "invalid item '3' found on line 2");
+    }
+
+    #[test]
+    fn multibyte_rucksacks() {
+        // '€' is 3 bytes, so the byte midpoint of "a€bc" falls inside it, but the character midpoint does not
+        let rucksack = Rucksack::new("a€bc", 1, SplitPolicy::Strict).unwrap();
+        assert_eq!((rucksack.left(), rucksack.right()), ("a€", "bc"));
+        assert_eq!(rucksack.common_items::<Letters>(), Err(Day3Error::InvalidItem { line: Some(1), ch: '€' }));
+        let error = sum_misplaced_priorities(Rucksacks::new("abca\na€bc".as_bytes())).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&Day3Error::InvalidItem { line: Some(2), ch: '€' }));
+
+        // Odd character counts are rejected even when the byte length is even
+        assert!(Rucksack::new("é", 1, SplitPolicy::Strict).is_err());
+        assert!(Rucksack::new("aéb", 1, SplitPolicy::Strict).is_err());
+        assert!(Rucksack::new("éa", 1, SplitPolicy::Strict).is_ok());
+        assert!(Rucksack::new("", 1, SplitPolicy::Strict).is_ok());
+    }
+
+    // Builds 'length' rucksacks of 'compartment' items each, with a single item common to both compartments
+    fn random_rucksacks(length: usize, compartment: usize, mut seed: u64) -> String {
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        let mut lines = Vec::with_capacity(length);
+        for _ in 0..length {
+            // Left items are lowercase and right items uppercase, so only the shared item is in both
+            let shared = Letters::item((next() % 52) as u32 + 1);
+            let mut left: String = (1..compartment).map(|_| Letters::item((next() % 26) as u32 + 1)).collect();
+            let right: String = (1..compartment).map(|_| Letters::item((next() % 26) as u32 + 27)).collect();
+            left.push(shared);
+            lines.push(format!("{left}{shared}{right}"));
+        }
+        lines.join("\n")
+    }
+
+    // Times reading rucksacks into owned lines against borrowing them from the input, over a million rucksacks
+    // Run with 'cargo test --release bench_rucksacks -- --ignored --nocapture'
+    #[test]
+    #[ignore]
+    fn bench_rucksacks() {
+        use std::time::Instant;
+
+        let input = random_rucksacks(1_000_000, 12, 0x2022);
+
+        let start = Instant::now();
+        let owned = sum_misplaced_priorities(Rucksacks::new(input.as_bytes())).unwrap();
+        let owned_time = start.elapsed();
+
+        let start = Instant::now();
+        let borrowed = sum_misplaced_priorities(parse_rucksacks(&input, SplitPolicy::Strict)).unwrap();
+        let borrowed_time = start.elapsed();
+
+        assert_eq!(owned, borrowed);
+        println!("owned: {owned_time:?}, borrowed: {borrowed_time:?}");
+    }
+
+    #[test]
+    fn rucksack_iterator() {
+        // Blank lines are skipped, and each rucksack keeps the line it was read from
+        let rucksacks: Vec<Rucksack> = Rucksacks::new("\nabcd\n\n  \nAbCd\n".as_bytes()).map(Result::unwrap).collect();
+        let read: Vec<(&str, &str, usize)> = rucksacks.iter().map(|r| (r.left(), r.right(), r.line)).collect();
+        assert_eq!(read, [("ab", "cd", 2), ("Ab", "Cd", 5)]);
+
+        // An odd-length line is an error with its line number, after which iteration stops
+        let mut rucksacks = Rucksacks::new("abcd\n\nabc\nabcd".as_bytes());
+        assert!(rucksacks.next().unwrap().is_ok());
+        let error = rucksacks.next().unwrap().unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&Day3Error::OddLengthRucksack { line: 3, len: 3 }));
+        assert_eq!(error.to_string(), "rucksack on line 3 has an odd number of items (3) and cannot be equally split");
+        assert!(rucksacks.next().is_none());
+
+        // A read error part way through (here, invalid UTF-8) is surfaced, and ends iteration
+        let mut rucksacks = Rucksacks::new(&b"abcd\n\xff\xfe\nabcd"[..]);
+        assert!(rucksacks.next().unwrap().is_ok());
+        let error = rucksacks.next().unwrap().unwrap_err();
+        assert_eq!(error.downcast_ref::<Error>().map(Error::kind), Some(ErrorKind::InvalidData));
+        assert!(rucksacks.next().is_none());
+
+        // Both parts report odd-length lines
+        assert!(sum_misplaced_priorities(Rucksacks::new("abc".as_bytes())).is_err());
+        assert!(badge_priorities(Rucksacks::new("ab\nab\nabc".as_bytes()), 3).is_err());
+    }
+
+    #[test]
+    fn example_analysis() {
+        let analysis = analyze::<Letters>(EXAMPLE_INPUT, BADGE_GROUP_SIZE, SplitPolicy::Strict).unwrap();
+        assert_eq!((analysis.part_1, analysis.part_2), (157, 70));
+
+        let misplaced: Vec<(usize, Option<char>, u32)> = analysis.rucksacks.iter().map(|r| (r.line, r.item, r.priority)).collect();
+        assert_eq!(misplaced, [
+            (1, Some('p'), 16),
+            (2, Some('L'), 38),
+            (3, Some('P'), 42),
+            (4, Some('v'), 22),
+            (5, Some('t'), 20),
+            (6, Some('s'), 19),
+        ]);
+        assert_eq!(analysis.badges, find_badges(EXAMPLE_INPUT, BADGE_GROUP_SIZE).unwrap());
+
+        assert_eq!(analysis.rucksack_table()[..2], ["Line | Item | Priority", "   1 |    p |       16"]);
+        assert_eq!(analysis.group_table(), [
+            "Group | Lines   | Badge | Priority",
+            "    0 |     1-3 |     r |       18",
+            "    1 |     4-6 |     Z |       52",
+        ]);
+
+        // Borrowed and streamed rucksacks agree
+        assert_eq!(misplaced_items::<Letters, _>(Rucksacks::new(EXAMPLE_INPUT.as_bytes())).unwrap(), analysis.rucksacks);
+    }
+
+    #[test]
+    fn example_badges() {
+        let badges = find_badges(EXAMPLE_INPUT, BADGE_GROUP_SIZE).unwrap();
+        assert_eq!(badges, [
+            Badge { group_index: 0, item: 'r', priority: 18, line_range: 1..=3 },
+            Badge { group_index: 1, item: 'Z', priority: 52, line_range: 4..=6 },
+        ]);
+        assert_eq!(badges.iter().map(|b| b.priority).sum::<u32>(), 70);
+        assert_eq!(badges.iter().find(|b| b.item == 'Z').map(|b| b.group_index), Some(1));
+
+        // Blank lines between rucksacks widen a group's line range, but not its index
+        let badges = find_badges("abcr\nrdef\n\nghri\nZabc\nZdef\nghZi", 3).unwrap();
+        assert_eq!(badges[0].line_range, 1..=4);
+        assert_eq!(badges[1], Badge { group_index: 1, item: 'Z', priority: 52, line_range: 5..=7 });
+    }
+
+    #[test]
+    fn split_policies() {
+        let split = |policy| Rucksack::new("abcXdef", 7, policy).map(|r| (r.left().to_string(), r.right().to_string()));
+        assert_eq!(split(SplitPolicy::Strict), Err(Day3Error::OddLengthRucksack { line: 7, len: 7 }));
+        assert_eq!(split(SplitPolicy::DropMiddle), Ok(("abc".to_string(), "def".to_string())));
+        assert_eq!(split(SplitPolicy::LeftBiased), Ok(("abcX".to_string(), "def".to_string())));
+
+        // Multibyte middle characters are dropped or kept whole
+        let rucksack = Rucksack::new("ab€cd", 1, SplitPolicy::DropMiddle).unwrap();
+        assert_eq!((rucksack.left(), rucksack.right()), ("ab", "cd"));
+        let rucksack = Rucksack::new("ab€cd", 1, SplitPolicy::LeftBiased).unwrap();
+        assert_eq!((rucksack.left(), rucksack.right()), ("ab€", "cd"));
+
+        // Even-length rucksacks split the same way under every policy
+        for policy in [SplitPolicy::Strict, SplitPolicy::DropMiddle, SplitPolicy::LeftBiased] {
+            let rucksack = Rucksack::new("abcd", 1, policy).unwrap();
+            assert_eq!((rucksack.left(), rucksack.right()), ("ab", "cd"));
+        }
+
+        // The policy applies when reading and analyzing, where the middle 'X' is (or is not) a misplaced item
+        let input = "abcXdXf\nabXcdX";
+        assert!(sum_misplaced_priorities(Rucksacks::new(input.as_bytes())).is_err());
+        assert_eq!(sum_misplaced_priorities(Rucksacks::with_policy(input.as_bytes(), SplitPolicy::DropMiddle)).unwrap(), 50);
+        assert_eq!(sum_misplaced_priorities(Rucksacks::with_policy(input.as_bytes(), SplitPolicy::LeftBiased)).unwrap(), 100);
+        assert_eq!(analyze::<Letters>(input, 2, SplitPolicy::LeftBiased).unwrap().part_1, 100);
+        assert_eq!(solve_with_policy(input.as_bytes(), 2, SplitPolicy::DropMiddle).unwrap().0, 50);
+        assert_eq!(validate::<Letters>(input, 2, SplitPolicy::DropMiddle).map(|r| r.len()), Ok(2));
+        assert_eq!(validate::<Letters>(input, 2, SplitPolicy::Strict).unwrap_err(), [Day3Error::OddLengthRucksack { line: 1, len: 7 }]);
+
+        // Policies are chosen by their --split names
+        assert_eq!("drop-middle".parse(), Ok(SplitPolicy::DropMiddle));
+        assert_eq!("left-biased".parse(), Ok(SplitPolicy::LeftBiased));
+        assert_eq!("strict".parse(), Ok(SplitPolicy::Strict));
+        assert_eq!("Strict".parse::<SplitPolicy>(), Err(Day3Error::UnknownSplitPolicy { name: "Strict".to_string() }));
+        assert!(analyze::<Letters>(input, 2, SplitPolicy::Strict).is_err());
+    }
+
+    // Builds a random string of 'length' items drawn from the first 'alphabet' priorities
+    fn random_items(length: usize, alphabet: u64, seed: &mut u64) -> String {
+        (0..length).map(|_| {
+            *seed ^= *seed << 13;
+            *seed ^= *seed >> 7;
+            *seed ^= *seed << 17;
+            Letters::item((*seed % alphabet) as u32 + 1)
+        }).collect()
+    }
+
+    #[test]
+    fn intersections_agree() {
+        let strategies = [Intersection::Bitmask, Intersection::SortedMerge];
+
+        // Random strings of different lengths and alphabet sizes, in twos and threes
+        let mut seed = 0x2022;
+        for length in [0, 1, 5, 24, 200] {
+            for alphabet in [3, 26, 52] {
+                for count in [2, 3] {
+                    let parts: Vec<String> = (0..count).map(|_| random_items(length, alphabet, &mut seed)).collect();
+                    let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+                    let expected = strategies[0].common_items::<Letters>(&parts).unwrap();
+                    assert_eq!(strategies[1].common_items::<Letters>(&parts).unwrap(), expected, "{parts:?}");
+                }
+            }
+        }
+
+        // Both find the example's items, and report the same invalid item
+        for strategy in strategies {
+            assert_eq!(strategy.common_items::<Letters>(&["vJrwpWtwJgWr", "hcsFMMfFFhFp"]), Ok(vec!['p']));
+            assert_eq!(strategy.common_items::<Letters>(&["aZb", "Zac", "dZa"]), Ok(vec!['a', 'Z']));
+            assert_eq!(strategy.common_items::<Letters>(&["ab", "b1c2"]), Err(Day3Error::InvalidItem { line: None, ch: '1' }));
+            assert_eq!(strategy.common_items::<Letters>(&[]).unwrap().len(), 52);
+        }
+    }
+
+    // Times each intersection strategy on the compartments of rucksacks of increasing length
+    // Run with 'cargo test --release bench_intersections -- --ignored --nocapture'
+    #[test]
+    #[ignore]
+    fn bench_intersections() {
+        use std::time::Instant;
+
+        for compartment in [8, 64, 1_000, 10_000, 100_000] {
+            let count = 4_000_000 / compartment;
+            let input = random_rucksacks(count, compartment, 0x2022);
+            let rucksacks: Vec<Rucksack> = parse_rucksacks(&input, SplitPolicy::Strict).map(Result::unwrap).collect();
+
+            let mut results = Vec::new();
+            for strategy in [Intersection::Bitmask, Intersection::SortedMerge] {
+                let start = Instant::now();
+                let found: usize = rucksacks.iter().map(|r| strategy.common_items::<Letters>(&[r.left(), r.right()]).unwrap().len()).sum();
+                results.push((strategy, found, start.elapsed()));
+            }
+
+            assert_eq!(results[0].1, results[1].1);
+            let times: Vec<String> = results.iter().map(|(strategy, _, time)| format!("{strategy:?}: {time:?}")).collect();
+            println!("{count} rucksacks of {} items - {}", compartment * 2, times.join(", "));
+        }
+    }
+
+    // Digit items, for testing mappings other than the puzzle's
+    // 0-9 ~ 1-10
+    struct Digits;
+
+    impl Priority for Digits {
+        type Set = u64;
+        const ITEM_TYPES: u32 = 10;
+
+        fn priority(c: char) -> Option<u32> {
+            c.to_digit(10).map(|d| d + 1)
+        }
+
+        fn item(priority: u32) -> char {
+            char::from_digit(priority - 1, 10).unwrap()
         }
     }
-    common
-}
\ No newline at end of file
+
+    // Every printable ASCII character as an item, for testing mappings of more than 64 item types
+    // '!'-'~' ~ 1-94
+    struct Printable;
+
+    impl Priority for Printable {
+        type Set = u128;
+        const ITEM_TYPES: u32 = 94;
+
+        fn priority(c: char) -> Option<u32> {
+            matches!(c, '!'..='~').then(|| c as u32 - '!' as u32 + 1)
+        }
+
+        fn item(priority: u32) -> char {
+            char::from(b'!' + (priority - 1) as u8)
+        }
+    }
+
+    #[test]
+    fn custom_priorities() {
+        // Misplaced items '0', '5' and '4', with the badge '0'
+        let input = "120340\n567850\n034554";
+        let analysis = analyze::<Digits>(input, 3, SplitPolicy::Strict).unwrap();
+        assert_eq!((analysis.part_1, analysis.part_2), (1 + 6 + 5, 1));
+        assert_eq!(analysis.badges[0].item, '0');
+        assert_eq!(sum_priorities::<Digits, _>(Rucksacks::new(input.as_bytes()), 3).unwrap(), (12, 1));
+
+        // Letters are not items under this mapping, and digits are not items under the puzzle's
+        let error = analyze::<Digits>("12a4", 3, SplitPolicy::Strict).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&Day3Error::InvalidItem { line: Some(1), ch: 'a' }));
+        assert!(analyze::<Letters>(input, 3, SplitPolicy::Strict).is_err());
+
+        // Mappings of more than 64 item types use wider item sets
+        let input = "a~B!\n!zz~\n~()~";
+        let analysis = analyze::<Printable>(input, 3, SplitPolicy::Strict).unwrap();
+        assert_eq!(analysis.rucksacks.iter().map(|r| r.item).collect::<Vec<_>>(), [None, Some('z'), Some('~')]);
+        assert_eq!(analysis.badges[0].item, '~');
+        assert_eq!((analysis.part_1, analysis.part_2), (90 + 94, 94));
+        for strategy in [Intersection::Bitmask, Intersection::SortedMerge] {
+            assert_eq!(strategy.common_items::<Printable>(&["}~!a", "a~}"]), Ok(vec!['a', '}', '~']));
+        }
+    }
+}
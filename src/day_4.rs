@@ -5,104 +5,1039 @@
 // Part 2: ... the number of Elf pairs where the two ranges overlap.
 
 use super::*;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::ops::RangeInclusive;
 
 
-// A pair of Elves (elves 'a' and 'b') who each encompass a range of values (a_0 -> a_1 and b_0 -> b_1)
+// A pair of Elves who are each assigned a range of section IDs
 struct ElfPair {
-    a_0: i32, // elf a, range start
-    a_1: i32, // elf a, range end
-    b_0: i32, // elf b, range start
-    b_1: i32  // elf b, range end
+    first: SectionRange, // sections assigned to the first elf
+    second: SectionRange, // sections assigned to the second elf
+    normalized: bool, // whether either range was written high-low, and swapped by RangePolicy::Normalize
 }
 
+// Options for parsing elf pairs, which default to the puzzle's exact format
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+struct ParseOptions {
+    reversed: RangePolicy, // how a range written high-low is handled
+    grammar: Grammar, // which delimiters and spacing are accepted
+}
+
+// How a range written high-low (eg: '5-3') is parsed
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+enum RangePolicy {
+    #[default]
+    Strict, // fail with a Day4Error::ReversedRange, as the puzzle never has reversed ranges
+    Normalize, // swap the endpoints, so '5-3' is read as '3-5'
+}
+
+// Which forms of a line are accepted
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+enum Grammar {
+    #[default]
+    Strict, // exactly the puzzle's form, eg: '2-4,6-8'
+    Lenient, // ranges delimited by '-' or '..', with whitespace around any value, eg: '2..4, 6 - 8'
+}
+
+// An inclusive range of section IDs assigned to an elf
+// eg: '2-5' => sections 2, 3, 4 and 5
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SectionRange(RangeInclusive<u64>);
+
+// A group of any number of Elves, each assigned a range of section IDs
+// Extends the puzzle's pairs to lines of 2 or more comma-separated ranges, eg: "2-4,6-8,5-7"
+#[cfg(test)] // extended format, checked against ElfPair in tests, as the puzzle input only has pairs
+#[derive(Debug, PartialEq)]
+struct ElfGroup(Vec<SectionRange>);
+
+// Summary of how much the pairs' assignments overlap, built up while parsing and shown in verbose mode
+// eg: "1000 pairs, 12345 overlapping sections, largest overlap on line 87 with 60 sections, 99 distinct sections covered in 1 run(s), longest 1-99"
+#[derive(Debug, PartialEq)]
+struct Report {
+    pair_count: usize, // number of elf pairs in the list
+    total_overlap: u64, // sum of every pair's overlapping sections
+    largest_overlap: Option<(usize, u64)>, // one-indexed line and overlap length of the pair overlapping most (None if no pair overlaps)
+    covered: Vec<RangeInclusive<u64>>, // every section assigned to any elf, as sorted runs of consecutive sections
+    normalized: usize, // number of pairs with a reversed range swapped by RangePolicy::Normalize
+}
+
+// How the two ranges of an ElfPair relate to each other
+// Every pair is exactly one of these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Relation {
+    Equal, // both ranges are the same
+    FirstContainsSecond, // the first range encompasses the (different) second range
+    SecondContainsFirst, // the second range encompasses the (different) first range
+    Overlap, // the ranges share some sections, but neither encompasses the other
+    Disjoint, // the ranges share no sections
+}
+
+// How the elf pairs' assignments relate, counted in one pass over the list for both parts
+// Every pair is exactly one of contained (including identical), partially overlapping or disjoint.
+#[derive(Debug, Default, PartialEq)]
+struct PairStats {
+    contained: usize, // pairs where one range fully encompasses the other, including identical ranges
+    identical: usize, // pairs with the same range twice
+    partial: usize, // pairs that overlap, but where neither range encompasses the other
+    disjoint: usize, // pairs that share no sections
+}
+
+// Number of sections shown in each --dump diagram, as the puzzle's sections run from 1 to 99
+const DIAGRAM_WIDTH: u64 = 99;
+
 // Run challenge.
 // Main entry point to day 4 challenge.
-pub fn run(part_2: bool) -> Result<(), Box<dyn error::Error>> { 
-    let mut counter = 0;
-
+// Both parts are answered from one read of the list.
+// If 'options.dump' is set, a diagram of every overlapping pair is also written there.
+// If 'options.lenient' is set, ranges may also be written with '..' and spaced out, and reversed ranges are swapped rather
+// than failing, and counted in the verbose report.
+pub fn run(options: &Options) -> Result<Answers, Box<dyn error::Error>> { 
     let f = File::open("input/day4input.txt") ?;
-    let buf = BufReader::new(f);
+    let mut input = String::new();
+    BufReader::new(f).read_to_string(&mut input)?;
+
+    let parse_options = if options.lenient {
+        ParseOptions { reversed: RangePolicy::Normalize, grammar: Grammar::Lenient }
+    } else {
+        ParseOptions::default()
+    };
 
-    // For each line, extract an ElfPair and apply either the part 1 check (whether their schedules encompass each other),
-    // or the part 2 check (Whether their schedules overlap), and add to accumulator if so.
-    for line in buf.lines() {
+    // In validate mode, check the whole input first so every bad line is reported at once
+    if options.validate {
+        validate(&input, parse_options).map_err(Day4Errors)?;
+    }
+
+    let (stats, report) = solve(input.as_bytes(), parse_options)?;
+
+    if let Some(path) = &options.dump {
+        let dump = io::BufWriter::new(File::create(path)?);
+        write_diagrams(input.as_bytes(), dump, DIAGRAM_WIDTH, ElfPair::check_overlap, parse_options)?;
+    }
+
+    let mut details = Vec::new();
+    if options.verbose {
+        details.push(stats.to_string());
+        details.push(report.to_string());
+
+        // List the pairs counted by each part, to find the culprit when an answer is off
+        for (part, predicate) in [(1, Relation::encompasses as fn(Relation) -> bool), (2, Relation::overlaps)] {
+            let lines: Vec<String> = matching_pairs(&input, predicate, parse_options)?.iter().map(usize::to_string).collect();
+            details.push(format!("Part {part} pairs on lines: {}", lines.join(", ")));
+        }
+    }
+    Ok(Answers {
+        part_1: stats.part1().to_string(),
+        part_2: stats.part2().to_string(),
+        notes: Vec::new(),
+        details,
+    })
+}
+
+// Reads the elf pair list from 'reader' and returns PairStats for both parts, alongside a Report of the overlaps.
+// Lines are parsed according to 'options'.
+fn solve<R: BufRead>(reader: R, options: ParseOptions) -> Result<(PairStats, Report), Box<dyn error::Error>> {
+    let mut stats = PairStats::default();
+    let mut report = Report { pair_count: 0, total_overlap: 0, largest_overlap: None, covered: Vec::new(), normalized: 0 };
+    let mut ranges = Vec::new();
+
+    // For each line, extract an ElfPair and count how its ranges relate
+    for (i, line) in reader.lines().enumerate() {
         let line = line?;
-        let elfpair = ElfPair::build(&line)?;
-        if (!part_2 && elfpair.check_encompass()) || (part_2 && elfpair.check_overlap() ) {
-            counter += 1;
+        let elfpair = ElfPair::parse_with(&line, options).map_err(|e| e.on_line(i + 1))?;
+        stats.add(elfpair.relation());
+        report.normalized += usize::from(elfpair.normalized);
+
+        // Keep the first pair found with the largest overlap
+        let overlap = elfpair.overlap_len();
+        report.pair_count += 1;
+        report.total_overlap = report.total_overlap.saturating_add(overlap);
+        if overlap > 0 && report.largest_overlap.is_none_or(|(_, largest)| overlap > largest) {
+            report.largest_overlap = Some((i + 1, overlap));
+        }
+        ranges.extend([elfpair.first().clone(), elfpair.second().clone()]);
+    }
+    report.covered = merge_ranges(ranges);
+    Ok((stats, report))
+}
+
+impl Relation {
+    // How the 'first' range relates to the 'second'
+    fn between(first: &SectionRange, second: &SectionRange) -> Relation {
+        match (first.contains(second), second.contains(first)) {
+            (true, true) => Relation::Equal,
+            (true, false) => Relation::FirstContainsSecond,
+            (false, true) => Relation::SecondContainsFirst,
+            (false, false) if first.overlaps(second) => Relation::Overlap,
+            (false, false) => Relation::Disjoint,
         }
     }
-    let part = if part_2 {2} else {1};
-    println!("Result for day 4-{part} = {counter}");
+}
+
+impl Relation {
+    // Whether one range fully encompasses the other (part 1)
+    fn encompasses(self) -> bool {
+        matches!(self, Relation::Equal | Relation::FirstContainsSecond | Relation::SecondContainsFirst)
+    }
 
-    Ok(())
+    // Whether the ranges share any sections (part 2)
+    fn overlaps(self) -> bool {
+        self != Relation::Disjoint
+    }
 }
 
+impl PairStats {
+    // Counts a pair whose ranges have 'relation' towards the statistics
+    fn add(&mut self, relation: Relation) {
+        match relation {
+            Relation::Equal => {
+                self.contained += 1;
+                self.identical += 1;
+            },
+            Relation::FirstContainsSecond | Relation::SecondContainsFirst => self.contained += 1,
+            Relation::Overlap => self.partial += 1,
+            Relation::Disjoint => self.disjoint += 1,
+        }
+    }
 
+    // Part 1: the number of pairs where one range fully encompasses the other
+    fn part1(&self) -> usize {
+        self.contained
+    }
+
+    // Part 2: the number of pairs where the two ranges overlap at all
+    fn part2(&self) -> usize {
+        self.contained + self.partial
+    }
+}
+
+impl fmt::Display for PairStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} contained ({} identical), {} partially overlapping, {} disjoint",
+            self.contained, self.identical, self.partial, self.disjoint)
+    }
+}
+
+// Reads the elf pair list from 'reader' and returns PairStats for both parts, without building an ElfPair or Report
+// Lines are read into one reused buffer and each range parsed onto the stack, so very large inputs need no allocation
+// per line (about 40% faster than 'solve' in bench_streaming). Ranges must not be reversed, and errors carry the
+// (one-indexed) line number they occurred on.
+#[cfg(test)] // for very large generated inputs, as the runner also builds a Report
+fn solve_streaming<R: BufRead>(mut reader: R) -> Result<PairStats, Box<dyn error::Error>> {
+    let mut stats = PairStats::default();
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(stats);
+        }
+        line_number += 1;
+
+        let (first, second) = line.trim_end_matches(['\n', '\r']).split_once(',')
+            .ok_or(Day4Error::MissingComma { line: Some(line_number) })?;
+        let parse = |s| SectionRange::parse_with(s, ParseOptions::default()).map(|(range, _)| range).map_err(|e| e.on_line(line_number));
+        stats.add(Relation::between(&parse(first)?, &parse(second)?));
+    }
+}
+
+// Checks every line of 'input', returning each (one-indexed) line that is not a valid pair, alongside why
+// Unlike 'solve', this carries on past the first bad line. Lines are parsed according to 'options'.
+fn validate(input: &str, options: ParseOptions) -> Result<(), Vec<(usize, Day4Error)>> {
+    let errors: Vec<(usize, Day4Error)> = input.lines().enumerate()
+        .filter_map(|(i, line)| ElfPair::parse_with(line, options).err().map(|e| (i + 1, e.on_line(i + 1))))
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// Finds the (one-indexed) lines of 'input' holding pairs whose Relation passes 'predicate'
+// Blank lines are skipped, but still counted, so line numbers match the input. Lines are parsed according to 'options'.
+// eg: Relation::encompasses over the puzzle example => [4, 5]
+fn matching_pairs(input: &str, predicate: fn(Relation) -> bool, options: ParseOptions) -> Result<Vec<usize>, Day4Error> {
+    let mut lines = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let pair = ElfPair::parse_with(line, options).map_err(|e| e.on_line(i + 1))?;
+        if predicate(pair.relation()) {
+            lines.push(i + 1);
+        }
+    }
+    Ok(lines)
+}
+
+// Writes the diagram of every pair read from 'reader' that passes 'filter' to 'writer', returning how many were written
+// Each diagram is 'width' sections wide, headed by its (one-indexed) line and followed by a blank line.
+// Lines are parsed according to 'options'.
+fn write_diagrams<R: BufRead, W: Write>(reader: R, mut writer: W, width: u64, filter: fn(&ElfPair) -> bool, options: ParseOptions)
+    -> Result<usize, Box<dyn error::Error>> {
+    let mut written = 0;
+    for (i, line) in reader.lines().enumerate() {
+        let pair = ElfPair::parse_with(&line?, options).map_err(|e| e.on_line(i + 1))?;
+        if filter(&pair) {
+            writeln!(writer, "Line {}:\n{}\n", i + 1, pair.render(width))?;
+            written += 1;
+        }
+    }
+    writer.flush()?;
+    Ok(written)
+}
+
+// Merges 'ranges' into the fewest ranges covering the same values, sorted by start
+// Overlapping and adjacent ranges are joined, as values are whole numbers.
+// eg: [5..=7, 1..=3, 2..=4, 9..=9] => [1..=7, 9..=9]
+fn merge_ranges(mut ranges: Vec<RangeInclusive<u64>>) -> Vec<RangeInclusive<u64>> {
+    ranges.sort_unstable_by_key(|r| *r.start());
+    let mut merged: Vec<RangeInclusive<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges.into_iter().filter(|r| !r.is_empty()) {
+        match merged.last_mut() {
+            Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                if range.end() > last.end() {
+                    *last = *last.start()..=*range.end();
+                }
+            },
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+impl Report {
+    // The number of distinct sections assigned to any elf
+    fn sections_covered(&self) -> u64 {
+        self.covered.iter().fold(0, |total, r| total.saturating_add(section_count(r)))
+    }
+
+    // The longest run of consecutive sections assigned to any elf (the first, if several are as long)
+    fn longest_run(&self) -> Option<&RangeInclusive<u64>> {
+        self.covered.iter().rev().max_by_key(|r| r.end() - r.start())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} pairs, {} overlapping sections, ", self.pair_count, self.total_overlap)?;
+        match self.largest_overlap {
+            Some((line, len)) => write!(f, "largest overlap on line {line} with {len} sections"),
+            None => write!(f, "no pairs overlap"),
+        }?;
+        write!(f, ", {} distinct sections covered in {} run(s)", self.sections_covered(), self.covered.len())?;
+        if let Some(run) = self.longest_run() {
+            write!(f, ", longest {}-{}", run.start(), run.end())?;
+        }
+        if self.normalized > 0 {
+            write!(f, ", {} pair(s) with reversed ranges normalized", self.normalized)?;
+        }
+        Ok(())
+    }
+}
+
+
+
+impl FromStr for ElfPair {
+    type Err = Day4Error;
+
+    // Converts a string representing an ElfPair into an ElfPair struct, rejecting reversed ranges
+    // s => "1-5,2-8"
+    fn from_str(s: &str) -> Result<ElfPair, Day4Error> {
+        ElfPair::parse_with(s, ParseOptions::default())
+    }
+}
+
+// Writes the pair in the form it is parsed from, with any normalized range as swapped
+// eg: "2-4,6-8".parse::<ElfPair>()?.to_string() => "2-4,6-8"
+impl fmt::Display for ElfPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.first, self.second)
+    }
+}
+
+// eg: 2..=5 => "2-5"
+impl fmt::Display for SectionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.0.start(), self.0.end())
+    }
+}
+
+impl FromStr for SectionRange {
+    type Err = Day4Error;
+
+    // Converts a range string slice into a SectionRange, rejecting reversed ranges
+    // eg: '2-5' => 2..=5
+    fn from_str(s: &str) -> Result<SectionRange, Day4Error> {
+        Ok(SectionRange::parse_with(s, ParseOptions::default())?.0)
+    }
+}
+
+#[cfg(test)]
+impl FromStr for ElfGroup {
+    type Err = Day4Error;
+
+    // Converts a string of 2 or more comma-separated ranges into an ElfGroup
+    // s => "1-5,2-8,4-4"
+    fn from_str(s: &str) -> Result<ElfGroup, Day4Error> {
+        let ranges = s.split(',').map(str::parse).collect::<Result<Vec<SectionRange>, Day4Error>>()?;
+        if ranges.len() < 2 {
+            return Err(Day4Error::MissingComma { line: None })
+        }
+        Ok(ElfGroup(ranges))
+    }
+}
+
+#[cfg(test)]
+impl ElfGroup {
+
+    // The group's ranges ordered by start, with longer ranges first for equal starts
+    // Any range containing a later one then comes before it, so pairs can be checked in one pass.
+    fn sorted(&self) -> Vec<&RangeInclusive<u64>> {
+        let mut ranges: Vec<_> = self.0.iter().map(|r| &r.0).collect();
+        ranges.sort_unstable_by(|a, b| a.start().cmp(b.start()).then(b.end().cmp(a.end())));
+        ranges
+    }
+
+    // Checks whether any range in the group totally encompasses another
+    // Once sorted, a range is encompassed if an earlier range (starting no later) ends no earlier.
+    fn any_encompass(&self) -> bool {
+        self.sorted().windows(2).scan(0, |furthest_end, w| {
+            *furthest_end = (*furthest_end).max(*w[0].end());
+            Some(w[1].end() <= furthest_end)
+        }).any(|b| b)
+    }
+
+    // Checks whether any two ranges in the group share overlap
+    // Once sorted, a range overlaps an earlier one if it starts before the furthest earlier end.
+    fn any_overlap(&self) -> bool {
+        self.sorted().windows(2).scan(0, |furthest_end, w| {
+            *furthest_end = (*furthest_end).max(*w[0].end());
+            Some(w[1].start() <= furthest_end)
+        }).any(|b| b)
+    }
+}
 
 impl ElfPair {
-    
-    // Converts a string representing an ElfPair into an ElfPair struct
+
+    // Converts a string representing an ElfPair into an ElfPair struct according to 'options'
     // String must consist of 2 comma-separated ranges where each range is 2 integers hyphen-separated
-    // For each range, the first value should be be equal to or lower than the second.
     // s => "1-5,2-8"
-    fn build (s: &str) -> Result<ElfPair, Error> {
+    fn parse_with(s: &str, options: ParseOptions) -> Result<ElfPair, Day4Error> {
         let mut s = s.split(',');
 
         // Split into two ranges
         let (a, b) = match (s.next(), s.next()) {
             (Some(s_0), Some(s_1)) => (s_0,s_1),
-            (None, _) | (_, None) => return Err(Error::new(ErrorKind::Other, "Line was not formatted correctly and could not recognize two distinct ranges."))
+            (None, _) | (_, None) => return Err(Day4Error::MissingComma { line: None })
         };
     
         // Split each range into its constituent values
-        // "1-5" => 1, 5
-        let (a_0, a_1) = Self::unravel_into_range(a)?;
-        let (b_0, b_1) = Self::unravel_into_range(b)?;
-
-        if b_1 < b_0 || a_1 < a_0{
-            return Err(Error::new(ErrorKind::Other, "The second value must be higher than the first in the given range."))
-        }
-
+        // "1-5" => 1..=5
+        let (first, first_swapped) = SectionRange::parse_with(a, options)?;
+        let (second, second_swapped) = SectionRange::parse_with(b, options)?;
         Ok(ElfPair {
-            a_0,
-            a_1,
-            b_0,
-            b_1
+            first,
+            second,
+            normalized: first_swapped || second_swapped,
         })
     }
+    
+    // The range of sections assigned to the first elf of the pair
+    fn first(&self) -> &RangeInclusive<u64> {
+        &self.first.0
+    }
+
+    // The range of sections assigned to the second elf of the pair
+    fn second(&self) -> &RangeInclusive<u64> {
+        &self.second.0
+    }
+
+    // The range of sections assigned to both elves of the pair, if any
+    // eg: '2-6,4-8' => 4..=6
+    fn intersection(&self) -> Option<RangeInclusive<u64>> {
+        let start = *self.first().start().max(self.second().start());
+        let end = *self.first().end().min(self.second().end());
+        (start <= end).then_some(start..=end)
+    }
+
+    // The number of sections assigned to both elves of the pair
+    // eg: '2-4,4-6' => 1
+    fn overlap_len(&self) -> u64 {
+        self.intersection().map_or(0, |r| section_count(&r))
+    }
+
+    // How the first elf's range relates to the second's
+    fn relation(&self) -> Relation {
+        Relation::between(&self.first, &self.second)
+    }
+
+    // Draws both ranges of the pair as in the puzzle description, showing sections 1 to 'width'
+    // Each section is shown by its last digit, or '.' if it is not assigned, with the range alongside.
+    // Ranges running past 'width' end in '…' rather than being cut off silently.
+    // eg: '2-4,6-8' at width 9 => ".234.....  ->  2-4\n.....678.  ->  6-8"
+    fn render(&self, width: u64) -> String {
+        format!("{}\n{}", self.first.render(width), self.second.render(width))
+    }
 
     // Checks whether one of the ranges defined in this ElfPair totally encompasses another
+    #[cfg(test)] // the runner counts each Relation, and only dumps overlapping pairs
     fn check_encompass(&self) -> bool {
-        if self.a_0 >= self.b_0 && self.a_1 <= self.b_1 {
-            return true
-        }
-        if self.a_0 <= self.b_0 && self.a_1 >= self.b_1 {
-            return true
-        }
-        return false
+        self.relation().encompasses()
     }
+
     // Checks whether one of the ranges defined in this ElfPair shares overlap with another
     fn check_overlap(&self) -> bool {
-        if self.a_1 < self.b_0 || self.b_1 < self.a_0 {
-            return false
-        }
-        return true
+        self.relation().overlaps()
     }
 
-    // Unravels a range string slice into two separate integers
-    // eg: '2-5' => (2, 5)
-    fn unravel_into_range(s: &str) -> Result<(i32, i32), Error> {
-        let mut s = s.split('-');
-        let (s_0, s_1) = match (s.next(), s.next()) {
-            (Some(s_0), Some(s_1)) => (s_0,s_1),
-            (None, _) | (_, None) => return Err(Error::new(ErrorKind::Other, "One of the ElfPairs could not be formatted into a number range."))
+}
+
+impl SectionRange {
+
+    // Converts a range string slice into a SectionRange according to 'options'
+    // Also returns whether the endpoints were swapped.
+    // eg: '2-5' => (2..=5, false), and '5-2' => (2..=5, true) when normalizing
+    fn parse_with(s: &str, options: ParseOptions) -> Result<(SectionRange, bool), Day4Error> {
+        let (s_0, s_1) = match options.grammar {
+            Grammar::Lenient => match s.split_once("..") {
+                Some((s_0, s_1)) => (s_0.trim(), s_1.trim()),
+                None => {
+                    let (s_0, s_1) = split_range(s.trim())?;
+                    (s_0.trim(), s_1.trim())
+                }
+            },
+            Grammar::Strict => split_range(s)?,
         };
-        Ok((s_0.parse().expect("Range value was not an integer."),
-            s_1.parse().expect("Range value was not an integer.")))
+        let (start, end) = (parse_section(s_0)?, parse_section(s_1)?);
+
+        match options.reversed {
+            _ if start <= end => Ok((SectionRange(start..=end), false)),
+            RangePolicy::Strict => Err(Day4Error::ReversedRange { line: None, start, end }),
+            RangePolicy::Normalize => Ok((SectionRange(end..=start), true)),
+        }
+    }
+
+    // Draws one line of an ElfPair diagram (see ElfPair::render)
+    fn render(&self, width: u64) -> String {
+        let mut line: String = (1..=width)
+            .map(|section| if self.0.contains(&section) { char::from_digit((section % 10) as u32, 10).unwrap() } else { '.' })
+            .collect();
+        if *self.0.end() > width && width > 0 {
+            line.pop();
+            line.push('…');
+        }
+        format!("{line}  ->  {self}")
+    }
+
+    // Checks whether this range includes every section of 'other'
+    fn contains(&self, other: &SectionRange) -> bool {
+        self.0.start() <= other.0.start() && other.0.end() <= self.0.end()
+    }
 
+    // Checks whether this range shares at least one section with 'other'
+    fn overlaps(&self, other: &SectionRange) -> bool {
+        self.0.start() <= other.0.end() && other.0.start() <= self.0.end()
     }
 
 }
 
+// Splits a range on the first hyphen after its first character, so a negative value is kept whole and rejected
+// eg: '2-5' => ('2', '5'), '-3-5' => ('-3', '5'), and '2--5' => ('2', '-5')
+fn split_range(s: &str) -> Result<(&str, &str), Day4Error> {
+    match s.char_indices().skip(1).find(|&(_, c)| c == '-') {
+        Some((i, _)) => Ok((&s[..i], &s[i + 1..])),
+        None => Err(Day4Error::MissingHyphen { line: None, token: s.to_string() })
+    }
+}
+
+// The number of sections in 'range', saturating at u64::MAX for the (otherwise one too many) full range
+fn section_count(range: &RangeInclusive<u64>) -> u64 {
+    (range.end() - range.start()).saturating_add(1)
+}
+
+// Parses a single section ID
+// eg: '5' => 5
+fn parse_section(token: &str) -> Result<u64, Day4Error> {
+    token.parse().map_err(|_| Day4Error::NotANumber { line: None, token: token.to_string() })
+}
+
+// Ways a line of the elf pair list can be malformed
+// Each has the (one-indexed) line it was found on, once known.
+#[derive(Debug, PartialEq)]
+enum Day4Error {
+    MissingComma { line: Option<usize> }, // the line is not two comma-separated ranges
+    MissingHyphen { line: Option<usize>, token: String }, // a range is not two hyphen-separated values
+    NotANumber { line: Option<usize>, token: String }, // a range value is not a non-negative integer
+    ReversedRange { line: Option<usize>, start: u64, end: u64 }, // a range ends before it starts
+}
+
+impl Day4Error {
+    // Attaches the (one-indexed) line the error was found on
+    fn on_line(self, line: usize) -> Day4Error {
+        let line = Some(line);
+        match self {
+            Self::MissingComma { .. } => Self::MissingComma { line },
+            Self::MissingHyphen { token, .. } => Self::MissingHyphen { line, token },
+            Self::NotANumber { token, .. } => Self::NotANumber { line, token },
+            Self::ReversedRange { start, end, .. } => Self::ReversedRange { line, start, end },
+        }
+    }
+
+    fn line(&self) -> Option<usize> {
+        match self {
+            Self::MissingComma { line } | Self::MissingHyphen { line, .. } | Self::NotANumber { line, .. }
+                | Self::ReversedRange { line, .. } => *line,
+        }
+    }
+}
+
+impl error::Error for Day4Error {}
+
+// Every bad line found in day 4 input by 'validate'
+#[derive(Debug, PartialEq)]
+struct Day4Errors(Vec<(usize, Day4Error)>);
+
+impl error::Error for Day4Errors {}
+impl fmt::Display for Day4Errors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bad line(s) found in day 4 input:", self.0.len())?;
+        for (_, error) in &self.0 {
+            write!(f, "\n  {error}")?;
+        }
+        Ok(())
+    }
+}
+impl fmt::Display for Day4Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingComma { .. } => write!(f, "expected two comma-separated ranges")?,
+            Self::MissingHyphen { token, .. } => write!(f, "range '{token}' is not two hyphen-separated values")?,
+            Self::NotANumber { token, .. } => write!(f, "range value '{token}' is not a non-negative integer")?,
+            Self::ReversedRange { start, end, .. } => write!(f, "range {start}-{end} ends before it starts")?,
+        }
+        match self.line() {
+            Some(line) => write!(f, " on line {line}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8";
+
+    // Counts the pairs in 'input' that pass 'check'
+    fn count_pairs(input: &str, check: fn(&ElfPair) -> bool) -> usize {
+        input.lines().map(|line| line.parse::<ElfPair>().unwrap()).filter(check).count()
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(count_pairs(EXAMPLE_INPUT, ElfPair::check_encompass), 2);
+        assert_eq!(count_pairs(EXAMPLE_INPUT, ElfPair::check_overlap), 4);
+    }
+
+    #[test]
+    fn parse() {
+        // Every example line parses into the ranges it lists
+        let expected = [(2..=4, 6..=8), (2..=3, 4..=5), (5..=7, 7..=9), (2..=8, 3..=7), (6..=6, 4..=6), (2..=6, 4..=8)];
+        for (line, (first, second)) in EXAMPLE_INPUT.lines().zip(expected) {
+            let pair: ElfPair = line.parse().unwrap();
+            assert_eq!((pair.first(), pair.second()), (&first, &second), "{line}");
+        }
+
+        // Missing ranges, missing range ends and backwards ranges are rejected
+        assert!("2-4".parse::<ElfPair>().is_err());
+        assert!("2-4,6".parse::<ElfPair>().is_err());
+        assert!("4-2,6-8".parse::<ElfPair>().is_err());
+        assert!("2-4,8-6".parse::<ElfPair>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        // Parsing then writing a pair gives back its line
+        for line in EXAMPLE_INPUT.lines().chain(random_pairs(1000, 0x2022).lines()) {
+            let pair: ElfPair = line.parse().unwrap();
+            assert_eq!(pair.to_string(), line);
+            assert_eq!(pair.to_string().parse::<ElfPair>().unwrap().relation(), pair.relation());
+        }
+
+        // Normalized ranges are written the right way round
+        let pair = ElfPair::parse_with("5-3,1-2", NORMALIZE).unwrap();
+        assert_eq!(pair.to_string(), "3-5,1-2");
+        assert_eq!(SectionRange(0..=u64::MAX).to_string(), "0-18446744073709551615");
+    }
+
+    #[test]
+    fn malformed_lines() {
+        for (line, expected) in [
+            ("2-4", Day4Error::MissingComma { line: None }),
+            ("", Day4Error::MissingComma { line: None }),
+            ("2-4,6", Day4Error::MissingHyphen { line: None, token: "6".to_string() }),
+            ("24,6-8", Day4Error::MissingHyphen { line: None, token: "24".to_string() }),
+            ("3-x,5-7", Day4Error::NotANumber { line: None, token: "x".to_string() }),
+            ("2-4,-6-8", Day4Error::NotANumber { line: None, token: "-6".to_string() }),
+            ("2-4,6--8", Day4Error::NotANumber { line: None, token: "-8".to_string() }),
+            ("2-4,6-8-9", Day4Error::NotANumber { line: None, token: "8-9".to_string() }),
+            ("2-4,6-18446744073709551616", Day4Error::NotANumber { line: None, token: "18446744073709551616".to_string() }),
+            ("2-4,6-8.5", Day4Error::NotANumber { line: None, token: "8.5".to_string() }),
+            ("4-2,6-8", Day4Error::ReversedRange { line: None, start: 4, end: 2 }),
+        ] {
+            assert_eq!(line.parse::<ElfPair>().err(), Some(expected), "{line}");
+        }
+
+        // The runner reports which line was malformed
+        let error = solve("2-4,6-8\n3-x,5-7".as_bytes(), ParseOptions::default()).unwrap_err();
+        assert_eq!(error.downcast_ref::<Day4Error>(), Some(&Day4Error::NotANumber { line: Some(2), token: "x".to_string() }));
+        assert_eq!(error.to_string(), "range value 'x' is not a non-negative integer on line 2");
+        assert_eq!("4-2,6-8".parse::<ElfPair>().err().unwrap().to_string(), "range 4-2 ends before it starts");
+    }
+
+    #[test]
+    fn encompass() {
+        // Either range may be the larger, and equal ranges encompass each other
+        for (line, expected) in [("2-8,3-7", true), ("3-7,2-8", true), ("6-6,4-6", true), ("4-6,4-6", true),
+                                 ("5-7,7-9", false), ("2-4,6-8", false), ("2-6,3-7", false)] {
+            assert_eq!(line.parse::<ElfPair>().unwrap().check_encompass(), expected, "{line}");
+        }
+    }
+
+    #[test]
+    fn overlap() {
+        // Ranges sharing only their ends still overlap, while adjacent ranges do not
+        for (line, expected) in [("5-7,7-9", true), ("7-9,5-7", true), ("2-8,3-7", true), ("4-6,4-6", true),
+                                 ("2-3,4-5", false), ("6-8,2-4", false)] {
+            assert_eq!(line.parse::<ElfPair>().unwrap().check_overlap(), expected, "{line}");
+        }
+    }
+
+    #[test]
+    fn relations() {
+        use Relation::*;
+        for (line, expected) in [
+            ("4-6,4-6", Equal), ("7-7,7-7", Equal),
+            ("2-8,3-7", FirstContainsSecond), ("2-8,2-7", FirstContainsSecond), ("2-8,3-8", FirstContainsSecond), ("7-9,7-7", FirstContainsSecond),
+            ("3-7,2-8", SecondContainsFirst), ("6-6,4-6", SecondContainsFirst), ("7-7,7-9", SecondContainsFirst), ("8-8,7-9", SecondContainsFirst),
+            ("5-7,7-9", Overlap), ("7-9,5-7", Overlap), ("2-6,4-8", Overlap),
+            ("2-4,6-8", Disjoint), ("2-3,4-5", Disjoint), ("7-7,8-8", Disjoint), ("9-9,1-8", Disjoint),
+        ] {
+            let pair: ElfPair = line.parse().unwrap();
+            assert_eq!(pair.relation(), expected, "{line}");
+            assert_eq!(pair.check_encompass(), matches!(expected, Equal | FirstContainsSecond | SecondContainsFirst), "{line}");
+            assert_eq!(pair.check_overlap(), expected != Disjoint, "{line}");
+        }
+    }
+
+    #[test]
+    fn intersections() {
+        // Touching, nested, identical, partly overlapping and disjoint ranges
+        for (line, expected, len) in [("2-4,4-6", Some(4..=4), 1), ("2-8,3-7", Some(3..=7), 5), ("3-7,2-8", Some(3..=7), 5),
+                                      ("4-6,4-6", Some(4..=6), 3), ("2-6,4-8", Some(4..=6), 3),
+                                      ("2-3,4-5", None, 0), ("6-8,2-4", None, 0)] {
+            let pair = line.parse::<ElfPair>().unwrap();
+            assert_eq!(pair.intersection(), expected, "{line}");
+            assert_eq!(pair.overlap_len(), len, "{line}");
+            assert_eq!(pair.check_overlap(), len > 0, "{line}");
+        }
+    }
+
+    const NORMALIZE: ParseOptions = ParseOptions { reversed: RangePolicy::Normalize, grammar: Grammar::Strict };
+    const LENIENT: ParseOptions = ParseOptions { reversed: RangePolicy::Strict, grammar: Grammar::Lenient };
+
+    #[test]
+    fn range_policies() {
+        assert_eq!("5-3,1-2".parse::<ElfPair>().err(), Some(Day4Error::ReversedRange { line: None, start: 5, end: 3 }));
+        assert!(ElfPair::parse_with("5-3,1-2", ParseOptions::default()).is_err());
+
+        let pair = ElfPair::parse_with("5-3,1-2", NORMALIZE).unwrap();
+        assert_eq!((pair.first(), pair.second(), pair.normalized), (&(3..=5), &(1..=2), true));
+        let pair = ElfPair::parse_with("3-5,1-2", NORMALIZE).unwrap();
+        assert_eq!((pair.first(), pair.second(), pair.normalized), (&(3..=5), &(1..=2), false));
+        assert_eq!(SectionRange::parse_with("7-7", NORMALIZE), Ok((SectionRange(7..=7), false)));
+
+        // Normalized pairs are counted in the report
+        let input = "5-3,1-2\n2-4,8-6\n9-7,7-9\n1-1,2-2";
+        assert!(solve(input.as_bytes(), ParseOptions::default()).is_err());
+        let (stats, report) = solve(input.as_bytes(), NORMALIZE).unwrap();
+        assert_eq!(stats, PairStats { contained: 1, identical: 1, partial: 0, disjoint: 3 });
+        assert_eq!(report.normalized, 3);
+        assert!(report.to_string().ends_with(", 3 pair(s) with reversed ranges normalized"));
+
+        // The verbose listings and validation read reversed ranges the same way
+        assert_eq!(matching_pairs(input, Relation::overlaps, NORMALIZE), Ok(vec![3]));
+        assert_eq!(validate(input, NORMALIZE), Ok(()));
+        assert_eq!(validate(input, ParseOptions::default()).unwrap_err().len(), 3);
+    }
+
+    #[test]
+    fn grammars() {
+        // Either delimiter, mixed on one line, with spacing around any value
+        for line in ["2..4,6..8", "2..4, 6..8", "2-4,6..8", " 2 .. 4 ,6 - 8 ", "2-4,6-8"] {
+            let pair = ElfPair::parse_with(line, LENIENT).unwrap();
+            assert_eq!((pair.first(), pair.second()), (&(2..=4), &(6..=8)), "{line}");
+        }
+
+        // Strict parsing only accepts the puzzle's form
+        for line in ["2..4,6..8", "2-4, 6-8", "2-4,6..8", " 2-4,6-8"] {
+            assert!(ElfPair::parse_with(line, ParseOptions::default()).is_err(), "{line}");
+            assert!(line.parse::<ElfPair>().is_err(), "{line}");
+        }
+        assert_eq!("2..4,6-8".parse::<ElfPair>().err(), Some(Day4Error::MissingHyphen { line: None, token: "2..4".to_string() }));
+
+        // Lenient parsing still rejects malformed ranges, and combines with normalizing
+        assert_eq!(ElfPair::parse_with("2..x,6..8", LENIENT).err(), Some(Day4Error::NotANumber { line: None, token: "x".to_string() }));
+        assert_eq!(ElfPair::parse_with("2 4,6..8", LENIENT).err(), Some(Day4Error::MissingHyphen { line: None, token: "2 4".to_string() }));
+        assert_eq!(ElfPair::parse_with("-3..5,6..8", LENIENT).err(), Some(Day4Error::NotANumber { line: None, token: "-3".to_string() }));
+        assert!(ElfPair::parse_with("4..2,6..8", LENIENT).is_err());
+        let options = ParseOptions { reversed: RangePolicy::Normalize, grammar: Grammar::Lenient };
+        assert_eq!(ElfPair::parse_with("4..2, 6..8", options).unwrap().to_string(), "2-4,6-8");
+        let (stats, _) = solve("2..4, 6..8\n2 - 8, 3 .. 7".as_bytes(), LENIENT).unwrap();
+        assert_eq!((stats.part1(), stats.part2()), (1, 1));
+        assert_eq!(matching_pairs("2..4, 6..8\n2 - 8, 3 .. 7", Relation::encompasses, LENIENT), Ok(vec![2]));
+        assert!(validate("2..4, 6..8\n2 - 8, 3 .. 7", ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn stats() {
+        let (stats, _) = solve(EXAMPLE_INPUT.as_bytes(), ParseOptions::default()).unwrap();
+        assert_eq!(stats, PairStats { contained: 2, identical: 0, partial: 2, disjoint: 2 });
+        assert_eq!((stats.part1(), stats.part2()), (2, 4));
+
+        // Identical pairs count as contained, and touching or nested single sections as overlapping
+        let (stats, _) = solve("4-6,4-6\n7-7,7-7\n1-2,3-4\n9-9,1-8\n1-5,5-9\n7-7,7-9".as_bytes(), ParseOptions::default()).unwrap();
+        assert_eq!(stats, PairStats { contained: 3, identical: 2, partial: 1, disjoint: 2 });
+        assert_eq!((stats.part1(), stats.part2()), (3, 4));
+        assert_eq!(stats.to_string(), "3 contained (2 identical), 1 partially overlapping, 2 disjoint");
+    }
+
+    // Builds 'length' lines of elf pairs, with sections from 1 to 99 as in the puzzle input
+    fn random_pairs(length: usize, mut seed: u64) -> String {
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % 99 + 1
+        };
+        let mut range = || {
+            let (a, b) = (next(), next());
+            format!("{}-{}", a.min(b), a.max(b))
+        };
+        (0..length).map(|_| format!("{},{}", range(), range())).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn streaming() {
+        let stats = solve_streaming(EXAMPLE_INPUT.as_bytes()).unwrap();
+        assert_eq!(stats, PairStats { contained: 2, identical: 0, partial: 2, disjoint: 2 });
+
+        // Windows line endings and a trailing newline are read as with 'lines'
+        assert_eq!(solve_streaming("2-4,6-8\r\n4-6,4-6\n".as_bytes()).unwrap(), PairStats { contained: 1, identical: 1, partial: 0, disjoint: 1 });
+
+        let input = random_pairs(10_000, 0x2022);
+        assert_eq!(solve_streaming(input.as_bytes()).unwrap(), solve(input.as_bytes(), ParseOptions::default()).unwrap().0);
+
+        // Errors carry the same line numbers as the struct-based path
+        for input in ["2-4,6-8\n3-x,5-7", "2-4,6-8\n\n1-2,3-4", "2-4,6-8\n2-4;6-8", "1-2,3-4\n2-4,6-8\n4-2,6-8"] {
+            let streamed = solve_streaming(input.as_bytes()).unwrap_err();
+            let built = solve(input.as_bytes(), ParseOptions::default()).unwrap_err();
+            assert_eq!(streamed.downcast_ref::<Day4Error>(), built.downcast_ref::<Day4Error>(), "{input}");
+        }
+    }
+
+    // Times the streaming solver against the struct-based one, over a million pairs
+    // Run with 'cargo test --release bench_streaming -- --ignored --nocapture'
+    #[test]
+    #[ignore]
+    fn bench_streaming() {
+        use std::time::Instant;
+
+        let input = random_pairs(1_000_000, 0x2022);
+
+        let start = Instant::now();
+        let (built, _) = solve(input.as_bytes(), ParseOptions::default()).unwrap();
+        let built_time = start.elapsed();
+
+        let start = Instant::now();
+        let streamed = solve_streaming(input.as_bytes()).unwrap();
+        let streamed_time = start.elapsed();
+
+        assert_eq!(built, streamed);
+        println!("struct-based: {built_time:?}, streaming: {streamed_time:?}");
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(validate(EXAMPLE_INPUT, ParseOptions::default()), Ok(()));
+
+        let input = "2-4,6-8\n2-3 4-5\n5-7,7-9\n2-8,3-7\n6-4,4-6\n2-6,4-8\n1-1,2-2\n3-3,3-3\n4-y,1-9\n9-9,1-1";
+        let errors = validate(input, ParseOptions::default()).unwrap_err();
+        assert_eq!(errors, [
+            (2, Day4Error::MissingComma { line: Some(2) }),
+            (5, Day4Error::ReversedRange { line: Some(5), start: 6, end: 4 }),
+            (9, Day4Error::NotANumber { line: Some(9), token: "y".to_string() }),
+        ]);
+        assert_eq!(Day4Errors(errors).to_string(), "3 bad line(s) found in day 4 input:
+  expected two comma-separated ranges on line 2
+  range 6-4 ends before it starts on line 5
+  range value 'y' is not a non-negative integer on line 9");
 
+        // The solver still stops at the first bad line
+        assert_eq!(solve(input.as_bytes(), ParseOptions::default()).unwrap_err().to_string(), "expected two comma-separated ranges on line 2");
+    }
+
+    #[test]
+    fn matching() {
+        assert_eq!(matching_pairs(EXAMPLE_INPUT, Relation::encompasses, ParseOptions::default()), Ok(vec![4, 5]));
+        assert_eq!(matching_pairs(EXAMPLE_INPUT, Relation::overlaps, ParseOptions::default()), Ok(vec![3, 4, 5, 6]));
+        assert_eq!(matching_pairs(EXAMPLE_INPUT, |r| r == Relation::Disjoint, ParseOptions::default()), Ok(vec![1, 2]));
+
+        // Blank lines are skipped without shifting line numbers, and errors carry their line
+        assert_eq!(matching_pairs("\n2-4,6-8\n\n  \n2-8,3-7\n", Relation::encompasses, ParseOptions::default()), Ok(vec![5]));
+        assert_eq!(matching_pairs("2-8,3-7\n\n3-x,5-7", Relation::encompasses, ParseOptions::default()),
+            Err(Day4Error::NotANumber { line: Some(3), token: "x".to_string() }));
+    }
+
+    #[test]
+    fn diagrams() {
+        // As in the puzzle description
+        let pair: ElfPair = "2-4,6-8".parse().unwrap();
+        assert_eq!(pair.render(9), ".234.....  ->  2-4\n.....678.  ->  6-8");
+        let pair: ElfPair = "6-6,4-6".parse().unwrap();
+        assert_eq!(pair.render(9), ".....6...  ->  6-6\n...456...  ->  4-6");
+
+        // Sections past 9 show their last digit, and ranges past the width are marked
+        let pair: ElfPair = "8-12,3-5".parse().unwrap();
+        assert_eq!(pair.render(12), ".......89012  ->  8-12\n..345.......  ->  3-5");
+        assert_eq!(pair.render(10), ".......89…  ->  8-12\n..345.....  ->  3-5");
+        let pair: ElfPair = "20-30,1-1".parse().unwrap();
+        assert_eq!(pair.render(5), "....…  ->  20-30\n1....  ->  1-1");
+
+        // Only pairs passing the filter are written
+        let mut dump = Vec::new();
+        let written = write_diagrams(EXAMPLE_INPUT.as_bytes(), &mut dump, 9, ElfPair::check_encompass, ParseOptions::default()).unwrap();
+        assert_eq!(written, 2);
+        assert_eq!(String::from_utf8(dump).unwrap(), "Line 4:
+.2345678.  ->  2-8
+..34567..  ->  3-7
+
+Line 5:
+.....6...  ->  6-6
+...456...  ->  4-6
+
+");
+    }
+
+    #[test]
+    fn report() {
+        let (_, report) = solve(EXAMPLE_INPUT.as_bytes(), ParseOptions::default()).unwrap();
+        assert_eq!(report, Report { pair_count: 6, total_overlap: 10, largest_overlap: Some((4, 5)), covered: vec![2..=9], normalized: 0 });
+        assert_eq!(report.to_string(), "6 pairs, 10 overlapping sections, largest overlap on line 4 with 5 sections, \
+                                        8 distinct sections covered in 1 run(s), longest 2-9");
+
+        let (_, report) = solve("2-3,5-5\n8-8,10-13\n".as_bytes(), ParseOptions::default()).unwrap();
+        assert_eq!(report.covered, [2..=3, 5..=5, 8..=8, 10..=13]);
+        assert_eq!(report.to_string(), "2 pairs, 0 overlapping sections, no pairs overlap, 8 distinct sections covered in 4 run(s), longest 10-13");
+
+        let (_, report) = solve("".as_bytes(), ParseOptions::default()).unwrap();
+        assert_eq!(report.to_string(), "0 pairs, 0 overlapping sections, no pairs overlap, 0 distinct sections covered in 0 run(s)");
+    }
+
+    #[test]
+    fn merging() {
+        // Overlapping, nested and adjacent ranges are joined, in any order
+        assert_eq!(merge_ranges(vec![5..=7, 1..=3, 2..=4, 9..=9]), [1..=7, 9..=9]);
+        assert_eq!(merge_ranges(vec![1..=10, 2..=3, 4..=11]), [1..=11]);
+        assert_eq!(merge_ranges(vec![4..=6, 1..=3, 7..=7]), [1..=7]);
+        assert_eq!(merge_ranges(vec![3..=3, 3..=3]), [3..=3]);
+
+        // Disjoint ranges are kept apart, and empty ranges dropped
+        assert_eq!(merge_ranges(vec![8..=9, 1..=2, 4..=6]), [1..=2, 4..=6, 8..=9]);
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 5..=4;
+        assert_eq!(merge_ranges(vec![1..=2, empty]), [1..=2]);
+        assert_eq!(merge_ranges(vec![]), []);
+        assert_eq!(merge_ranges(vec![0..=u64::MAX, 7..=9]), [0..=u64::MAX]);
+    }
+
+    // Checks every pair of ranges in 'line' with 'check', for comparison against the sorted ElfGroup checks
+    fn any_pair(line: &str, check: fn(&SectionRange, &SectionRange) -> bool) -> bool {
+        let ElfGroup(ranges) = line.parse().unwrap();
+        ranges.iter().enumerate().any(|(i, a)| ranges.iter().skip(i + 1).any(|b| check(a, b) || check(b, a)))
+    }
+
+    #[test]
+    fn groups() {
+        for (line, encompass, overlap) in [
+            // Two ranges behave as the puzzle's pairs
+            ("2-4,6-8", false, false), ("5-7,7-9", false, true), ("6-6,4-6", true, true),
+            // Only the first and last ranges overlap or encompass, with a range sorted between them
+            ("1-3,5-6,3-4", false, true), ("1-3,5-6,2-2", true, true), ("4-8,1-2,10-12", false, false),
+            // One range overlaps two others that are apart from each other, or an outer range holds a later one
+            ("10-12,1-2,20-30,4-5,11-25", false, true), ("1-9,20-21,30-31,40-41,2-3", true, true),
+            ("1-1,3-3,5-5,7-7,9-9", false, false), ("9-9,7-7,5-5,3-3,5-5", true, true),
+            // Equal starts, where the longer range encompasses the shorter
+            ("3-4,6-7,3-9", true, true), ("3-9,6-7,3-4", true, true),
+        ] {
+            let group: ElfGroup = line.parse().unwrap();
+            assert_eq!(group.any_encompass(), encompass, "{line}");
+            assert_eq!(group.any_overlap(), overlap, "{line}");
+            assert_eq!(any_pair(line, SectionRange::contains), encompass, "{line}");
+            assert_eq!(any_pair(line, SectionRange::overlaps), overlap, "{line}");
+        }
+
+        // Pairs give the same answers as ElfPair
+        for line in EXAMPLE_INPUT.lines() {
+            let (pair, group): (ElfPair, ElfGroup) = (line.parse().unwrap(), line.parse().unwrap());
+            assert_eq!(group.any_encompass(), pair.check_encompass(), "{line}");
+            assert_eq!(group.any_overlap(), pair.check_overlap(), "{line}");
+        }
+
+        // Fewer than two ranges are rejected
+        assert_eq!("2-4".parse::<ElfGroup>(), Err(Day4Error::MissingComma { line: None }));
+        assert_eq!("".parse::<ElfGroup>(), Err(Day4Error::MissingHyphen { line: None, token: "".to_string() }));
+        assert_eq!("2-4,6-8,x-9".parse::<ElfGroup>(), Err(Day4Error::NotANumber { line: None, token: "x".to_string() }));
+    }
+
+    #[test]
+    fn large_sections() {
+        // IDs past u32::MAX parse, and the overlap and containment checks do not overflow
+        let pair: ElfPair = "4294967290-4294967300,4294967295-18446744073709551615".parse().unwrap();
+        assert_eq!(pair.relation(), Relation::Overlap);
+        assert_eq!(pair.intersection(), Some(4294967295..=4294967300));
+        assert_eq!(pair.overlap_len(), 6);
+        let pair: ElfPair = "0-18446744073709551615,0-18446744073709551615".parse().unwrap();
+        assert_eq!(pair.relation(), Relation::Equal);
+        assert_eq!(pair.overlap_len(), u64::MAX);
+        let (stats, report) = solve("0-18446744073709551615,5-5\n0-18446744073709551615,18446744073709551615-18446744073709551615".as_bytes(), ParseOptions::default()).unwrap();
+        assert_eq!(stats.part1(), 2);
+        assert_eq!((report.total_overlap, report.sections_covered()), (2, u64::MAX));
+
+        // Negative values are rejected rather than wrapped
+        assert_eq!("-3-5,1-2".parse::<ElfPair>().err(), Some(Day4Error::NotANumber { line: None, token: "-3".to_string() }));
+        assert_eq!(solve_streaming("1-2,3-4\n1-2,-4-3".as_bytes()).unwrap_err().to_string(), "range value '-4' is not a non-negative integer on line 2");
+    }
+
+    #[test]
+    fn section_ranges() {
+        let outer = SectionRange(2..=8);
+        let inner = SectionRange(3..=7);
+        assert!(outer.contains(&inner));
+        assert!(!inner.contains(&outer));
+        assert!(outer.contains(&outer));
+        assert!(outer.overlaps(&inner) && inner.overlaps(&outer));
+        assert!(!SectionRange(1..=1).overlaps(&SectionRange(2..=2)));
+        assert_eq!("10-12".parse::<SectionRange>().unwrap(), SectionRange(10..=12));
+    }
+
+}
@@ -13,203 +13,865 @@ use super::Regex;
 use lazy_static::lazy_static;
 
 // Cargo with a number of LIFO stacks that can have objects amongst the stack tops.
-// Items are repesented by chars
-struct Cargo {
-    stacks: Vec<Vec<char>> // set of many different stacks of items
+// Items are repesented by their labels, which are chars in the puzzle, but may be longer (eg: String)
+#[derive(Debug, Clone)]
+struct Cargo<T = char> {
+    stacks: Vec<Vec<T>>, // set of many different stacks of items
+    label_width: usize, // number of characters between the brackets of each item in a drawing
+    empty_stacks: EmptyStackPolicy, // what a crane moving items one at a time does when it runs out of items
+    recording: HistoryPolicy, // whether applied moves are kept so they can be undone
+    history: Vec<AppliedMove<T>>, // moves applied so far while recording, oldest first
+    checks: CheckPolicy, // whether each applied move is checked to neither lose nor duplicate items
+    #[cfg(test)]
+    after_move: Option<fn(&mut Cargo<T>)>, // called after each move's items are moved, so tests can corrupt the stacks
 }
 
-// Run challenge.
-// Main entry point to day 5 challenge.
-pub fn run(part_2: bool) -> Result<(), Box<dyn error::Error>> {
+// Whether a Cargo checks that moves keep the number of items it holds the same
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+enum CheckPolicy {
+    #[default]
+    Unchecked, // trust the move logic
+    Conserve, // count every item before and after each move, failing with a Day5Error::InvariantViolation if they differ
+}
+
+// Whether a Cargo keeps a history of the moves applied to it
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+enum HistoryPolicy {
+    #[default]
+    Off, // keep nothing, so applying a move never allocates
+    #[cfg(test)] // only used when stepping through moves in tests
+    Record, // keep every applied move, so it can be undone
+}
+
+// A move that has been applied to a Cargo, and the stacks it moved items between as they were before the move
+// Whole stacks are kept, as a crane may take its items from anywhere in the 'from' stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AppliedMove<T = char> {
+    mv: Move, // the move as requested
+    from_stack: Vec<T>, // the 'from' stack before the move, bottom first
+    to_stack: Vec<T>, // the 'to' stack before the move, bottom first
+}
+
+// What a crane moving items one at a time does when asked to move more items than its stack has
+// A crane moving items as a group always fails, as it cannot lift a group that is not there.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+enum EmptyStackPolicy {
+    #[default]
+    Skip, // move the items there are and ignore the rest, as the puzzle never runs out
+    Fail, // fail with a Day5Error::TooFewItems before moving anything
+}
 
-    // File input has been split into two files for convenience
-    let f = File::open("input/day5input_starting.txt")?;
-    let buf = BufReader::new(f);
+// A crane, which moves items between two stacks
+// 'transfer' takes 'count' items from 'from' and puts them on 'to', failing without changing either stack if it cannot.
+trait Crane<T = char> {
+    fn transfer(&self, from: &mut Vec<T>, to: &mut Vec<T>, count: usize) -> Result<(), TooFewItemsError>;
+}
 
-    // Initialize new Cargo instance with given size
-    let mut item_string_list: Vec<_> = buf.lines().map(|s| s.unwrap()).collect();
-    let mut cargo = Cargo::new(get_last_digit_in_string(&item_string_list.pop().unwrap())? as usize);
+// The CrateMover 9000 (part 1), which moves items singly, so a group ends up reversed
+// Moving items singly, it stops early rather than failing if the stack runs out.
+struct CrateMover9000;
 
-    // Load items into Cargo stacks from the bottom up
-    item_string_list.reverse();
-    for line in item_string_list {
-        cargo.parse_row(&line)?;
+impl<T> Crane<T> for CrateMover9000 {
+    fn transfer(&self, from: &mut Vec<T>, to: &mut Vec<T>, count: usize) -> Result<(), TooFewItemsError> {
+        let from_length = from.len();
+        to.extend(from.drain(from_length - count.min(from_length)..).rev());
+        Ok(())
     }
-    
-    // Load items into Cargo stacks from the bottom up
-    let f = File::open("input/day5input_moving.txt")?;
-    let buf = BufReader::new(f);
-    for line in buf.lines() {
+}
+
+// The CrateMover 9001 (part 2), which moves a group together, keeping its order
+struct CrateMover9001;
+
+impl<T> Crane<T> for CrateMover9001 {
+    fn transfer(&self, from: &mut Vec<T>, to: &mut Vec<T>, count: usize) -> Result<(), TooFewItemsError> {
+        let from_length = from.len();
+        if from_length < count {
+            return Err(TooFewItemsError { wanted: count, available: from_length });
+        }
+        to.extend(from.drain(from_length - count..));
+        Ok(())
+    }
+}
+
+// How a crane moves a group of items between stacks, naming each of the puzzle's cranes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CraneMode {
+    OneAtATime, // CrateMover 9000 (part 1): items are moved singly, so a group ends up reversed
+    InGroups, // CrateMover 9001 (part 2): a group is moved together, keeping its order
+}
+
+impl<T> Crane<T> for CraneMode {
+    fn transfer(&self, from: &mut Vec<T>, to: &mut Vec<T>, count: usize) -> Result<(), TooFewItemsError> {
+        match self {
+            CraneMode::OneAtATime => CrateMover9000.transfer(from, to, count),
+            CraneMode::InGroups => CrateMover9001.transfer(from, to, count),
+        }
+    }
+}
+
+// A movement command, with zero-indexed stacks
+// eg: "move 3 from 1 to 2" => Move { count: 3, from: 0, to: 1 }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Move {
+    count: usize, // number of items to move
+    from: usize, // stack the items are taken from
+    to: usize, // stack the items are put on
+}
+
+// Which spellings of a movement command are accepted
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+enum Grammar {
+    #[default]
+    Strict, // exactly "move a from b to c", as in the puzzle (surrounding whitespace, such as a '\r', is ignored)
+    Lenient, // any case and spacing, ignoring anything after a '#'
+}
+
+impl Grammar {
+    // Gets the part of 'line' that holds the command, without any comment
+    fn command(self, line: &str) -> &str {
+        match self {
+            Grammar::Strict => line,
+            Grammar::Lenient => line.split('#').next().unwrap_or(""),
+        }
+    }
+}
+
+impl Move {
+    // Parses a movement command in 'grammar', which must follow format "move a from b to c" where 'a' is the number of items,
+    // and items are moved from stack 'b' to 'c'. Stacks are numbered from 1, but whether they exist is only known
+    // once the move is applied to a Cargo.
+    // eg: "MOVE 1  from 2 To 3 # note" parses with Grammar::Lenient, but not with Grammar::Strict
+    fn parse_with(command_str: &str, grammar: Grammar) -> Result<Move, Day5Error> {
+        lazy_static! {
+            static ref REGEX_MOVE_FROM: Regex = Regex::new(r"^move (\S+) from (\S+) to (\S+)$").unwrap();
+        }
+        let fields = match grammar {
+            Grammar::Strict => REGEX_MOVE_FROM.captures(command_str.trim())
+                .map(|caps| [1, 2, 3].map(|i| caps.get(i).unwrap().as_str())),
+            Grammar::Lenient => match grammar.command(command_str).split_whitespace().collect::<Vec<_>>()[..] {
+                [keyword_move, count, keyword_from, from, keyword_to, to] if keyword_move.eq_ignore_ascii_case("move")
+                    && keyword_from.eq_ignore_ascii_case("from") && keyword_to.eq_ignore_ascii_case("to") => Some([count, from, to]),
+                _ => None,
+            },
+        };
+        let [count, from, to] = fields.ok_or_else(|| Day5Error::UnmatchedLine { line: None, text: command_str.to_string() })?;
+
+        // Every field was found, so only its value can be invalid
+        let field = |s: &str| s.parse::<usize>().map_err(|_| Day5Error::NotANumber { line: None, field: s.to_string() });
+        let stack = |s: &str| match field(s)? {
+            0 => Err(Day5Error::StackZero { line: None }),
+            stack => Ok(stack - 1),
+        };
+        Ok(Move {
+            count: field(count)?,
+            from: stack(from)?,
+            to: stack(to)?,
+        })
+    }
+}
+
+// Parses a movement command in the puzzle's strict grammar (see 'Move::parse_with')
+impl str::FromStr for Move {
+    type Err = Day5Error;
+
+    fn from_str(command_str: &str) -> Result<Move, Day5Error> {
+        Move::parse_with(command_str, Grammar::Strict)
+    }
+}
 
-        // Use regex to match text to expetected challenge text
-        let line = line?;
-        cargo.parse_command(&line, part_2);        
+// Run challenge.
+// Main entry point to day 5 challenge.
+pub fn run(part_2: bool, options: &Options) -> Result<(), Box<dyn error::Error>> {
+    let (starting, moving, moving_line) = read_input()?;
+    let mode = if part_2 { CraneMode::InGroups } else { CraneMode::OneAtATime };
+    let checks = if options.check { CheckPolicy::Conserve } else { CheckPolicy::Unchecked };
+    let empty_stacks = if options.check { EmptyStackPolicy::Fail } else { EmptyStackPolicy::Skip };
+    let grammar = if options.lenient { Grammar::Lenient } else { Grammar::Strict };
+
+    // In validate mode, check every move can be made before making any of them
+    if options.validate {
+        let numbered = parse_moves(&moving, moving_line, grammar)?;
+        let moves: Vec<Move> = numbered.iter().map(|&(_, mv)| mv).collect();
+        let mut cargo = Cargo::from_drawing(&starting)?;
+        cargo.empty_stacks = empty_stacks;
+        cargo.validate_moves(&moves, mode)
+            .map_err(|(i, e)| Day5Error::from(e).on_line(numbered[i].0))?;
     }
+
+    let cargo = solve(&starting, &moving, moving_line, mode, empty_stacks, checks, grammar)?;
+
     let part = if part_2 {2} else {1};
-    println!("Result for day 5-{part} = {}",cargo.get_top_chars());
+    println!("Result for day 5-{part} = {}",cargo.get_top_chars()?);
+
+    // In verbose mode, draw the stacks before the moves (once), and after them for each part
+    if options.verbose {
+        if !part_2 {
+            println!("Starting stacks:\n{}", Cargo::from_drawing(&starting)?);
+        }
+        println!("Stacks after part {part}:\n{cargo}");
+        let tallest = cargo.stack_heights().into_iter().max().unwrap_or(0);
+        println!("{} stacks, {} crates, tallest stack {tallest}", cargo.num_stacks(), cargo.total_crates());
+    }
     Ok(())
     
 }
 
+// Reads the starting stacks and the movement commands, returning them as separate sections
+// alongside the (one-indexed) line of their file that the movement commands start on.
+// The puzzle input is a single file with the sections separated by a blank line, but inputs previously split
+// into 'day5input_starting.txt' and 'day5input_moving.txt' are still read if there is no single file.
+fn read_input() -> Result<(String, String, usize), Box<dyn error::Error>> {
+    match File::open("input/day5input.txt") {
+        Ok(mut f) => {
+            let mut input = String::new();
+            f.read_to_string(&mut input)?;
+            let (starting, moving) = split_input(&input)?;
+            Ok((starting.to_string(), moving.to_string(), starting.lines().count() + 2))
+        },
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            let mut starting = String::new();
+            File::open("input/day5input_starting.txt")?.read_to_string(&mut starting)?;
+            let mut moving = String::new();
+            File::open("input/day5input_moving.txt")?.read_to_string(&mut moving)?;
+            Ok((starting, moving, 1))
+        },
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+// Splits a combined input at its first blank line into the starting stacks (with their numbered footer) and the movement commands
+// Leading spaces are kept, as they place items in the right stacks.
+fn split_input(input: &str) -> Result<(&str, &str), Error> {
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            return Ok((&input[..offset], &input[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    Err(Error::other("Could not find the blank line separating the starting stacks from the movement commands."))
+}
+
+// Builds the starting Cargo from 'starting' (see 'Cargo::from_drawing'), then applies every movement command in 'moving' (see 'Move::parse_with' and 'Cargo::apply')
+// Blank lines in 'moving' are skipped, and errors carry their line number, counting the first line of 'moving' as 'moving_line'.
+// 'empty_stacks' - whether moving one at a time from a stack that runs out fails, rather than moving what there is
+// 'checks' - whether every move is checked to neither lose nor duplicate items
+// 'grammar' - which spellings of movement commands are accepted, where lines with only a comment count as blank in Grammar::Lenient
+fn solve(starting: &str, moving: &str, moving_line: usize, mode: CraneMode, empty_stacks: EmptyStackPolicy, checks: CheckPolicy, grammar: Grammar)
+    -> Result<Cargo, Box<dyn error::Error>> {
+    let mut cargo = Cargo::from_drawing(starting)?;
+    cargo.empty_stacks = empty_stacks;
+    cargo.checks = checks;
+
+    // Apply each movement command in turn
+    for (i, line) in moving.lines().enumerate() {
+        if grammar.command(line).trim().is_empty() {
+            continue;
+        }
+        let mv = Move::parse_with(line, grammar).map_err(|e| e.on_line(moving_line + i))?;
+        cargo.apply(&mv, &mode).map_err(|e| e.on_line(moving_line + i))?;
+    }
+    Ok(cargo)
+}
+
+// Parses every movement command in 'moving' (see 'Move::parse_with'), alongside the line each is on
+// Lines are numbered and skipped as in 'solve'.
+fn parse_moves(moving: &str, moving_line: usize, grammar: Grammar) -> Result<Vec<(usize, Move)>, Day5Error> {
+    moving.lines().enumerate()
+        .filter(|(_, line)| !grammar.command(line).trim().is_empty())
+        .map(|(i, line)| Move::parse_with(line, grammar).map(|mv| (moving_line + i, mv)).map_err(|e| e.on_line(moving_line + i)))
+        .collect()
+}
+
 impl Cargo {
     // Creates new Cargo with 'num_stacks' many stacks
-    fn new(num_stacks: usize) -> Cargo{
-        let mut stacks: Vec<Vec<char>> = Vec::new();
+    #[cfg(test)] // the puzzle's Cargo is built with 'from_drawing'
+    fn new(num_stacks: usize) -> Cargo {
+        Cargo::with_stacks(num_stacks)
+    }
+
+    // Builds a Cargo from the drawing of its stacks, as in the puzzle input
+    // The last line must number the stacks from 1, and the rows above it are loaded from the bottom up.
+    // Rows may have their trailing spaces stripped, but must not be wider than the footer allows.
+    // eg: "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 "
+    fn from_drawing(drawing: &str) -> Result<Cargo, Day5Error> {
+        Cargo::from_labelled_drawing(drawing)
+    }
+}
+
+impl<T: Clone + str::FromStr> Cargo<T> {
+    // Creates new Cargo with 'num_stacks' many stacks of labels of type 'T', drawn a single character wide
+    fn with_stacks(num_stacks: usize) -> Cargo<T> {
+        let mut stacks: Vec<Vec<T>> = Vec::new();
         for _ in 0..num_stacks {
             stacks.push(Vec::new());
         }
         Cargo {
-            stacks
+            stacks,
+            label_width: 1,
+            empty_stacks: EmptyStackPolicy::default(),
+            recording: HistoryPolicy::default(),
+            history: Vec::new(),
+            checks: CheckPolicy::default(),
+            #[cfg(test)]
+            after_move: None,
         }
     }
 
-    // Parses row of items into items on the top of stacks.
-    // Line must follow space-separated format (i is item, which is either a word char or a space):
-    // 'i i i i i i'
-    // Each item is added to the top of its corresponding stack in order, starting from the first stack. 
-    // If a space is found for an item, that stack is skipped.
-    // eg: 'i i   i i'
-    fn parse_row(&mut self, row_str: &str) -> Result<(), Error> {
-        lazy_static! {
-            static ref REGEX_CAPTURE_STACKS: Regex = Regex::new(r"(\w|\s\s\s\s)").unwrap();
+    // Builds a Cargo from the drawing of its stacks, as in 'Cargo::from_drawing', but with labels of type 'T'
+    // Labels may be longer than a character, with every column as wide as the widest label, and must parse as 'T'.
+    // eg: "[AB]     \n[C7] [D]\n  1    2  " for String labels
+    fn from_labelled_drawing(drawing: &str) -> Result<Cargo<T>, Day5Error> {
+        let mut rows: Vec<&str> = drawing.lines().collect();
+        while rows.last().is_some_and(|row| row.trim().is_empty()) {
+            rows.pop();
         }
+        let footer = rows.pop().ok_or(Day5Error::InvalidFooter { line: None, text: String::new() })?;
 
-        // Search for word chars or several spaces in a row (representing a skipped stack)
-        for (i,regmatch) in REGEX_CAPTURE_STACKS.find_iter(row_str).into_iter().enumerate() {
-            match regmatch.as_str().chars().nth(0) {
-                Some(' ') => (), // match the whitespace to ensure stacks can be 'skipped' over
-                Some(c) => self.stacks[i].push(c),
-                None => ()
+        // The footer must count up from 1, and gives the number of stacks
+        let footer_line = rows.len() + 1;
+        let num_stacks = footer.split_whitespace().enumerate().try_fold(0, |_, (i, n)| match n.parse::<usize>() {
+            Ok(n) if n == i + 1 => Ok(n),
+            _ => Err(Day5Error::InvalidFooter { line: Some(footer_line), text: footer.to_string() }),
+        })?;
+        if num_stacks == 0 {
+            return Err(Day5Error::InvalidFooter { line: Some(footer_line), text: footer.to_string() });
+        }
+
+        // Each stack takes the label and its brackets, with a space between stacks
+        let mut cargo = Cargo::with_stacks(num_stacks);
+        cargo.label_width = rows.iter().flat_map(|row| bracketed(row)).map(|(_, label)| label.chars().count()).max().unwrap_or(1);
+        let max_width = num_stacks * (cargo.label_width + 3) - 1;
+        for (i, row) in rows.iter().enumerate() {
+            let width = row.trim_end().chars().count();
+            if width > max_width {
+                return Err(Day5Error::RowTooWide { line: Some(i + 1), width, max_width });
+            }
+            if let Some((_, label)) = bracketed(row).find(|(_, label)| label.trim().parse::<T>().is_err()) {
+                return Err(Day5Error::InvalidLabel { line: Some(i + 1), label: label.to_string() });
             }
         }
+
+        // Load items into Cargo stacks from the bottom up
+        for row in rows.iter().rev() {
+            cargo.parse_row(row);
+        }
+        Ok(cargo)
+    }
+
+    // Parses row of items into items on the top of stacks.
+    // Each stack's item sits in its own column, as wide as the Cargo's labels and their brackets, with a space between columns:
+    // '[i] [i]     [i]'
+    // Each item is added to the top of its corresponding stack, found from its position in the row.
+    // If a column is blank (or cut short by stripped trailing spaces), that stack is skipped, as are labels that do not parse.
+    fn parse_row(&mut self, row_str: &str) {
+        // The item for stack 'i' starts at column (label_width + 3)*i
+        let column_width = self.label_width + 3;
+        for (start, label) in bracketed(row_str) {
+            if start % column_width == 0 {
+                if let Ok(label) = label.trim().parse() {
+                    self.stacks[start / column_width].push(label);
+                }
+            }
+        }
+    }
+
+    // Parses movement command and applies it to Cargo instance with 'crane' (see 'Move::from_str' and 'Cargo::apply')
+    #[cfg(test)] // the run loop parses every move before applying it
+    fn parse_command(&mut self, command_str: &str, crane: &dyn Crane<T>) -> Result<(), Day5Error> {
+        self.apply(&command_str.parse()?, crane)
+    }
+
+    // Applies a movement command with 'crane', such as one of the puzzle's CraneModes
+    // A failed move leaves the stacks unchanged, unless it fails the checks after moving its items.
+    fn apply(&mut self, mv: &Move, crane: &dyn Crane<T>) -> Result<(), Day5Error> {
+        self.check_indices(mv.from, mv.to)?;
+        let available = self.stacks[mv.from].len();
+        if self.empty_stacks == EmptyStackPolicy::Fail && available < mv.count {
+            return Err(MoveError::TooFewItems { index: mv.from, wanted: mv.count, available }.into());
+        }
+
+        // Note the stacks as they are, so the move can be undone
+        let before = match self.recording {
+            HistoryPolicy::Off => None,
+            #[cfg(test)]
+            HistoryPolicy::Record => Some((self.stacks[mv.from].clone(), self.stacks[mv.to].clone())),
+        };
+
+        let total = match self.checks {
+            CheckPolicy::Unchecked => 0,
+            CheckPolicy::Conserve => self.total_crates(),
+        };
+
+        self.transfer(mv.from, mv.to, mv.count, crane)?;
+
+        #[cfg(test)]
+        if let Some(after_move) = self.after_move {
+            after_move(self);
+        }
+
+        // A move that loses or duplicates items leaves the stacks as they are, so the mistake can be seen
+        if self.checks == CheckPolicy::Conserve && self.total_crates() != total {
+            return Err(Day5Error::InvariantViolation { line: None, expected: total, actual: self.total_crates() });
+        }
+
+        if let Some((from_stack, to_stack)) = before {
+            self.history.push(AppliedMove { mv: *mv, from_stack, to_stack });
+        }
         Ok(())
     }
 
-    // Parses movement command and applies it to Cargo instance
-    // Command 'command_str must follow format  "move a from b to c" where 'a' is the number of items, and items are moved from stack 'b' to 'c'.
-    // In part 1, groups of items are moved one at a time.
-    // In part 2, groups of items are moved together.
-    fn parse_command(&mut self, command_str: &str, part_2: bool) {
-        // Parse movement command from input file into correct 
-        lazy_static! {
-            static ref REGEX_MOVE_FROM: Regex = Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap();
+    // Checks that every one of 'moves' could be made in turn by a crane working in 'mode', without moving anything
+    // Only the height of each stack is followed, and the first move that would fail is returned with its index in 'moves'.
+    fn validate_moves(&self, moves: &[Move], mode: CraneMode) -> Result<(), (usize, MoveError)> {
+        let mut heights = self.stack_heights();
+        for (i, mv) in moves.iter().enumerate() {
+            self.check_indices(mv.from, mv.to).map_err(|e| (i, e))?;
+
+            // Only a crane moving items singly can stop early, and then only if allowed to
+            let available = heights[mv.from];
+            if available < mv.count && (mode == CraneMode::InGroups || self.empty_stacks == EmptyStackPolicy::Fail) {
+                return Err((i, MoveError::TooFewItems { index: mv.from, wanted: mv.count, available }));
+            }
+            let moved = mv.count.min(available);
+            heights[mv.from] -= moved;
+            heights[mv.to] += moved;
         }
-        let caps = REGEX_MOVE_FROM.captures(&command_str).unwrap();  
-        let item_move_count = caps.get(1).unwrap().as_str().parse().unwrap();
-        let from_stack = caps.get(2).unwrap().as_str().parse::<usize>().unwrap() - 1;//stacks are one-indexed in .txt
-        let to_stack = caps.get(3).unwrap().as_str().parse::<usize>().unwrap() - 1; //stacks are one-indexed in .txt
+        Ok(())
+    }
 
-        if part_2 {
-            // Part 2 - move n-sized groups of objects, keeping the same order.
-            self.move_top_n_items_between_stacks(from_stack, to_stack, item_move_count).unwrap();
-        } else {
-            // Part 1 - move n-sized number of objects, one at a time
-            for _ in 0..item_move_count{
-                self.move_top_item_between_stacks(from_stack, to_stack);
+    // Replays 'moves' with 'crane' on a copy of this Cargo, yielding the state after each move in turn
+    // States are only made as they are asked for, and the first failed move is yielded as an error, ending the replay.
+    #[cfg(test)] // only used to step through the rearrangement in tests
+    fn replay<'a>(&self, moves: &'a [Move], crane: &'a dyn Crane<T>) -> impl Iterator<Item = Result<Cargo<T>, Day5Error>> + 'a
+    where T: 'a {
+        let mut cargo = Some(self.clone());
+        moves.iter().map_while(move |mv| {
+            let current = cargo.as_mut()?;
+            match current.apply(mv, crane) {
+                Ok(()) => Some(Ok(current.clone())),
+                Err(e) => {
+                    cargo = None;
+                    Some(Err(e))
+                },
             }
+        })
+    }
+
+    // Gets the items in stack 'i' (zero-indexed), bottom first, or None if there is no such stack
+    // eg: for the puzzle's example, get_stack(1) == Some(&['M','C','D'][..])
+    #[cfg(test)] // for tests inspecting a single stack
+    fn get_stack(&self, i: usize) -> Option<&[T]> {
+        self.stacks.get(i).map(Vec::as_slice)
+    }
+
+    // Gets the number of items in each stack, in order
+    // eg: for the puzzle's example, stack_heights() == vec![2, 3, 1]
+    fn stack_heights(&self) -> Vec<usize> {
+        self.stacks.iter().map(Vec::len).collect()
+    }
+
+    // Gets the number of stacks, including empty ones
+    // eg: for the puzzle's example, num_stacks() == 3
+    fn num_stacks(&self) -> usize {
+        self.stacks.len()
+    }
+
+    // Counts the items across every stack
+    // eg: for the puzzle's example, total_crates() == 6
+    fn total_crates(&self) -> usize {
+        self.stacks.iter().map(Vec::len).sum()
+    }
+
+    // Reverses the most recently recorded move, putting both of its stacks back as they were
+    #[cfg(test)]
+    fn undo(&mut self) -> Result<(), NothingToUndoError> {
+        let applied = self.history.pop().ok_or(NothingToUndoError)?;
+        self.stacks[applied.mv.to] = applied.to_stack;
+        self.stacks[applied.mv.from] = applied.from_stack;
+        Ok(())
+    }
+
+    // Moves applied so far while recording, oldest first
+    #[cfg(test)]
+    fn history(&self) -> &[AppliedMove<T>] {
+        &self.history
+    }
+
+    // Checks that 'from_ind' and 'to_ind' are both indices of this Cargo's stacks
+    fn check_indices(&self, from_ind: usize, to_ind: usize) -> Result<(), MoveError> {
+        match [from_ind, to_ind].into_iter().find(|&index| index >= self.stacks.len()) {
+            Some(index) => Err(MoveError::InvalidStackIndex { index, stacks: self.stacks.len() }),
+            None => Ok(()),
         }
     }
 
     // Moves last item in stack 'from_ind' to the top of stack 'to_ind'
-    // Panics if from_ind or to_ind exceeds the number of stacks
-    fn move_top_item_between_stacks(&mut self, from_ind: usize, to_ind: usize) {
-        if self.stacks[from_ind].len() > 0 {
-            let item = self.stacks[from_ind].pop().unwrap();
-            self.stacks[to_ind].push(item);
-        }
+    // Fails without changing any stack if there is no item to move, or from_ind or to_ind exceeds the number of stacks
+    #[cfg(test)] // moves are applied in batches, which tests check against this
+    fn move_top_item_between_stacks(&mut self, from_ind: usize, to_ind: usize) -> Result<(), MoveError> {
+        self.check_indices(from_ind, to_ind)?;
+        let item = self.stacks[from_ind].pop().ok_or(MoveError::TooFewItems { index: from_ind, wanted: 1, available: 0 })?;
+        self.stacks[to_ind].push(item);
+        Ok(())
     }
-    
 
     // Moves top 'num_items' in stack 'from_ind' to the top of stack 'to_ind'
-    // Panics if from_ind or to_ind exceeds the number of stacks
-    fn move_top_n_items_between_stacks(&mut self, from_ind: usize, to_ind: usize, num_items: usize) -> Result<(), StackTooSmallError> {        
-        if self.stacks[from_ind].len() >= num_items {
-
-            // Create iter_mut of Vec<Vec<>> and use nth to leverage unsafe code in iter_mut to 
-            // get two mutable references to different elemenets of self.stacks
-            let mut stacks_iter = self.stacks.iter_mut();
-            let from_stack;
-            let to_stack;
-
-            // nth consumes mut_iter up to nth element, so extract elements in correct order
-            // second nth is reduced by index of first and 1 more (as nth is removed)
-            if to_ind > from_ind {
-                from_stack = stacks_iter.nth(from_ind).unwrap();
-                to_stack = stacks_iter.nth(to_ind - from_ind - 1).unwrap();
-            } else if from_ind > to_ind {
-                to_stack = stacks_iter.nth(to_ind).unwrap();
-                from_stack = stacks_iter.nth(from_ind - to_ind - 1).unwrap();    
-            } else {
-               return Ok(()); // Do nothing if we are taking and moving to the same stack
-            }
-            
-            // Extract items to move, unwrapping is fine as we've already checked length
-            let from_length = from_stack.len();
-            let items = from_stack.get(from_length-num_items..).unwrap();
+    // Fails without changing any stack if there are too few items, or from_ind or to_ind exceeds the number of stacks
+    #[cfg(test)] // moves are applied with 'transfer', which tests check against this
+    fn move_top_n_items_between_stacks(&mut self, from_ind: usize, to_ind: usize, num_items: usize) -> Result<(), MoveError> {
+        self.check_n_items(from_ind, to_ind, num_items)?;
+        self.transfer(from_ind, to_ind, num_items, &CrateMover9001)
+    }
 
-            // Put them into 'to' stack
-            to_stack.extend_from_slice(items);            
+    // Moves 'count' items from stack 'from_ind' to stack 'to_ind' with 'crane'
+    // Fails without changing any stack if the crane cannot move them, or from_ind or to_ind exceeds the number of stacks.
+    // Moving items from a stack onto itself leaves it as it is.
+    fn transfer(&mut self, from_ind: usize, to_ind: usize, count: usize, crane: &dyn Crane<T>) -> Result<(), MoveError> {
+        self.check_indices(from_ind, to_ind)?;
+        match self.stack_pair(from_ind, to_ind) {
+            Some((from_stack, to_stack)) => crane.transfer(from_stack, to_stack, count)
+                .map_err(|e| MoveError::TooFewItems { index: from_ind, wanted: e.wanted, available: e.available }),
+            None => Ok(()),
+        }
+    }
 
-            // Remove items from 'from' stack
-            from_stack.truncate(from_length - num_items);
+    // Checks that both stacks exist, and stack 'from_ind' has at least 'num_items' items
+    #[cfg(test)]
+    fn check_n_items(&self, from_ind: usize, to_ind: usize, num_items: usize) -> Result<(), MoveError> {
+        self.check_indices(from_ind, to_ind)?;
+        match self.stacks[from_ind].len() {
+            available if available < num_items => Err(MoveError::TooFewItems { index: from_ind, wanted: num_items, available }),
+            _ => Ok(()),
+        }
+    }
 
-            Ok(())
+    // Gets mutable references to two different stacks, or None if they are the same stack (when moving does nothing)
+    fn stack_pair(&mut self, from_ind: usize, to_ind: usize) -> Option<(&mut Vec<T>, &mut Vec<T>)> {
+        // Split the stacks after the lower index, so each half holds one of the two
+        if from_ind < to_ind {
+            let (lower, upper) = self.stacks.split_at_mut(to_ind);
+            Some((&mut lower[from_ind], &mut upper[0]))
+        } else if to_ind < from_ind {
+            let (lower, upper) = self.stacks.split_at_mut(from_ind);
+            Some((&mut upper[0], &mut lower[to_ind]))
         } else {
-            Err(StackTooSmallError)
+            None
         }
     }
 
-    // Gets the top character of each stack (as a String)
-    fn get_top_chars(& self) -> String {
-        let mut s = String::new();
-        for stack in self.stacks.iter() {
-            s.push(*stack.last().unwrap());
+    // Gets the label of the top item of each stack, failing on the first empty stack
+    fn top_labels(&self) -> Result<Vec<&T>, EmptyStackError> {
+        self.stacks.iter().enumerate()
+            .map(|(i, stack)| stack.last().ok_or(EmptyStackError { stack: i + 1 }))
+            .collect()
+    }
+}
+
+impl<T: Clone + str::FromStr + fmt::Display> Cargo<T> {
+    // Gets the top label of each stack joined into a String, which for the puzzle's single character labels is the answer
+    fn get_top_chars(&self) -> Result<String, EmptyStackError> {
+        Ok(self.top_labels()?.iter().map(|label| label.to_string()).collect())
+    }
+}
+
+// Finds each bracketed label in a drawing row, with the (character) column its opening bracket is at
+// eg: "    [D] [AB]" => (4, "D"), (8, "AB")
+fn bracketed(row: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut rest = row.char_indices().enumerate();
+    std::iter::from_fn(move || {
+        let (start, (open, _)) = rest.find(|(_, (_, c))| *c == '[')?;
+        let (_, (close, _)) = rest.find(|(_, (_, c))| *c == ']')?;
+        Some((start, &row[open + 1..close]))
+    })
+}
+
+// Draws the stacks as in the puzzle description, from the tallest stack's top row down to the numbered footer
+// Each item is drawn as '[X]' in its stack's column, with empty slots left blank.
+// eg: "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 "
+impl<T: fmt::Display> fmt::Display for Cargo<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width, column_width) = (self.label_width, self.label_width + 2);
+        let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0);
+        for row in (0..height).rev() {
+            let cells: Vec<String> = self.stacks.iter()
+                .map(|stack| stack.get(row).map_or(" ".repeat(column_width), |c| format!("[{c:<width$}]")))
+                .collect();
+            writeln!(f, "{}", cells.join(" "))?;
         }
-        s
+        let footer: Vec<String> = (1..=self.stacks.len()).map(|i| format!("{i:^column_width$}")).collect();
+        write!(f, "{}", footer.join(" "))
     }
 }
 
-// Gets last instance of a digit in a string of characters
-// eg: 'fineoin423inod43dfe' -> 3
-fn get_last_digit_in_string(s: &str) -> Result<u32, Error> {
-    let mut i = 0;
-    let mut found = false;
-    for c in s.chars() {
-        match c.to_digit(10) {
-            Some(v) => {i = v; found = true},
-            None => ()
+// Ways a move between stacks can fail, with zero-indexed stacks
+#[derive(Debug, Clone, PartialEq)]
+enum MoveError {
+    InvalidStackIndex { index: usize, stacks: usize }, // the stack index is not less than the number of stacks
+    TooFewItems { index: usize, wanted: usize, available: usize }, // the stack has fewer items than are being moved
+}
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidStackIndex { index, stacks } => write!(f, "stack index {index} is out of range for {stacks} stacks"),
+            Self::TooFewItems { index, wanted, available } =>
+                write!(f, "cannot move {wanted} items from stack index {index}, which only has {available}"),
         }
     }
-    if !found {
-       return Err(Error::new(ErrorKind::Other, "Could not find usable digit in string."))
+}
+impl error::Error for MoveError {}
+
+// A stack with no items, where one was needed
+#[derive(Debug, Clone, PartialEq)]
+struct EmptyStackError {
+    stack: usize, // one-indexed number of the empty stack
+}
+impl fmt::Display for EmptyStackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "stack {} is empty", self.stack)
     }
-    Ok(i)
 }
+impl error::Error for EmptyStackError {}
 
-#[derive(Debug, Clone)]
-struct StackTooSmallError;
-impl fmt::Display for StackTooSmallError {
+// Error for a crane asked to move more items than its stack has
+#[derive(Debug, PartialEq)]
+struct TooFewItemsError {
+    wanted: usize, // number of items the crane was asked to move
+    available: usize, // number of items on the stack
+}
+
+impl fmt::Display for TooFewItemsError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "number of items exceeded list size")
+        write!(f, "cannot move {} items from a stack of {}", self.wanted, self.available)
     }
-}    
-impl error::Error for StackTooSmallError {}
+}
+
+impl error::Error for TooFewItemsError {}
 
+// Error for undoing a move on a Cargo with no recorded moves left
+#[cfg(test)]
+#[derive(Debug, PartialEq)]
+struct NothingToUndoError;
+
+#[cfg(test)]
+impl fmt::Display for NothingToUndoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no recorded moves to undo")
+    }
+}
+
+#[cfg(test)]
+impl error::Error for NothingToUndoError {}
+
+// Ways a movement command can be invalid
+// Each has the (one-indexed) line it was found on, once known.
+#[derive(Debug, PartialEq)]
+enum Day5Error {
+    UnmatchedLine { line: Option<usize>, text: String }, // the line is not a 'move a from b to c' command
+    NotANumber { line: Option<usize>, field: String }, // a command field is not a non-negative integer
+    InvalidStack { line: Option<usize>, stack: usize, stacks: usize }, // a stack number is 0 or past the last stack
+    TooFewItems { line: Option<usize>, stack: usize, wanted: usize, available: usize }, // a group is larger than its stack (part 2)
+    InvariantViolation { line: Option<usize>, expected: usize, actual: usize }, // a move changed the number of items (see 'CheckPolicy')
+    InvalidLabel { line: Option<usize>, label: String }, // a drawing has a label that is not a valid item
+    StackZero { line: Option<usize> }, // a command refers to stack 0, though stacks are numbered from 1
+    InvalidFooter { line: Option<usize>, text: String }, // the drawing's last line does not number the stacks from 1
+    RowTooWide { line: Option<usize>, width: usize, max_width: usize }, // a drawing row has items past the last stack
+}
+
+impl Day5Error {
+    // Attaches the (one-indexed) line the error was found on
+    fn on_line(self, line: usize) -> Day5Error {
+        let line = Some(line);
+        match self {
+            Self::UnmatchedLine { text, .. } => Self::UnmatchedLine { line, text },
+            Self::NotANumber { field, .. } => Self::NotANumber { line, field },
+            Self::InvalidStack { stack, stacks, .. } => Self::InvalidStack { line, stack, stacks },
+            Self::TooFewItems { stack, wanted, available, .. } => Self::TooFewItems { line, stack, wanted, available },
+            Self::InvariantViolation { expected, actual, .. } => Self::InvariantViolation { line, expected, actual },
+            Self::InvalidLabel { label, .. } => Self::InvalidLabel { line, label },
+            Self::StackZero { .. } => Self::StackZero { line },
+            Self::InvalidFooter { text, .. } => Self::InvalidFooter { line, text },
+            Self::RowTooWide { width, max_width, .. } => Self::RowTooWide { line, width, max_width },
+        }
+    }
+
+    fn line(&self) -> Option<usize> {
+        match self {
+            Self::UnmatchedLine { line, .. } | Self::NotANumber { line, .. } | Self::InvalidStack { line, .. }
+                | Self::TooFewItems { line, .. } | Self::InvariantViolation { line, .. }
+                | Self::InvalidLabel { line, .. } | Self::StackZero { line, .. } | Self::InvalidFooter { line, .. } | Self::RowTooWide { line, .. } => *line,
+        }
+    }
+}
+
+impl error::Error for Day5Error {}
+
+// Converts a failed move into the error for its command, numbering stacks from one as the input does
+impl From<MoveError> for Day5Error {
+    fn from(e: MoveError) -> Day5Error {
+        match e {
+            MoveError::InvalidStackIndex { index, stacks } => Day5Error::InvalidStack { line: None, stack: index + 1, stacks },
+            MoveError::TooFewItems { index, wanted, available } =>
+                Day5Error::TooFewItems { line: None, stack: index + 1, wanted, available },
+        }
+    }
+}
+impl fmt::Display for Day5Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnmatchedLine { text, .. } => write!(f, "expected 'move a from b to c', found '{text}'")?,
+            Self::NotANumber { field, .. } => write!(f, "command field '{field}' is not a non-negative integer")?,
+            Self::InvalidStack { stack, stacks, .. } => write!(f, "stack {stack} does not exist, as stacks are numbered 1 to {stacks}")?,
+            Self::TooFewItems { stack, wanted, available, .. } =>
+                write!(f, "cannot move {wanted} items from stack {stack}, which only has {available}")?,
+            Self::InvariantViolation { expected, actual, .. } =>
+                write!(f, "move left {actual} items across the stacks, but there should be {expected}")?,
+            Self::InvalidLabel { label, .. } => write!(f, "'[{label}]' is not a valid crate label")?,
+            Self::StackZero { .. } => write!(f, "stacks are numbered from 1, so there is no stack 0")?,
+            Self::InvalidFooter { text, .. } => write!(f, "expected stacks numbered from 1 below the drawing, found '{text}'")?,
+            Self::RowTooWide { width, max_width, .. } =>
+                write!(f, "drawing row is {width} characters wide, but the stacks only allow {max_width}")?,
+        }
+        match self.line() {
+            Some(line) => write!(f, " on line {line}"),
+            None => Ok(()),
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+
+    const EXAMPLE_INPUT: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+";
+
+    #[test]
+    fn example() -> Result<(), Box<dyn error::Error>> {
+        let (starting, moving) = split_input(EXAMPLE_INPUT)?;
+        assert_eq!(starting, "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n");
+        assert!(moving.starts_with("move 1 from 2 to 1\n"));
+
+        assert_eq!(solve(starting, moving, 6, CraneMode::OneAtATime, EmptyStackPolicy::Skip, CheckPolicy::Conserve, Grammar::Strict)?.get_top_chars()?, "CMZ");
+        assert_eq!(solve(starting, moving, 6, CraneMode::InGroups, EmptyStackPolicy::Skip, CheckPolicy::Conserve, Grammar::Strict)?.get_top_chars()?, "MCD");
+
+        // Windows line endings split the same way, and an input without a blank line is rejected
+        let windows = EXAMPLE_INPUT.replace('\n', "\r\n");
+        let (starting, moving) = split_input(&windows)?;
+        assert_eq!(solve(starting, moving, 6, CraneMode::InGroups, EmptyStackPolicy::Skip, CheckPolicy::Conserve, Grammar::Strict)?.get_top_chars()?, "MCD");
+        assert!(split_input("[A]\n 1 \nmove 1 from 1 to 1").is_err());
+        Ok(())
+    }
+
+    // Gets every stack of 'cargo' through its accessors, bottom first
+    fn stacks(cargo: &Cargo) -> Vec<&[char]> {
+        (0..cargo.num_stacks()).map(|i| cargo.get_stack(i).unwrap()).collect()
+    }
+
+    // Creates a Cargo holding 'columns' of items, bottom first, by parsing its rows from the bottom up as 'create_cargo' does
+    fn from_columns(columns: &[Vec<char>]) -> Cargo {
+        let mut cargo = Cargo::new(columns.len());
+        let height = columns.iter().map(Vec::len).max().unwrap_or(0);
+        for row in 0..height {
+            let cells: Vec<String> = columns.iter().map(|column| column.get(row).map_or("   ".to_string(), |c| format!("[{c}]"))).collect();
+            cargo.parse_row(&cells.join(" "));
+        }
+        cargo
+    }
+
     // Create a dummy cargo instance for testing, and fill it with some basic example data.
     fn create_cargo() -> Result<Cargo,Error> {
         let mut cargo = Cargo::new(3);
-        cargo.parse_row("[A] [B] [C]")?;
-        cargo.parse_row("[D]     [E]")?;
-        cargo.parse_row("[F]     [G]")?;
-        cargo.parse_row("[H] [I]    ")?;
+        cargo.parse_row("[A] [B] [C]");
+        cargo.parse_row("[D]     [E]");
+        cargo.parse_row("[F]     [G]");
+        cargo.parse_row("[H] [I]    ");
+        cargo.checks = CheckPolicy::Conserve;
         Ok(cargo)
     }
 
+    #[test]
+    fn move_from_str() {
+        assert_eq!("move 1 from 2 to 1".parse(), Ok(Move { count: 1, from: 1, to: 0 }));
+        assert_eq!(" move 12 from 10 to 3 \r".parse(), Ok(Move { count: 12, from: 9, to: 2 }));
+
+        // Stacks past the end of a Cargo parse, and only fail once applied
+        assert_eq!("move 0 from 99 to 1".parse(), Ok(Move { count: 0, from: 98, to: 0 }));
+        for (command, expected) in [
+            ("move 1 from 0 to 2", Day5Error::StackZero { line: None }),
+            ("move one from 1 to 2", Day5Error::NotANumber { line: None, field: "one".to_string() }),
+            ("move 1 from 1 to 2.5", Day5Error::NotANumber { line: None, field: "2.5".to_string() }),
+            ("move 1 to 2 from 1", Day5Error::UnmatchedLine { line: None, text: "move 1 to 2 from 1".to_string() }),
+            ("Move 1 from 1 to 2", Day5Error::UnmatchedLine { line: None, text: "Move 1 from 1 to 2".to_string() }),
+        ] {
+            assert_eq!(command.parse::<Move>(), Err(expected), "{command}");
+        }
+    }
+
+    #[test]
+    fn lenient_commands() {
+        for command in ["MOVE 1 FROM 2 TO 3", "move  1  from 2 to 3  # note", "\tMove 1 from\t2 to 3#"] {
+            assert_eq!(Move::parse_with(command, Grammar::Lenient), Ok(Move { count: 1, from: 1, to: 2 }), "{command}");
+            assert_eq!(Move::parse_with(command, Grammar::Strict), Err(Day5Error::UnmatchedLine { line: None, text: command.to_string() }));
+        }
+
+        // Lenient commands still need every field, and only what comes before a comment counts
+        for (command, expected) in [
+            ("move 1 from 2 # to 3", Day5Error::UnmatchedLine { line: None, text: "move 1 from 2 # to 3".to_string() }),
+            ("moved 1 from 2 to 3", Day5Error::UnmatchedLine { line: None, text: "moved 1 from 2 to 3".to_string() }),
+            ("MOVE one FROM 2 TO 3", Day5Error::NotANumber { line: None, field: "one".to_string() }),
+            ("move 1 from 0 to 3 # zero", Day5Error::StackZero { line: None }),
+        ] {
+            assert_eq!(Move::parse_with(command, Grammar::Lenient), Err(expected), "{command}");
+        }
+
+        // Comment lines are skipped like blank ones, without moving line numbers
+        let (starting, _) = split_input(EXAMPLE_INPUT).unwrap();
+        let moving = "# rearrangement\nmove 1 from 2 to 1\nMOVE 3 FROM 1 TO 3 # all of stack 1\n\nmove 2 from 2 to 1\nmove 1 from 1 to 2";
+        let cargo = solve(starting, moving, 6, CraneMode::OneAtATime, EmptyStackPolicy::Skip, CheckPolicy::Conserve, Grammar::Lenient).unwrap();
+        assert_eq!(cargo.get_top_chars().unwrap(), "CMZ");
+        let error = solve(starting, moving, 6, CraneMode::OneAtATime, EmptyStackPolicy::Skip, CheckPolicy::Conserve, Grammar::Strict).unwrap_err();
+        assert_eq!(error.downcast_ref::<Day5Error>().and_then(Day5Error::line), Some(6));
+    }
+
+    #[test]
+    fn invalid_commands() {
+        let mut cargo = create_cargo().expect("Could not create basic cargo instance.");
+        for (command, expected) in [
+            ("move x from 1 to 2", Day5Error::NotANumber { line: None, field: "x".to_string() }),
+            ("move 1 from 1 to -2", Day5Error::NotANumber { line: None, field: "-2".to_string() }),
+            ("move 3 from 9 to 1", Day5Error::InvalidStack { line: None, stack: 9, stacks: 3 }),
+            ("move 3 from 1 to 0", Day5Error::StackZero { line: None }),
+            ("", Day5Error::UnmatchedLine { line: None, text: "".to_string() }),
+            ("move 1 from 1", Day5Error::UnmatchedLine { line: None, text: "move 1 from 1".to_string() }),
+            ("move 1 from 1 to 2 now", Day5Error::UnmatchedLine { line: None, text: "move 1 from 1 to 2 now".to_string() }),
+        ] {
+            assert_eq!(cargo.parse_command(command, &CraneMode::OneAtATime), Err(expected), "{command}");
+        }
+        assert_eq!(cargo.parse_command("move 3 from 2 to 1", &CraneMode::InGroups),
+            Err(Day5Error::TooFewItems { line: None, stack: 2, wanted: 3, available: 2 }));
+
+        // Failed commands leave the stacks as they were
+        assert_eq!(stacks(&cargo), [vec!['A','D','F','H'], vec!['B','I'], vec!['C','E','G']]);
+
+        // Blank lines are skipped, and errors carry their line number in the input
+        let (starting, moving) = split_input(EXAMPLE_INPUT).unwrap();
+        let moving = moving.replace("move 2 from 2 to 1", "\nmove 3 from 9 to 1");
+        let error = solve(starting, &moving, 6, CraneMode::OneAtATime, EmptyStackPolicy::Skip, CheckPolicy::Conserve, Grammar::Strict).unwrap_err();
+        assert_eq!(error.downcast_ref::<Day5Error>(), Some(&Day5Error::InvalidStack { line: Some(9), stack: 9, stacks: 3 }));
+        assert_eq!(error.to_string(), "stack 9 does not exist, as stacks are numbered 1 to 3 on line 9");
+        assert!(solve(starting, "\n  \nmove 1 from 2 to 1\n\n", 6, CraneMode::OneAtATime, EmptyStackPolicy::Skip, CheckPolicy::Conserve, Grammar::Strict).is_ok());
+    }
+
     #[test]
     // Test creating a cargo instance and parsing a row string to it
     // 'parse_row'
@@ -217,18 +879,380 @@ mod tests {
         let mut cargo = create_cargo().expect("Could not create basic cargo instance.");
 
         // Assert initial conditions
-        assert_eq!(cargo.stacks.get(0).unwrap(), &vec!['A','D','F','H']);
-        assert_eq!(cargo.stacks.get(1).unwrap(), &vec!['B','I']);
-        assert_eq!(cargo.stacks.get(2).unwrap(), &vec!['C','E','G']);
+        assert_eq!(cargo.get_stack(0).unwrap(), ['A','D','F','H']);
+        assert_eq!(cargo.get_stack(1).unwrap(), ['B','I']);
+        assert_eq!(cargo.get_stack(2).unwrap(), ['C','E','G']);
 
         // Parse an additional row and confirm they are added correctly
-        cargo.parse_row("    [J] [K]")?;
-        assert_eq!(cargo.stacks.get(0).unwrap(), &vec!['A','D','F','H']);
-        assert_eq!(cargo.stacks.get(1).unwrap(), &vec!['B','I','J']);
-        assert_eq!(cargo.stacks.get(2).unwrap(), &vec!['C','E','G', 'K']);
+        cargo.parse_row("    [J] [K]");
+        assert_eq!(cargo.get_stack(0).unwrap(), ['A','D','F','H']);
+        assert_eq!(cargo.get_stack(1).unwrap(), ['B','I','J']);
+        assert_eq!(cargo.get_stack(2).unwrap(), ['C','E','G', 'K']);
         Ok(())
     }
 
+    #[test]
+    fn many_stacks() {
+        let drawing = concat!(
+            "[A]                                         [L]\n",
+            "[B] [C] [D] [E] [F] [G] [H] [I] [J] [K] [M] [N]\n",
+            " 1   2   3   4   5   6   7   8   9  10  11  12 ",
+        );
+        let mut cargo = Cargo::from_drawing(drawing).unwrap();
+        assert_eq!(cargo.num_stacks(), 12);
+        assert_eq!(cargo.stack_heights(), [2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2]);
+        assert_eq!((cargo.get_stack(9), cargo.get_stack(11)), (Some(&['K'][..]), Some(&['N','L'][..])));
+
+        // Two-digit stack numbers in moves refer to the same stacks as the footer
+        assert_eq!("move 2 from 12 to 10".parse::<Move>(), Ok(Move { count: 2, from: 11, to: 9 }));
+        for command in ["move 2 from 12 to 10", "move 1 from 10 to 12", "move 1 from 11 to 10"] {
+            cargo.parse_command(command, &CraneMode::InGroups).unwrap();
+        }
+        assert_eq!(stacks(&cargo)[9..], [vec!['K','N','M'], vec![], vec!['L']]);
+    }
+
+    #[test]
+    fn from_drawing() {
+        let (starting, _) = split_input(EXAMPLE_INPUT).unwrap();
+        let cargo = Cargo::from_drawing(starting).unwrap();
+        assert_eq!(stacks(&cargo), [vec!['Z','N'], vec!['M','C','D'], vec!['P']]);
+
+        // Trailing spaces stripped by editors, and blank lines after the footer, make no difference
+        let stripped: Vec<&str> = starting.lines().map(str::trim_end).collect();
+        assert_eq!(stacks(&Cargo::from_drawing(&(stripped.join("\n") + "\n\n")).unwrap()), stacks(&cargo));
+
+        // More than 9 stacks are numbered in full
+        let wide = "[A]                                     [K]\n 1   2   3   4   5   6   7   8   9  10  11 ";
+        let cargo = Cargo::from_drawing(wide).unwrap();
+        assert_eq!((cargo.num_stacks(), cargo.get_stack(0), cargo.get_stack(10)), (11, Some(&['A'][..]), Some(&['K'][..])));
+
+        // Footers must count up from 1, and rows must fit within them
+        for (drawing, expected) in [
+            ("", Day5Error::InvalidFooter { line: None, text: "".to_string() }),
+            ("[A]\n 1   3 ", Day5Error::InvalidFooter { line: Some(2), text: " 1   3 ".to_string() }),
+            ("[A] [B]\n[C]", Day5Error::InvalidFooter { line: Some(2), text: "[C]".to_string() }),
+            ("[A]\n   ", Day5Error::InvalidFooter { line: Some(1), text: "[A]".to_string() }),
+            ("[A] [B]\n[C] [D] [E]\n 1   2 ", Day5Error::RowTooWide { line: Some(2), width: 11, max_width: 7 }),
+        ] {
+            assert_eq!(Cargo::from_drawing(drawing).err(), Some(expected), "{drawing}");
+        }
+    }
+
+    #[test]
+    fn multi_character_labels() {
+        let drawing = "[AB]          \n[C7] [D ] [EF]\n 1    2    3  ";
+        let mut cargo = Cargo::<String>::from_labelled_drawing(drawing).unwrap();
+        assert_eq!(cargo.stack_heights(), [2, 1, 1]);
+        assert_eq!(cargo.get_stack(0).unwrap(), ["C7", "AB"]);
+        assert_eq!(cargo.to_string(), drawing);
+
+        // Labels move as whole items, in either crane mode
+        cargo.parse_command("move 2 from 1 to 3", &CraneMode::OneAtATime).unwrap();
+        assert_eq!(cargo.get_stack(2).unwrap(), ["EF", "AB", "C7"]);
+        cargo.parse_command("move 2 from 3 to 2", &CraneMode::InGroups).unwrap();
+        assert_eq!(cargo.get_stack(1).unwrap(), ["D", "AB", "C7"]);
+        cargo.parse_command("move 1 from 2 to 1", &CraneMode::InGroups).unwrap();
+        assert_eq!(cargo.top_labels().unwrap(), [&"C7".to_string(), &"AB".to_string(), &"EF".to_string()]);
+        assert_eq!(cargo.get_top_chars().unwrap(), "C7ABEF");
+
+        // Trailing spaces may be stripped, as with single characters
+        let stripped: Vec<&str> = drawing.lines().map(str::trim_end).collect();
+        let cargo = Cargo::<String>::from_labelled_drawing(&stripped.join("\n")).unwrap();
+        assert_eq!(cargo.stack_heights(), [2, 1, 1]);
+
+        // The puzzle's Cargo only takes single characters, and longer labels widen the columns
+        assert_eq!(Cargo::from_drawing("[A]\n[BC]\n 1  ").unwrap_err(), Day5Error::InvalidLabel { line: Some(2), label: "BC".to_string() });
+        assert_eq!(Cargo::<String>::from_labelled_drawing("[AB] [CD] [EF]\n 1    2  ").unwrap_err(),
+            Day5Error::RowTooWide { line: Some(1), width: 14, max_width: 9 });
+    }
+
+    #[test]
+    fn drawing() {
+        // The example's starting stacks are drawn as they were written
+        let (starting, _) = split_input(EXAMPLE_INPUT).unwrap();
+        assert_eq!(Cargo::from_drawing(starting).unwrap().to_string(), starting.trim_end_matches('\n'));
+
+        // Uneven stacks are padded to the tallest, including an empty stack in the middle
+        let cargo = from_columns(&[vec!['A','D','F','H'], vec![], vec!['C','E','G','J','K']]);
+        assert_eq!(cargo.to_string(), "        [K]
+[H]     [J]
+[F]     [G]
+[D]     [E]
+[A]     [C]
+ 1   2   3 ");
+
+        // A completely empty cargo is just its footer
+        assert_eq!(Cargo::new(3).to_string(), " 1   2   3 ");
+        assert_eq!(Cargo::new(0).to_string(), "");
+    }
+
+    #[test]
+    fn empty_stacks() {
+        let mut cargo = create_cargo().expect("Could not create basic cargo instance.");
+        assert_eq!(cargo.get_top_chars(), Ok("HIG".to_string()));
+
+        // Emptying a stack is allowed, but it then has no top item
+        cargo.parse_command("move 2 from 2 to 1", &CraneMode::OneAtATime).unwrap();
+        assert_eq!(cargo.get_top_chars(), Err(EmptyStackError { stack: 2 }));
+        assert_eq!(cargo.move_top_item_between_stacks(1, 0), Err(MoveError::TooFewItems { index: 1, wanted: 1, available: 0 }));
+
+        // By default, moving one at a time from a stack that runs out moves what there is
+        cargo.parse_command("move 3 from 3 to 2", &CraneMode::OneAtATime).unwrap();
+        cargo.parse_command("move 5 from 2 to 3", &CraneMode::OneAtATime).unwrap();
+        assert_eq!(stacks(&cargo), [vec!['A','D','F','H','I','B'], vec![], vec!['C','E','G']]);
+
+        // Failing instead leaves the stacks untouched
+        cargo.empty_stacks = EmptyStackPolicy::Fail;
+        assert_eq!(cargo.parse_command("move 4 from 3 to 2", &CraneMode::OneAtATime),
+            Err(Day5Error::TooFewItems { line: None, stack: 3, wanted: 4, available: 3 }));
+        assert_eq!(stacks(&cargo), [vec!['A','D','F','H','I','B'], vec![], vec!['C','E','G']]);
+        cargo.parse_command("move 3 from 3 to 2", &CraneMode::OneAtATime).unwrap();
+        assert_eq!(cargo.get_top_chars(), Err(EmptyStackError { stack: 3 }));
+        assert_eq!(EmptyStackError { stack: 3 }.to_string(), "stack 3 is empty");
+
+        // The solver can fail too, at the move that runs out
+        let (starting, moving) = split_input(EXAMPLE_INPUT).unwrap();
+        let moving = format!("{moving}move 9 from 1 to 2\n");
+        assert!(solve(starting, &moving, 6, CraneMode::OneAtATime, EmptyStackPolicy::Skip, CheckPolicy::Conserve, Grammar::Strict).is_ok());
+        let error = solve(starting, &moving, 6, CraneMode::OneAtATime, EmptyStackPolicy::Fail, CheckPolicy::Conserve, Grammar::Strict).unwrap_err();
+        assert_eq!(error.to_string(), "cannot move 9 items from stack 1, which only has 1 on line 10");
+    }
+
+    #[test]
+    fn invalid_stack_indices() {
+        let mut cargo = create_cargo().expect("Could not create basic cargo instance.");
+        let before = cargo.clone();
+
+        assert_eq!(cargo.move_top_item_between_stacks(3, 0), Err(MoveError::InvalidStackIndex { index: 3, stacks: 3 }));
+        assert_eq!(cargo.move_top_item_between_stacks(0, 9), Err(MoveError::InvalidStackIndex { index: 9, stacks: 3 }));
+        assert_eq!(cargo.move_top_n_items_between_stacks(0, 3, 2), Err(MoveError::InvalidStackIndex { index: 3, stacks: 3 }));
+        assert_eq!(cargo.move_top_n_items_between_stacks(1, 0, 3), Err(MoveError::TooFewItems { index: 1, wanted: 3, available: 2 }));
+
+        // Moves built directly, rather than parsed, are checked as well
+        for mode in [CraneMode::OneAtATime, CraneMode::InGroups] {
+            assert_eq!(cargo.apply(&Move { count: 1, from: 0, to: 9 }, &mode),
+                Err(Day5Error::InvalidStack { line: None, stack: 10, stacks: 3 }));
+            assert_eq!(cargo.apply(&Move { count: 1, from: 5, to: 0 }, &mode),
+                Err(Day5Error::InvalidStack { line: None, stack: 6, stacks: 3 }));
+        }
+
+        // None of the failed moves changed the stacks
+        assert_eq!(stacks(&cargo), stacks(&before));
+        assert_eq!(MoveError::InvalidStackIndex { index: 9, stacks: 3 }.to_string(), "stack index 9 is out of range for 3 stacks");
+    }
+
+    // Test parsing movement commands and applying them with each crane
+    #[test]
+    fn crane_modes() {
+        let mut cargo = create_cargo().expect("Could not create basic cargo instance.");
+        let mv: Move = "move 3 from 1 to 3".parse().unwrap();
+        assert_eq!(mv, Move { count: 3, from: 0, to: 2 });
+
+        // One at a time reverses the group, while moving it as a group keeps its order
+        cargo.apply(&mv, &CraneMode::OneAtATime).unwrap();
+        assert_eq!(cargo.get_stack(2).unwrap(), ['C','E','G','H','F','D']);
+        cargo.apply(&Move { count: 3, from: 2, to: 0 }, &CraneMode::InGroups).unwrap();
+        assert_eq!(cargo.get_stack(0).unwrap(), ['A','H','F','D']);
+        assert_eq!(cargo.get_stack(2).unwrap(), ['C','E','G']);
+    }
+
+    #[test]
+    fn undo() {
+        let (starting, moving) = split_input(EXAMPLE_INPUT).unwrap();
+        for mode in [CraneMode::OneAtATime, CraneMode::InGroups] {
+            let mut cargo = Cargo::from_drawing(starting).unwrap();
+            cargo.recording = HistoryPolicy::Record;
+            for command in moving.lines() {
+                cargo.parse_command(command, &mode).unwrap();
+            }
+            assert_eq!(cargo.history().len(), 4);
+            assert_eq!(cargo.history()[1], AppliedMove { mv: Move { count: 3, from: 0, to: 2 }, from_stack: vec!['Z','N','D'], to_stack: vec!['P'] });
+
+            // Undoing every move restores the drawing exactly
+            for _ in 0..4 {
+                cargo.undo().unwrap();
+            }
+            assert_eq!(stacks(&cargo), stacks(&Cargo::from_drawing(starting).unwrap()));
+            assert_eq!(cargo.undo(), Err(NothingToUndoError));
+        }
+
+        // Without recording, there is nothing to undo
+        let mut cargo = create_cargo().unwrap();
+        cargo.parse_command("move 1 from 1 to 2", &CraneMode::OneAtATime).unwrap();
+        assert!(cargo.history().is_empty());
+        assert_eq!(cargo.undo(), Err(NothingToUndoError));
+    }
+
+    #[test]
+    fn replay() {
+        let (starting, moving) = split_input(EXAMPLE_INPUT).unwrap();
+        let moves: Vec<Move> = moving.lines().map(|line| line.parse().unwrap()).collect();
+        let cargo = Cargo::from_drawing(starting).unwrap();
+        for mode in [CraneMode::OneAtATime, CraneMode::InGroups] {
+            let states: Vec<Cargo> = cargo.replay(&moves, &mode).map(Result::unwrap).collect();
+            assert_eq!(states.len(), 4);
+            assert_eq!(stacks(&states[0]), [vec!['Z','N','D'], vec!['M','C'], vec!['P']]);
+            assert_eq!(stacks(&states[3]), stacks(&solve(starting, moving, 6, mode, EmptyStackPolicy::Skip, CheckPolicy::Conserve, Grammar::Strict).unwrap()));
+        }
+
+        // The replayed Cargo is a copy, and a failed move ends the replay
+        let bad = [moves[0], Move { count: 1, from: 0, to: 5 }, moves[1]];
+        let states: Vec<_> = cargo.replay(&bad, &CraneMode::OneAtATime).collect();
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[1].as_ref().unwrap_err(), &Day5Error::InvalidStack { line: None, stack: 6, stacks: 3 });
+        assert_eq!(stacks(&cargo), stacks(&Cargo::from_drawing(starting).unwrap()));
+    }
+
+    // Creates a Cargo of 'num_stacks' stacks of random heights, and moves between them that sometimes ask for more items than there are
+    fn random_moves(num_stacks: usize, num_moves: usize, mut seed: u64) -> (Cargo, Vec<Move>) {
+        let mut next = |n: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % n) as usize
+        };
+        let columns: Vec<Vec<char>> = (0..num_stacks).map(|_| {
+            let height = next(50);
+            (0..height).map(|_| (b'A' + next(26) as u8) as char).collect()
+        }).collect();
+        let cargo = from_columns(&columns);
+        assert_eq!(stacks(&cargo), columns);
+        let moves = (0..num_moves).map(|_| Move { count: next(20), from: next(num_stacks as u64), to: next(num_stacks as u64) }).collect();
+        (cargo, moves)
+    }
+
+    // Applies 'moves' one item at a time with 'move_top_item_between_stacks', as part 1 used to
+    fn apply_single_moves(cargo: &mut Cargo, moves: &[Move]) {
+        for mv in moves {
+            for _ in 0..mv.count.min(cargo.stack_heights()[mv.from]) {
+                cargo.move_top_item_between_stacks(mv.from, mv.to).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn batched_single_moves() {
+        for seed in [0x2022, 0x5, 0xC0FFEE] {
+            let (mut batched, moves) = random_moves(9, 1_000, seed);
+            let mut single = batched.clone();
+            apply_single_moves(&mut single, &moves);
+            for mv in &moves {
+                batched.apply(mv, &CraneMode::OneAtATime).unwrap();
+            }
+            assert_eq!(stacks(&batched), stacks(&single));
+        }
+
+        // Too few items fails without moving any for a crane moving groups, but not one moving items singly
+        let mut cargo = create_cargo().expect("Could not create basic cargo instance.");
+        assert_eq!(cargo.transfer(1, 0, 3, &CrateMover9001), Err(MoveError::TooFewItems { index: 1, wanted: 3, available: 2 }));
+        cargo.transfer(0, 1, 3, &CrateMover9000).unwrap();
+        assert_eq!(stacks(&cargo), [vec!['A'], vec!['B','I','H','F','D'], vec!['C','E','G']]);
+    }
+
+    // Times batched part 1 moves against moving items one at a time, over a million moves
+    // Run with 'cargo test --release bench_batched_moves -- --ignored --nocapture'
+    #[test]
+    #[ignore]
+    fn bench_batched_moves() {
+        use std::time::Instant;
+
+        let (mut batched, moves) = random_moves(9, 1_000_000, 0x2022);
+        let mut single = batched.clone();
+
+        let start = Instant::now();
+        apply_single_moves(&mut single, &moves);
+        let single_time = start.elapsed();
+
+        let start = Instant::now();
+        for mv in &moves {
+            batched.apply(mv, &CraneMode::OneAtATime).unwrap();
+        }
+        let batched_time = start.elapsed();
+
+        assert_eq!(stacks(&batched), stacks(&single));
+        println!("one at a time: {single_time:?}, batched: {batched_time:?}");
+    }
+
+    #[test]
+    fn move_validation() {
+        let (starting, moving) = split_input(EXAMPLE_INPUT).unwrap();
+        let cargo = Cargo::from_drawing(starting).unwrap();
+        let moves: Vec<Move> = parse_moves(moving, 6, Grammar::Strict).unwrap().into_iter().map(|(_, mv)| mv).collect();
+        for mode in [CraneMode::OneAtATime, CraneMode::InGroups] {
+            assert_eq!(cargo.validate_moves(&moves, mode), Ok(()));
+        }
+
+        // Stack 2 has 3 items, so can give 2 then 1, but not 2 more, when moving groups
+        let moves = [Move { count: 2, from: 1, to: 0 }, Move { count: 1, from: 1, to: 2 }, Move { count: 2, from: 1, to: 0 }];
+        assert_eq!(cargo.validate_moves(&moves, CraneMode::InGroups), Err((2, MoveError::TooFewItems { index: 1, wanted: 2, available: 0 })));
+        assert_eq!(cargo.validate_moves(&moves, CraneMode::OneAtATime), Ok(()));
+        let mut failing = Cargo::from_drawing(starting).unwrap();
+        failing.empty_stacks = EmptyStackPolicy::Fail;
+        assert_eq!(failing.validate_moves(&moves, CraneMode::OneAtATime), Err((2, MoveError::TooFewItems { index: 1, wanted: 2, available: 0 })));
+
+        // Missing stacks are found wherever they are, and validating moves nothing
+        let moves = [Move { count: 1, from: 0, to: 1 }, Move { count: 1, from: 1, to: 3 }];
+        assert_eq!(cargo.validate_moves(&moves, CraneMode::OneAtATime), Err((1, MoveError::InvalidStackIndex { index: 3, stacks: 3 })));
+        assert_eq!(stacks(&cargo), stacks(&Cargo::from_drawing(starting).unwrap()));
+
+        // Parsed moves keep their line numbers, past blank lines
+        assert_eq!(parse_moves("move 1 from 2 to 1\n\nmove 3 from 1 to 3", 6, Grammar::Strict),
+            Ok(vec![(6, Move { count: 1, from: 1, to: 0 }), (8, Move { count: 3, from: 0, to: 2 })]));
+        assert_eq!(parse_moves("move 1 from 2 to 1\n\nmove x from 1 to 3", 6, Grammar::Strict),
+            Err(Day5Error::NotANumber { line: Some(8), field: "x".to_string() }));
+    }
+
+    // An example crane, which pulls its group from the bottom of the stack, keeping its order
+    struct BottomCrane;
+
+    impl<T> Crane<T> for BottomCrane {
+        fn transfer(&self, from: &mut Vec<T>, to: &mut Vec<T>, count: usize) -> Result<(), TooFewItemsError> {
+            if from.len() < count {
+                return Err(TooFewItemsError { wanted: count, available: from.len() });
+            }
+            to.extend(from.drain(..count));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn custom_crane() {
+        let mut cargo = create_cargo().expect("Could not create basic cargo instance.");
+        cargo.recording = HistoryPolicy::Record;
+        cargo.parse_command("move 2 from 1 to 3", &BottomCrane).unwrap();
+        assert_eq!(stacks(&cargo), [vec!['F','H'], vec!['B','I'], vec!['C','E','G','A','D']]);
+        assert_eq!(cargo.parse_command("move 3 from 2 to 1", &BottomCrane),
+            Err(Day5Error::TooFewItems { line: None, stack: 2, wanted: 3, available: 2 }));
+
+        // Cranes can be mixed, and undone whichever end of the stack they took items from
+        cargo.parse_command("move 2 from 3 to 2", &CraneMode::OneAtATime).unwrap();
+        cargo.parse_command("move 4 from 2 to 1", &BottomCrane).unwrap();
+        assert_eq!(stacks(&cargo), [vec!['F','H','B','I','D','A'], vec![], vec!['C','E','G']]);
+        cargo.undo().unwrap();
+        cargo.undo().unwrap();
+        assert_eq!(stacks(&cargo), [vec!['F','H'], vec!['B','I'], vec!['C','E','G','A','D']]);
+        assert_eq!(TooFewItemsError { wanted: 3, available: 2 }.to_string(), "cannot move 3 items from a stack of 2");
+    }
+
+    #[test]
+    fn conservation() {
+        let mut cargo = create_cargo().expect("Could not create basic cargo instance.");
+        assert_eq!(cargo.total_crates(), 9);
+        cargo.parse_command("move 3 from 1 to 2", &CraneMode::InGroups).unwrap();
+        assert_eq!(cargo.total_crates(), 9);
+
+        // A move that loses an item is caught, and numbered like any other failed command
+        cargo.after_move = Some(|cargo| { cargo.stacks[2].pop(); });
+        let error = cargo.parse_command("move 1 from 2 to 3", &CraneMode::OneAtATime).unwrap_err();
+        assert_eq!(error, Day5Error::InvariantViolation { line: None, expected: 9, actual: 8 });
+        assert_eq!(error.on_line(12).to_string(), "move left 8 items across the stacks, but there should be 9 on line 12");
+
+        // Without checks, the loss goes unnoticed
+        cargo.checks = CheckPolicy::Unchecked;
+        cargo.parse_command("move 1 from 2 to 3", &CraneMode::OneAtATime).unwrap();
+        assert_eq!(cargo.total_crates(), 7);
+    }
+
     // Test stack movement logic, both indivieaul items and groups
     // 'move_top_item_between_stacks'
     // 'move_top_items_between_stacks'
@@ -237,27 +1261,27 @@ mod tests {
         let mut cargo = create_cargo().expect("Could not create basic cargo instance.");
 
         // Move top item from 0 to 1
-        cargo.move_top_item_between_stacks(0, 1);
-        assert_eq!(cargo.stacks.get(0).unwrap(), &vec!['A','D','F']);
-        assert_eq!(cargo.stacks.get(1).unwrap(), &vec!['B','I','H']);
+        cargo.move_top_item_between_stacks(0, 1).unwrap();
+        assert_eq!(cargo.get_stack(0).unwrap(), ['A','D','F']);
+        assert_eq!(cargo.get_stack(1).unwrap(), ['B','I','H']);
 
         // Move top item from 0 to 1
-        cargo.move_top_item_between_stacks(2, 0);
-        assert_eq!(cargo.stacks.get(0).unwrap(), &vec!['A','D','F', 'G']);
-        assert_eq!(cargo.stacks.get(1).unwrap(), &vec!['B','I','H']);
-        assert_eq!(cargo.stacks.get(2).unwrap(), &vec!['C','E']);
+        cargo.move_top_item_between_stacks(2, 0).unwrap();
+        assert_eq!(cargo.get_stack(0).unwrap(), ['A','D','F', 'G']);
+        assert_eq!(cargo.get_stack(1).unwrap(), ['B','I','H']);
+        assert_eq!(cargo.get_stack(2).unwrap(), ['C','E']);
 
         // Move top 3 items in a group from the top of stack 0 to 2
         cargo.move_top_n_items_between_stacks(0,2,3).unwrap();
-        assert_eq!(cargo.stacks.get(0).unwrap(), &vec!['A']);
-        assert_eq!(cargo.stacks.get(1).unwrap(), &vec!['B','I','H']);
-        assert_eq!(cargo.stacks.get(2).unwrap(), &vec!['C','E','D','F', 'G']);
+        assert_eq!(cargo.get_stack(0).unwrap(), ['A']);
+        assert_eq!(cargo.get_stack(1).unwrap(), ['B','I','H']);
+        assert_eq!(cargo.get_stack(2).unwrap(), ['C','E','D','F', 'G']);
 
         // Move top 5 items in a group from the top of stack 2 to `
         cargo.move_top_n_items_between_stacks(2,1,5).unwrap();
-        assert_eq!(cargo.stacks.get(0).unwrap(), &vec!['A']);
-        assert_eq!(cargo.stacks.get(1).unwrap(), &vec!['B','I','H','C','E','D','F', 'G']);
-        assert_eq!(cargo.stacks.get(2).unwrap(), &Vec::<char>::new());
+        assert_eq!(cargo.get_stack(0).unwrap(), ['A']);
+        assert_eq!(cargo.get_stack(1).unwrap(), ['B','I','H','C','E','D','F', 'G']);
+        assert!(cargo.get_stack(2).unwrap().is_empty());
     }
 }
 
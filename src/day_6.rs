@@ -5,46 +5,572 @@
 // In part 1, x = 3.
 // In part 2, x = 14
 
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::io;
+use std::slice;
+use std::thread;
 use super::*;
 
 // Run challenge.
 // Main entry point to day 6 challenge.
-pub fn run(part_2: bool) ->Result<(), Box<dyn error::Error>> {
-
-    // Load input file to BufReader
-    let f = File::open("input/day6input.txt")?;
-    let buf = BufReader::new(f);
-
-    for line in buf.lines() {
-        // Read signal characters into a string
-        let line = line?;
-        let part = if part_2 {2} else {1};
-        let marker_length = if part_2 {14} else {4};
-
-        // Get location of start marker in 'line' based on start marker size 'marker_length'
-        // Start marker is point where 'marker_length' unique characters in a row first appear
-        let start_marker = match get_start_marker(&line, marker_length) {
-            Some(t) => t,
-            None => {
-                println!("Result for day 6-{part} = Could not find a start marker.");
-                return Err(Box::new(Error::new(ErrorKind::Other, "Could not find a start marker.")));
-            },
-        };
-        println!("Result for day 6-{part} = {start_marker}");
-        break; // Only need first line
+pub(crate) fn run(options: &Options) -> Result<Answers, Box<dyn error::Error>> {
+    let marker_lengths = match options.marker_len {
+        Some(marker_length) => [check_marker_length(marker_length)?; 2],
+        None => [4, 14],
+    };
+
+    // Read the input file once, then search it for both parts' markers on every line together
+    // Start marker is point where 'marker_length' unique characters in a row first appear
+    let mut input = String::new();
+    File::open("input/day6input.txt")?.read_to_string(&mut input)?;
+    let signals = read_signals(input.as_bytes(), &LineWindows::new(&marker_lengths))?;
+    let first_line = &signals[0].1;
+    let answers = solve(first_line)?;
+
+    // List every line's markers when there are several signals, as only the first line's are the answers
+    let mut notes = Vec::new();
+    let lines: Vec<&(usize, LineWindows)> = signals.iter().filter(|(_, windows)| windows.length > 0).collect();
+    if lines.len() > 1 {
+        for (line, windows) in lines {
+            let positions: Vec<String> = windows.markers.iter()
+                .map(|marker| marker.as_ref().map_or("no start marker".to_string(), |window| window.position.to_string()))
+                .collect();
+            notes.push(format!("Line {line}: {}", positions.join(", ")));
+        }
     }
-    Ok(())
+
+    let mut details = Vec::new();
+    if options.verbose {
+        // Show which characters make up each marker, as its window held them when it was found
+        for (part, marker) in [1, 2].into_iter().zip(&first_line.markers) {
+            if let Some(window) = marker {
+                details.push(format!("Start marker for part {part}: {}", Marker::from(window)));
+            }
+        }
+
+        let (length, end) = longest_distinct_run(input.lines().next().unwrap_or(""));
+        details.push(format!("Longest unique run: {length} chars ending at {end}"));
+    }
+    Ok(Answers {
+        part_1: answers[0].to_string(),
+        part_2: answers[1].to_string(),
+        notes,
+        details,
+    })
+}
+
+// Gets location of start marker of each size searched for in 'first_line' (see 'read_signals'), which are the puzzle's answers
+// Fails with a MarkerNotFound for the first size that has no marker in the line.
+fn solve(first_line: &LineWindows) -> Result<Vec<u64>, Day6Error> {
+    let mut positions = Vec::new();
+    for (window, marker) in first_line.windows.iter().zip(&first_line.markers) {
+        match marker {
+            Some(marker) => positions.push(marker.position),
+            None => return Err(Day6Error::MarkerNotFound { len: window.marker_length, stream_len: first_line.length }),
+        }
+    }
+    Ok(positions)
+}
+
+// Error for a day 6 signal that cannot be solved
+#[derive(Debug, PartialEq)]
+enum Day6Error {
+    MarkerNotFound { len: usize, stream_len: u64 }, // no 'len' characters in a row are all different, in a signal of 'stream_len'
+}
+
+impl fmt::Display for Day6Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MarkerNotFound { len, stream_len } =>
+                write!(f, "could not find a start marker of {len} different characters in a signal of {stream_len}"),
+        }
+    }
+}
+
+impl error::Error for Day6Error {}
+
+// Gets location of start marker of size 'marker_length' in the first line of 'input', as the runner does for each part
+// Fails if 'marker_length' is 0, and finds no marker if it is longer than the line.
+pub fn solve_with_len(input: &str, marker_length: usize) -> Result<Option<usize>, MarkerLengthError> {
+    Ok(get_start_marker(input.lines().next().unwrap_or(""), check_marker_length(marker_length)?))
+}
+
+// Checks 'marker_length' is at least 1, as an empty marker would be found before the signal starts
+fn check_marker_length(marker_length: usize) -> Result<usize, MarkerLengthError> {
+    match marker_length {
+        0 => Err(MarkerLengthError),
+        _ => Ok(marker_length),
+    }
+}
+
+// Error for a marker length of 0
+#[derive(Debug, PartialEq)]
+pub struct MarkerLengthError;
+
+impl fmt::Display for MarkerLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "marker length must be at least 1")
+    }
+}
+
+impl error::Error for MarkerLengthError {}
+
+// Gets location of start marker of each size in 'marker_lengths' for string 'stream', in a single pass
+// Each position is as 'get_start_marker' finds it, and the search stops once every marker has been found.
+// eg: find_markers("abcabcd", &[3, 4]) == [Some(3), Some(7)]
+pub fn find_markers(stream: &str, marker_lengths: &[usize]) -> Vec<Option<usize>> {
+    let mut windows: Vec<Window> = marker_lengths.iter().map(|&marker_length| Window::new(marker_length)).collect();
+    let mut markers = vec![None; marker_lengths.len()];
+    let mut remaining = marker_lengths.len();
+    for &b in stream.as_bytes() {
+        if remaining == 0 {
+            break;
+        }
+        for (window, marker) in windows.iter_mut().zip(markers.iter_mut()) {
+            if marker.is_none() && window.push(b) {
+                *marker = Some(window.position as usize);
+                remaining -= 1;
+            }
+        }
+    }
+    markers
+}
+
+// Gets the length and (one-indexed) end position of the longest run of all-different characters in 'stream'
+// Characters are compared byte by byte as 'get_start_marker' does, and the first of several longest runs is chosen.
+// eg: longest_distinct_run("abcabcdab") == (4, 7), for "abcd"
+pub fn longest_distinct_run(stream: &str) -> (usize, usize) {
+    let mut run = DistinctRun::new();
+    for &b in stream.as_bytes() {
+        run.push(b);
+    }
+    (run.longest.0, run.longest.1 as usize)
+}
+
+// Gets location of start marker of size 'marker_length' in a stream of any symbols, such as token IDs rather than characters
+// The window counts its symbols in a HashMap, so each step is O(1) on average but hashes and may allocate.
+// 'get_start_marker' and 'get_start_marker_bytes' find the same markers in text and bytes with a fixed array of 256
+// counts instead, which never hashes or allocates after it starts, and 'find_marker_lowercase' with a single bitmask.
+// eg: find_marker(vec![7, 7, 3, 9], 3) == Some(4)
+pub fn find_marker<I, T>(stream: I, marker_length: usize) -> Option<usize>
+where
+    I: IntoIterator<Item = T>,
+    T: Eq + Hash + Clone,
+{
+    let mut recent = VecDeque::with_capacity(marker_length + 1);
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    let mut duplicated = 0; // number of symbols that appear more than once in the window
+    for (i, symbol) in stream.into_iter().enumerate() {
+        let count = counts.entry(symbol.clone()).or_insert(0);
+        *count += 1;
+        if *count == 2 {
+            duplicated += 1;
+        }
+        recent.push_back(symbol);
+
+        // Drop the oldest symbol once the window is full, forgetting symbols that have left it entirely
+        if recent.len() > marker_length {
+            let old = recent.pop_front().expect("window is not empty");
+            let count = counts.get_mut(&old).expect("symbols in the window are counted");
+            *count -= 1;
+            match *count {
+                0 => { counts.remove(&old); },
+                1 => duplicated -= 1,
+                _ => (),
+            }
+        }
+
+        if recent.len() == marker_length && duplicated == 0 {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+// Gets location of the first window of 'marker_length' characters in 'stream' in which at most 'max_duplicates' characters repeat
+// A character counts once towards 'max_duplicates' however many times it appears, so "aaab" has one repeat, and with a
+// 'max_duplicates' of 0 this is 'get_start_marker'. As a window can't have more repeats than half its length, any
+// 'max_duplicates' of at least 'marker_length' matches the first full window, at position 'marker_length'.
+// eg: find_marker_with_tolerance("aabcc", 4, 1) == Some(4), for "aabc"
+pub fn find_marker_with_tolerance(stream: &str, marker_length: usize, max_duplicates: usize) -> Option<usize> {
+    let mut window = Window::new(marker_length);
+    for &b in stream.as_bytes() {
+        window.push(b);
+        if window.within_tolerance(max_duplicates) {
+            return Some(window.position as usize);
+        }
+    }
+    None
 }
 
 // Gets location of start marker of size 'marker_length' for alphabetic string 'stream'
 // The start marker represents the first position in the string for which there have been
 // 'marker_length' unique characters in a row.
 // (return a None if no such start_marker exists).
-fn get_start_marker(stream : &str, marker_length: usize) -> Option<usize> {
+// Characters are compared byte by byte (see 'get_start_marker_bytes'), which is the same for ASCII signals.
+pub fn get_start_marker(stream : &str, marker_length: usize) -> Option<usize> {
+    get_start_marker_bytes(stream.as_bytes(), marker_length)
+}
+
+// Gets location of start marker of size 'marker_length' for a signal of any bytes, as 'get_start_marker' does for strings
+// Positions count bytes, so a multi-byte UTF-8 character counts as several (possibly repeated) bytes.
+// eg: get_start_marker_bytes("ééab".as_bytes(), 2) == Some(2), as 'é' is the two different bytes 0xC3 0xA9
+pub fn get_start_marker_bytes(stream: &[u8], marker_length: usize) -> Option<usize> {
+    MarkerPositions::new(stream, marker_length).next()
+}
+
+// Gets location of start marker of size 'marker_length' as 'get_start_marker_bytes' does, faster for signals of only 'a'-'z'
+// Each letter is a bit that is flipped as it enters and leaves the window, so a bit is set when its letter appears an odd
+// number of times, and the window is a marker exactly when 'marker_length' bits are set.
+// The alphabet is checked up front, and other signals (or a 'marker_length' of 0) are searched by 'get_start_marker_bytes'.
+pub fn find_marker_lowercase(stream: &[u8], marker_length: usize) -> Option<usize> {
+    if marker_length == 0 || !stream.iter().all(u8::is_ascii_lowercase) {
+        return get_start_marker_bytes(stream, marker_length);
+    }
+
+    let bit = |b: u8| 1u32 << (b - b'a');
+    let mut mask = 0;
+    for (i, &b) in stream.iter().enumerate() {
+        mask ^= bit(b);
+        if i >= marker_length {
+            mask ^= bit(stream[i - marker_length]);
+        }
+        if mask.count_ones() as usize == marker_length {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+// Gets location of start marker of size 'marker_length' as 'get_start_marker_bytes' does, searching on 'threads' threads at once
+// The signal is split into one chunk per thread, each also holding the 'marker_length' - 1 bytes before it, so a marker
+// spanning two chunks is found whole by the later one. Each chunk only finds markers that end inside it, and the earliest wins.
+pub fn find_marker_parallel(stream: &[u8], marker_length: usize, threads: usize) -> Option<usize> {
+    let chunk_size = stream.len().div_ceil(threads.max(1)).max(1);
+    let overlap = marker_length.saturating_sub(1);
+
+    thread::scope(|scope| {
+        let searches: Vec<_> = (0..stream.len()).step_by(chunk_size).map(|start| {
+            let offset = start.saturating_sub(overlap);
+            let chunk = &stream[offset..stream.len().min(start + chunk_size)];
+            scope.spawn(move || get_start_marker_bytes(chunk, marker_length).map(|position| offset + position))
+        }).collect();
+
+        // Chunks are in order, so the first marker found is the earliest
+        let mut markers = searches.into_iter().map(|search| search.join().expect("marker search panicked"));
+        markers.find_map(|marker| marker)
+    })
+}
+
+// Gets every (one-indexed) position in 'stream' at which the last 'marker_length' characters are all different, in order
+// Positions are found lazily, as the iterator is advanced, and count bytes as 'get_start_marker' does.
+// eg: marker_positions("abcab", 3) yields 3, 4, 5
+pub fn marker_positions(stream: &str, marker_length: usize) -> impl Iterator<Item = usize> + '_ {
+    MarkerPositions::new(stream.as_bytes(), marker_length)
+}
+
+// A start marker found in a signal, with its (one-indexed, inclusive) position
+#[derive(Debug, PartialEq)]
+struct Marker {
+    start_pos: usize, // position of the marker's first character
+    end_pos: usize, // position of the marker's last character, which is the puzzle's answer
+    chars: String, // the marker's characters, with any bytes that are not UTF-8 replaced
+}
+
+impl From<&Window> for Marker {
+    fn from(window: &Window) -> Marker {
+        Marker {
+            start_pos: window.position as usize + 1 - window.marker_length,
+            end_pos: window.position as usize,
+            chars: String::from_utf8_lossy(&window.contents()).into_owned(),
+        }
+    }
+}
+
+impl fmt::Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' at characters {}-{}", self.chars, self.start_pos, self.end_pos)
+    }
+}
+
+// Gets location of start marker of size 'marker_length' in the first line of 'reader', as 'get_start_marker_bytes' does
+// The signal is read in fixed-size chunks, so only the last 'marker_length' bytes are held at once, and positions count
+// from the start of 'reader'. The search ends at the first newline ('\n' or '\r\n'), which is not part of the signal.
+// eg: find_marker_in_reader(Cursor::new("aabcd\nefgh"), 3) == Ok(Some(4))
+pub fn find_marker_in_reader(reader: impl Read, marker_length: usize) -> io::Result<Option<u64>> {
+    let mut marker = None;
+    scan_lines(reader, &LineWindows::new(&[marker_length]), |_, windows| {
+        marker = windows.markers[0].as_ref().map(|window| window.position);
+        false
+    })?;
+    Ok(marker)
+}
+
+// Searches every line of 'reader' as 'windows' does (see 'scan_lines'), returning each line's (one-indexed) number and windows
+// The signal is read in fixed-size chunks, so only the last 'marker_length' bytes of each line are held at once.
+// There is always at least one line, which is empty if 'reader' is.
+fn read_signals(reader: impl Read, windows: &LineWindows) -> io::Result<Vec<(usize, LineWindows)>> {
+    let mut signals = Vec::new();
+    scan_lines(reader, windows, |line, windows| {
+        signals.push((line, windows.clone()));
+        true
+    })?;
+    Ok(signals)
+}
+
+// Searches each line of 'reader' with a fresh copy of 'windows', reading it in fixed-size chunks
+// Calls 'each' once per line with its (one-indexed) number and the windows searching it, which hold each size's marker
+// if there is one, stopping once 'each' returns false.
+// Lines end with '\n' or '\r\n', which are not part of the signal, and a final empty line after the last newline is still passed to 'each'.
+fn scan_lines(mut reader: impl Read, template: &LineWindows, mut each: impl FnMut(usize, &LineWindows) -> bool) -> io::Result<()> {
+    let mut windows = template.clone();
+    let mut line = 1;
+    let mut found = false; // whether this line's markers have been passed to 'each', so the rest of the line can be skipped
+    let mut carriage_return = false; // whether the last byte was a '\r' that may start a '\r\n'
+    let mut chunk = [0; 8192];
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for &b in &chunk[..read] {
+            if b == b'\n' {
+                if !found && !each(line, &windows) {
+                    return Ok(());
+                }
+                line += 1;
+                windows = template.clone();
+                found = false;
+                carriage_return = false;
+                continue;
+            }
+            if found {
+                continue;
+            }
+
+            // A '\r' is only part of the signal if it is not followed by a '\n'
+            let pending = carriage_return;
+            carriage_return = b == b'\r';
+            if pending {
+                windows.push(b'\r');
+            }
+            if !carriage_return {
+                windows.push(b);
+            }
+            if windows.all_found() {
+                found = true;
+                if !each(line, &windows) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    // A '\r' at the very end of the signal is part of it
+    if !found {
+        if carriage_return {
+            windows.push(b'\r');
+        }
+        each(line, &windows);
+    }
+    Ok(())
+}
+
+// The windows searching one line of a signal for markers of several sizes at once (see 'scan_lines')
+#[derive(Clone)]
+struct LineWindows {
+    windows: Vec<Window>, // a window for each marker size
+    markers: Vec<Option<Window>>, // each window as it was when its marker was found
+    length: u64, // number of bytes pushed, which is the whole line unless every marker was found before its end
+}
+
+impl LineWindows {
+    fn new(marker_lengths: &[usize]) -> LineWindows {
+        LineWindows {
+            windows: marker_lengths.iter().map(|&marker_length| Window::new(marker_length)).collect(),
+            markers: vec![None; marker_lengths.len()],
+            length: 0,
+        }
+    }
+
+    // Pushes 'b' into each window still searching for its marker
+    fn push(&mut self, b: u8) {
+        self.length += 1;
+        for (window, marker) in self.windows.iter_mut().zip(self.markers.iter_mut()) {
+            if marker.is_none() && window.push(b) {
+                *marker = Some(window.clone());
+            }
+        }
+    }
+
+    fn all_found(&self) -> bool {
+        self.markers.iter().all(Option::is_some)
+    }
+}
+
+// The longest run of all-different bytes in a signal, as it is pushed one byte at a time (see 'longest_distinct_run')
+// Keeps the run ending at the latest byte, dropping bytes from its start until it has no duplicates again.
+#[derive(Clone)]
+struct DistinctRun {
+    run: VecDeque<u8>, // the run ending at the latest byte, which never holds more than 256 bytes
+    counts: ByteCounts, // count of each byte in 'run'
+    position: u64, // number of bytes pushed so far
+    longest: (usize, u64), // length and (one-indexed) end position of the longest run so far
+}
+
+impl DistinctRun {
+    fn new() -> DistinctRun {
+        DistinctRun {
+            run: VecDeque::new(),
+            counts: ByteCounts::new(),
+            position: 0,
+            longest: (0, 0),
+        }
+    }
+
+    fn push(&mut self, b: u8) {
+        self.position += 1;
+        self.run.push_back(b);
+        self.counts.add(b);
+        while !self.counts.all_distinct() {
+            let old = self.run.pop_front().expect("a run with a duplicate is not empty");
+            self.counts.remove(old);
+        }
+        if self.run.len() > self.longest.0 {
+            self.longest = (self.run.len(), self.position);
+        }
+    }
+}
+
+// Iterator over start marker positions in a signal of bytes (see 'marker_positions')
+struct MarkerPositions<'a> {
+    bytes: slice::Iter<'a, u8>, // bytes yet to enter the window
+    window: Window, // the last 'marker_length' bytes
+}
+
+impl MarkerPositions<'_> {
+    fn new(stream: &[u8], marker_length: usize) -> MarkerPositions<'_> {
+        MarkerPositions {
+            bytes: stream.iter(),
+            window: Window::new(marker_length),
+        }
+    }
+}
+
+impl Iterator for MarkerPositions<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        for &b in self.bytes.by_ref() {
+            if self.window.push(b) {
+                return Some(self.window.position as usize);
+            }
+        }
+        None
+    }
+}
+
+// The last 'marker_length' bytes of a signal, as they are pushed one at a time
+// Keeps a rolling count of each byte in the window, so each position is checked in O(1).
+#[derive(Clone)]
+struct Window {
+    marker_length: usize, // number of different bytes in a row that make a marker
+    recent: Vec<u8>, // the bytes in the window, as a ring indexed by position
+    position: u64, // number of bytes that have entered the window so far
+    counts: ByteCounts, // count of each byte in the window
+}
+
+impl Window {
+    fn new(marker_length: usize) -> Window {
+        Window {
+            marker_length,
+            recent: vec![0; marker_length],
+            position: 0,
+            counts: ByteCounts::new(),
+        }
+    }
+
+    // Pushes 'b' into the window, dropping the oldest byte if it is full
+    // Returns whether the window then holds a marker: 'marker_length' bytes, all different.
+    fn push(&mut self, b: u8) -> bool {
+        self.counts.add(b);
+
+        if self.marker_length > 0 {
+            let slot = (self.position % self.marker_length as u64) as usize;
+            if self.position >= self.marker_length as u64 {
+                self.counts.remove(self.recent[slot]);
+            }
+            self.recent[slot] = b;
+        } else {
+            // An empty window holds nothing, so the byte leaves as soon as it enters
+            self.counts.remove(b);
+        }
+
+        self.position += 1;
+        self.within_tolerance(0)
+    }
+
+    // Whether the window is full and no more than 'max_duplicates' different bytes appear in it more than once
+    fn within_tolerance(&self, max_duplicates: usize) -> bool {
+        self.position >= self.marker_length as u64 && self.counts.duplicated <= max_duplicates
+    }
+
+    // Gets the bytes in the window, oldest first
+    fn contents(&self) -> Vec<u8> {
+        let filled = self.position.min(self.marker_length as u64) as usize;
+        let oldest = if filled < self.marker_length { 0 } else { (self.position % self.marker_length as u64) as usize };
+        (0..filled).map(|i| self.recent[(oldest + i) % self.marker_length]).collect()
+    }
+}
+
+// A count of each byte in a stretch of signal, tracking whether any byte appears more than once
+#[derive(Clone)]
+struct ByteCounts {
+    counts: [usize; 256], // count of each byte
+    duplicated: usize, // number of bytes that appear more than once
+}
+
+impl ByteCounts {
+    fn new() -> ByteCounts {
+        ByteCounts { counts: [0; 256], duplicated: 0 }
+    }
+
+    fn add(&mut self, b: u8) {
+        self.counts[b as usize] += 1;
+        if self.counts[b as usize] == 2 {
+            self.duplicated += 1;
+        }
+    }
+
+    // Removes one 'b', which must have been added
+    fn remove(&mut self, b: u8) {
+        self.counts[b as usize] -= 1;
+        if self.counts[b as usize] == 1 {
+            self.duplicated -= 1;
+        }
+    }
+
+    fn all_distinct(&self) -> bool {
+        self.duplicated == 0
+    }
+}
+
+// Gets location of start marker as 'get_start_marker' does, but by checking the whole window for duplicates at every position
+// Kept to cross-check the rolling window against in tests.
+#[cfg(test)]
+fn get_start_marker_by_checking(stream : &str, marker_length: usize) -> Option<usize> {
+
+    // An empty window has nothing to repeat, so as with 'get_start_marker' it is a marker from the first character on
+    if marker_length == 0 {
+        return stream.chars().next().map(|_| 1);
+    }
 
     // Maintain array of the most recent 'marker_length' many characters
-    let mut recents = Vec::new();
-    recents.reserve(marker_length-1);
+    let mut recents = Vec::with_capacity(marker_length);
 
     for (i,c) in stream.chars().enumerate() {
         // Add current value to recent value array, up to size 'marker_length'
@@ -57,7 +583,7 @@ fn get_start_marker(stream : &str, marker_length: usize) -> Option<usize> {
 
         if recents.len() >= marker_length {
             // If marker is full, check if there are any duplicates in recent character array (making it not a valid marker)
-            let duplicate = check_duplicates(recents.to_vec());
+            let duplicate = check_duplicates(&recents);
             if !duplicate {
                 return Some(i + 1); //if valid marker, return index +1 because advent of code design specifies one-indexed
             }
@@ -67,36 +593,59 @@ fn get_start_marker(stream : &str, marker_length: usize) -> Option<usize> {
 }
 
 
-// Checks for duplicates elements in a Vector
-// Since we are using characters, which can be ordered, do the n log n solution of sorting and iterating through.
-fn check_duplicates<T : PartialEq + Ord>(arr : Vec<T>) -> bool {
+// Largest slice 'check_duplicates' compares pairwise, beyond which sorting is faster
+const PAIRWISE_CHECK_LIMIT: usize = 16;
+
+// Checks for duplicates elements in a slice
+// Small slices (such as the puzzle's 4 and 14 character windows) compare every pair, which needs no allocation and beats sorting.
+// Since we are using characters, which can be ordered, larger slices do the n log n solution of sorting references and iterating through.
+pub fn check_duplicates<T : Ord>(arr : &[T]) -> bool {
+    if arr.len() <= PAIRWISE_CHECK_LIMIT {
+        // n^2, but n is small
+        return arr.iter().enumerate().any(|(i, x)| arr[i + 1..].contains(x));
+    }
+
     // n log n
-    let mut arr = Vec::from(arr);
-    arr.sort();
+    let mut sorted: Vec<&T> = arr.iter().collect();
+    sorted.sort();
 
     // Iterate to find any duplicates in a row
-    for i in 0..arr.len() {
-        match (arr.get(i), arr.get(i + 1)) {
-            (Some(x), Some(y)) => {
-                if x == y { return true }
-            }
-            _ => continue
-        }
-    }
-    false
+    sorted.windows(2).any(|pair| pair[0] == pair[1])
 }
 
 #[cfg(test)]
 mod tests {
     use super::get_start_marker;
-    use crate::day_6::check_duplicates;
+    use super::get_start_marker_by_checking;
+    use super::marker_positions;
+    use super::get_start_marker_bytes;
+    use super::{read_signals, LineWindows, Marker};
+    use super::{find_markers, find_marker_in_reader};
+    use super::longest_distinct_run;
+    use super::find_marker_lowercase;
+    use super::find_marker_parallel;
+    use super::{solve, Day6Error};
+    use super::find_marker;
+    use super::find_marker_with_tolerance;
+    use super::{solve_with_len, MarkerLengthError};
+    use std::io::{Cursor, Read};
+    use crate::day_6::{check_duplicates, PAIRWISE_CHECK_LIMIT};
 
     #[test]
     fn check_duplicates_test() {
         // Ensures duplicate function correctly identifies presence of duplicates in lists of ordinal
-        assert!(!check_duplicates(vec![0,1,2,3,4,5]));
-        assert!(!check_duplicates(vec!['A','B','C','D','E']));
-        assert!(check_duplicates(vec!['A','B','E','C','D','E']));
+        assert!(!check_duplicates(&[0,1,2,3,4,5]));
+        assert!(!check_duplicates(&['A','B','C','D','E']));
+        assert!(check_duplicates(&['A','B','E','C','D','E']));
+        assert!(!check_duplicates::<char>(&[]));
+        assert!(!check_duplicates(&['A']));
+
+        // Slices too large to compare pairwise are sorted instead, and must agree
+        let letters: Vec<char> = ('A'..='Z').collect();
+        assert!(letters.len() > PAIRWISE_CHECK_LIMIT);
+        assert!(!check_duplicates(&letters));
+        assert!(check_duplicates(&[&letters[..], &['Q']].concat()));
+        assert!(check_duplicates(&[&['Z'], &letters[..]].concat()));
     }
 
 
@@ -126,4 +675,453 @@ mod tests {
 
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn every_marker_position() {
+        // Advent of Code challenge-provided examples, with every marker rather than the first
+        for (stream, expected) in [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", (7..=30).collect::<Vec<usize>>()),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", (5..=10).chain(12..=28).collect()),
+            ("nppdvjthqldpwncqszvftbrmjlhg", (6..=28).collect()),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", (10..=14).chain(16..=33).collect()),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", (11..=32).collect()),
+        ] {
+            assert_eq!(marker_positions(stream, 4).collect::<Vec<usize>>(), expected, "{stream}");
+            assert_eq!(marker_positions(stream, 4).next(), get_start_marker(stream, 4));
+        }
+
+        assert_eq!(marker_positions("abcab", 3).collect::<Vec<usize>>(), [3, 4, 5]);
+        assert_eq!(marker_positions("AAAAAA", 2).count(), 0);
+    }
+
+    #[test]
+    fn binary_signals() {
+        // Any byte can be part of a marker, including zero and high-bit bytes
+        assert_eq!(get_start_marker_bytes(&[0, 0, 1, 0, 2, 0xFF], 3), Some(5));
+        assert_eq!(get_start_marker_bytes(&[0x80, 0xFF, 0x80, 0xFF, 0x00, 0x7F], 4), Some(6));
+        assert_eq!(get_start_marker_bytes(&[0, 0, 0, 0], 2), None);
+        assert_eq!(get_start_marker_bytes(&(0..=255).collect::<Vec<u8>>(), 256), Some(256));
+        assert_eq!(get_start_marker_bytes(b"", 1), None);
+
+        // Strings are compared byte by byte, so 'é' (0xC3 0xA9) is two different bytes rather than one repeated character
+        assert_eq!(get_start_marker("ééab", 2), Some(2));
+        assert_eq!(get_start_marker("éé", 3), None);
+        assert_eq!(get_start_marker("bvwbjplbgvbhsrlpgdmjqwftvncz", 4), get_start_marker_bytes(b"bvwbjplbgvbhsrlpgdmjqwftvncz", 4));
+    }
+
+    // Reader that hands out at most 'chunk' bytes per read, to split the signal at awkward places
+    struct Chunked<R> {
+        inner: R,
+        chunk: usize,
+    }
+
+    impl<R: Read> Read for Chunked<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.chunk);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    // Gets location of start marker of each size in 'marker_lengths' in every non-empty line of 'reader', alongside its line
+    fn find_markers_by_line(reader: impl Read, marker_lengths: &[usize]) -> std::io::Result<Vec<(usize, Vec<Option<u64>>)>> {
+        Ok(read_signals(reader, &LineWindows::new(marker_lengths))?.into_iter()
+            .filter(|(_, windows)| windows.length > 0)
+            .map(|(line, windows)| (line, windows.markers.iter().map(|window| window.as_ref().map(|window| window.position)).collect()))
+            .collect())
+    }
+
+    #[test]
+    fn streaming() {
+        // Markers straddling chunk edges are found at their absolute position, as in memory
+        let stream = random_stream(20_000, 15, 0x2022) + "abcdefghijklmnop";
+        for chunk in [1, 3, 13, 14, 4096, 100_000] {
+            for marker_length in [1, 4, 14, 16] {
+                let reader = Chunked { inner: Cursor::new(&stream), chunk };
+                assert_eq!(find_marker_in_reader(reader, marker_length).unwrap(),
+                    get_start_marker(&stream, marker_length).map(|t| t as u64), "{chunk} {marker_length}");
+            }
+        }
+
+        // Only the first line is searched, with or without a '\r'
+        assert_eq!(find_marker_in_reader(Cursor::new("aab\r\nabcd"), 3).unwrap(), None);
+        assert_eq!(find_marker_in_reader(Cursor::new("aabc\r\nabcd"), 3).unwrap(), Some(4));
+        assert_eq!(find_marker_in_reader(Cursor::new("aab\rc"), 3).unwrap(), Some(4));
+        assert_eq!(find_marker_in_reader(Cursor::new("aab\r"), 3).unwrap(), Some(4));
+        assert_eq!(find_marker_in_reader(Cursor::new(""), 4).unwrap(), None);
+    }
+
+    #[test]
+    fn every_line() {
+        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb\r\n\naaaaaaa\nbvwbjplbgvbhsrlpgdmjqwftvncz\n";
+        let markers = find_markers_by_line(Cursor::new(input), &[4]).unwrap();
+        assert_eq!(markers, [(1, vec![Some(7)]), (3, vec![None]), (4, vec![Some(5)])]);
+
+        // Lines are split the same way across chunk edges, and the last line needs no newline
+        for chunk in [1, 2, 5] {
+            let reader = Chunked { inner: Cursor::new(input.trim_end()), chunk };
+            assert_eq!(find_markers_by_line(reader, &[4, 14]).unwrap(),
+                [(1, vec![Some(7), Some(19)]), (3, vec![None, None]), (4, vec![Some(5), Some(23)])]);
+        }
+        assert_eq!(find_markers_by_line(Cursor::new(""), &[4]).unwrap(), []);
+        assert_eq!(read_signals(Cursor::new(""), &LineWindows::new(&[4])).unwrap().len(), 1);
+    }
+
+    // Gets the start marker of each size in 'marker_lengths' in the first line of 'reader', with its characters
+    fn read_markers(reader: impl Read, marker_lengths: &[usize]) -> std::io::Result<Vec<Option<Marker>>> {
+        let (_, first_line) = read_signals(reader, &LineWindows::new(marker_lengths))?.remove(0);
+        Ok(first_line.markers.iter().map(|window| window.as_ref().map(Marker::from)).collect())
+    }
+
+    #[test]
+    fn marker_contents() {
+        // Advent of Code challenge-provided examples
+        for (stream, marker_length, expected) in [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4, "jpqm"),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 4, "vwbj"),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 4, "pdvj"),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 4, "rfnt"),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 4, "zqfr"),
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14, "qmgbljsphdztnv"),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 14, "vbhsrlpgdmjqwf"),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 14, "ldpwncqszvftbr"),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14, "wmzdfjlvtqnbhc"),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14, "jwzlrfnpqdbhtm"),
+        ] {
+            let marker = read_markers(Cursor::new(stream), &[marker_length]).unwrap().remove(0).unwrap();
+            assert_eq!(marker.chars, expected, "{stream}");
+            assert_eq!(Some(marker.end_pos), get_start_marker(stream, marker_length));
+            assert_eq!(&stream[marker.start_pos - 1..marker.end_pos], expected);
+        }
+
+        let marker = read_markers(Cursor::new("aabcd"), &[3]).unwrap().remove(0).unwrap();
+        assert_eq!(marker, Marker { start_pos: 2, end_pos: 4, chars: "abc".to_string() });
+        assert_eq!(marker.to_string(), "'abc' at characters 2-4");
+        assert_eq!(read_markers(Cursor::new("ab"), &[1]).unwrap().remove(0).map(|m| m.chars), Some("a".to_string()));
+
+        // Markers of several sizes are read together
+        let markers = read_markers(Cursor::new("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), &[4, 14]).unwrap();
+        let chars: Vec<Option<String>> = markers.into_iter().map(|m| m.map(|m| m.chars)).collect();
+        assert_eq!(chars, [Some("jpqm".to_string()), Some("qmgbljsphdztnv".to_string())]);
+    }
+
+    #[test]
+    fn longest_run() {
+        // An all-unique string is one run, and an all-identical one has runs of a single character
+        assert_eq!(longest_distinct_run("abcdefghijklmnopqrstuvwxyz"), (26, 26));
+        assert_eq!(longest_distinct_run("aaaaaa"), (1, 1));
+        assert_eq!(longest_distinct_run(""), (0, 0));
+        assert_eq!(longest_distinct_run("abcabcdab"), (4, 7));
+
+        // Advent of Code challenge-provided examples
+        assert_eq!(longest_distinct_run("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), (18, 30)); // "phdztnvjfqwrcgsmlb"
+        assert_eq!(longest_distinct_run("bvwbjplbgvbhsrlpgdmjqwftvncz"), (18, 28)); // "bhsrlpgdmjqwftvncz"
+        assert_eq!(longest_distinct_run("nppdvjthqldpwncqszvftbrmjlhg"), (18, 28)); // "dpwncqszvftbrmjlhg"
+        assert_eq!(longest_distinct_run("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), (18, 33)); // "wmzdfjlvtqnbhcprsg"
+        assert_eq!(longest_distinct_run("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), (18, 30)); // "jwzlrfnpqdbhtmscgv"
+
+        // The longest run is at least as long as any marker found
+        for seed in 1..=20 {
+            let stream = random_stream(300, 3 + seed % 20, seed);
+            let (length, end) = longest_distinct_run(&stream);
+            assert_eq!(get_start_marker(&stream, length + 1), None, "{stream}");
+            assert_eq!(get_start_marker(&stream, length), Some(end), "{stream}");
+        }
+    }
+
+    #[test]
+    fn lowercase_markers() {
+        // Advent of Code challenge-provided examples
+        assert_eq!(find_marker_lowercase(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4), Some(7));
+        assert_eq!(find_marker_lowercase(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), Some(19));
+        assert_eq!(find_marker_lowercase(b"zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14), Some(26));
+
+        // A marker can't be longer than the alphabet, and other signals fall back to the general search
+        assert_eq!(find_marker_lowercase(b"abcdefghijklmnopqrstuvwxyzabc", 27), None);
+        assert_eq!(find_marker_lowercase(b"abcdefghijklmnopqrstuvwxyz", 26), Some(26));
+        assert_eq!(find_marker_lowercase(b"aaaA", 2), Some(4));
+        assert_eq!(find_marker_lowercase(b"aabc\n", 4), Some(5));
+        assert_eq!(find_marker_lowercase(b"ab", 0), get_start_marker("ab", 0));
+        assert_eq!(find_marker_lowercase(b"", 4), None);
+
+        // The bitmask finds the same markers as the general search
+        for seed in 1..=100 {
+            let stream = random_stream(400, 2 + seed % 25, seed);
+            for marker_length in 1..=27 {
+                assert_eq!(find_marker_lowercase(stream.as_bytes(), marker_length), get_start_marker(&stream, marker_length),
+                    "{stream} {marker_length}");
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_markers() {
+        // Advent of Code challenge-provided examples
+        assert_eq!(find_marker_parallel(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4, 3), Some(7));
+        assert_eq!(find_marker_parallel(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14, 4), Some(19));
+        assert_eq!(find_marker_parallel(b"", 4, 4), None);
+        assert_eq!(find_marker_parallel(b"abcd", 4, 0), Some(4));
+        assert_eq!(find_marker_parallel(b"abcd", 4, 10), Some(4));
+
+        // A lone marker placed at, just before and just after every split point is found whole, however the signal is split
+        // (every byte is a marker of length 1, so markers start at length 2)
+        for marker_length in 2..=6 {
+            for end in marker_length..=40 {
+                let mut stream = vec![b'a'; 40];
+                for (i, b) in stream[end - marker_length..end].iter_mut().enumerate() {
+                    *b = b'b' + i as u8;
+                }
+                let expected = get_start_marker_bytes(&stream, marker_length);
+                assert!(expected == Some(end) || expected == Some(end - 1)); // the 'a' before the marker may start it a byte early
+                for threads in 1..=9 {
+                    assert_eq!(find_marker_parallel(&stream, marker_length, threads), expected,
+                        "{marker_length} {end} {threads}");
+                }
+            }
+        }
+
+        // Random signals give the same answer as the sequential search
+        for seed in 1..=30 {
+            let stream = random_stream(2000, 4 + seed % 12, seed);
+            for marker_length in [1, 4, 9, 14] {
+                for threads in [1, 2, 7, 16] {
+                    assert_eq!(find_marker_parallel(stream.as_bytes(), marker_length, threads), get_start_marker(&stream, marker_length),
+                        "{stream} {marker_length} {threads}");
+                }
+            }
+        }
+    }
+
+    // Gets the puzzle's answers for the first line of 'reader', as the runner does
+    fn solve_reader(reader: impl Read, marker_lengths: &[usize]) -> Result<Vec<u64>, Day6Error> {
+        solve(&read_signals(reader, &LineWindows::new(marker_lengths)).unwrap()[0].1)
+    }
+
+    #[test]
+    fn solve_signal() {
+        assert_eq!(solve_reader(Cursor::new("mjqjpqmgbljsphdztnvjfqwrcgsmlb\nabcd"), &[4, 14]), Ok(vec![7, 19]));
+
+        // An all-identical signal has no marker, so can't be solved
+        let error = solve_reader(Cursor::new("aaaaaa\r\nabcdefghijklmn"), &[4, 14]).unwrap_err();
+        assert_eq!(error, Day6Error::MarkerNotFound { len: 4, stream_len: 6 });
+        assert_eq!(error.to_string(), "could not find a start marker of 4 different characters in a signal of 6");
+
+        // Only the first size without a marker is reported
+        assert_eq!(solve_reader(Cursor::new("abcdabcd"), &[4, 14]), Err(Day6Error::MarkerNotFound { len: 14, stream_len: 8 }));
+        assert_eq!(solve_reader(Cursor::new(""), &[4]), Err(Day6Error::MarkerNotFound { len: 4, stream_len: 0 }));
+    }
+
+    #[test]
+    fn generic_markers() {
+        // Token IDs
+        assert_eq!(find_marker(vec![7u32, 7, 3, 9], 3), Some(4));
+        assert_eq!(find_marker(vec![1u32, 2, 1, 2, 1, 2], 3), None);
+        assert_eq!(find_marker(vec![100_000u32, 5, 100_000, 6, 7], 3), Some(4));
+        assert_eq!(find_marker(Vec::<u32>::new(), 1), None);
+        assert_eq!(find_marker(vec![4u32], 0), get_start_marker("a", 0));
+
+        // The character search is a specialization of the generic one
+        for stream in [
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            "bvwbjplbgvbhsrlpgdmjqwftvncz",
+            "nppdvjthqldpwncqszvftbrmjlhg",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+        ] {
+            for marker_length in [4, 14] {
+                assert_eq!(find_marker(stream.chars(), marker_length), get_start_marker(stream, marker_length), "{stream}");
+            }
+        }
+
+        // As are the byte searches, on random signals
+        for seed in 1..=30 {
+            let stream = random_stream(300, 3 + seed % 20, seed);
+            for marker_length in 1..=16 {
+                assert_eq!(find_marker(stream.bytes(), marker_length), get_start_marker_bytes(stream.as_bytes(), marker_length));
+            }
+        }
+    }
+
+    #[test]
+    fn tolerant_markers() {
+        assert_eq!(find_marker_with_tolerance("aabcc", 4, 1), Some(4));
+        assert_eq!(find_marker_with_tolerance("aabbcd", 4, 1), Some(5));
+        assert_eq!(find_marker_with_tolerance("aabbcd", 4, 2), Some(4));
+
+        // A character repeated three times counts once towards the repeats allowed
+        assert_eq!(find_marker_with_tolerance("aaab", 4, 1), Some(4));
+        assert_eq!(find_marker_with_tolerance("aaabcx", 5, 1), Some(5));
+        assert_eq!(find_marker_with_tolerance("aabbx", 5, 1), None);
+        assert_eq!(find_marker_with_tolerance("aaaaaa", 4, 1), Some(4));
+
+        // Allowing as many repeats as the window is long matches the first full window
+        assert_eq!(find_marker_with_tolerance("aaaaaa", 4, 4), Some(4));
+        assert_eq!(find_marker_with_tolerance("aaa", 4, 4), None);
+
+        // No repeats is the puzzle's marker
+        for stream in [
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            "bvwbjplbgvbhsrlpgdmjqwftvncz",
+            "nppdvjthqldpwncqszvftbrmjlhg",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+            "aaaaaa",
+        ] {
+            for marker_length in [1, 4, 14] {
+                assert_eq!(find_marker_with_tolerance(stream, marker_length, 0), get_start_marker(stream, marker_length), "{stream}");
+            }
+        }
+
+        // Allowing more repeats never finds a window later
+        for seed in 1..=20 {
+            let stream = random_stream(300, 3 + seed % 12, seed);
+            let positions: Vec<Option<usize>> = (0..8).map(|k| find_marker_with_tolerance(&stream, 14, k)).collect();
+            for pair in positions.windows(2) {
+                if let Some(stricter) = pair[0] {
+                    assert!(pair[1].is_some_and(|looser| looser <= stricter), "{stream}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn both_markers() {
+        // Advent of Code challenge-provided examples
+        assert_eq!(find_markers("mjqjpqmgbljsphdztnvjfqwrcgsmlb", &[4, 14]), [Some(7), Some(19)]);
+        assert_eq!(find_markers("bvwbjplbgvbhsrlpgdmjqwftvncz", &[4, 14]), [Some(5), Some(23)]);
+        assert_eq!(find_markers("nppdvjthqldpwncqszvftbrmjlhg", &[4, 14]), [Some(6), Some(23)]);
+        assert_eq!(find_markers("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", &[4, 14]), [Some(10), Some(29)]);
+        assert_eq!(find_markers("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", &[4, 14]), [Some(11), Some(26)]);
+
+        // A signal can have a start-of-packet marker without a start-of-message marker
+        assert_eq!(find_markers("abcdabcd", &[4, 14]), [Some(4), None]);
+        assert_eq!(find_markers("", &[4, 14]), [None, None]);
+        assert_eq!(find_markers("abcabcd", &[3, 4]), [Some(3), Some(7)]);
+        assert_eq!(find_markers("abc", &[]), []);
+
+        // Searching for several sizes at once finds the same markers as searching for each alone
+        for seed in 1..=50 {
+            let stream = random_stream(500, 3 + seed % 14, seed);
+            let lengths = [1, 4, 7, 14, 20];
+            let individual: Vec<Option<usize>> = lengths.iter().map(|&length| get_start_marker(&stream, length)).collect();
+            assert_eq!(find_markers(&stream, &lengths), individual, "{stream}");
+            assert_eq!(find_markers(&stream, &[4, 14]), [individual[1], individual[3]], "{stream}");
+        }
+    }
+
+    #[test]
+    fn marker_lengths() {
+        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb\nabcd";
+
+        // The puzzle's lengths give its answers, from the first line only
+        assert_eq!(solve_with_len(input, 4), Ok(Some(7)));
+        assert_eq!(solve_with_len(input, 14), Ok(Some(19)));
+
+        // A single character is always a marker, and the whole line can be one
+        assert_eq!(solve_with_len(input, 1), Ok(Some(1)));
+        assert_eq!(solve_with_len("abcdef", 6), Ok(Some(6)));
+        assert_eq!(solve_with_len("abcdea", 6), Ok(None));
+
+        // Markers longer than the line are never found, and empty ones are not allowed
+        assert_eq!(solve_with_len(input, 31), Ok(None));
+        assert_eq!(solve_with_len("", 1), Ok(None));
+        assert_eq!(solve_with_len(input, 0), Err(MarkerLengthError));
+        assert_eq!(MarkerLengthError.to_string(), "marker length must be at least 1");
+    }
+
+    // Creates a random string of 'length' characters, from the first 'alphabet' lowercase letters
+    fn random_stream(length: usize, alphabet: u64, mut seed: u64) -> String {
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % alphabet
+        };
+        (0..length).map(|_| (b'a' + next() as u8) as char).collect()
+    }
+
+    #[test]
+    fn rolling_window() {
+        // The rolling window finds the same markers as checking each whole window, including when there are none
+        for (seed, alphabet) in [(0x2022, 4), (0x6, 8), (0xC0FFEE, 16), (0xBEEF, 26)] {
+            let stream = random_stream(500, alphabet, seed);
+            for marker_length in 1..=16 {
+                assert_eq!(get_start_marker(&stream, marker_length), get_start_marker_by_checking(&stream, marker_length),
+                    "{stream} {marker_length}");
+            }
+        }
+
+        assert_eq!(get_start_marker("", 4), None);
+
+        // Including for an empty marker, which does not underflow or divide by zero
+        for stream in ["", "a", "abc"] {
+            assert_eq!(get_start_marker_by_checking(stream, 0), get_start_marker(stream, 0), "{stream}");
+        }
+    }
+
+    // Times the parallel search against the sequential one, on a 256MB stream with its only 14 character marker at the end
+    // Run with 'cargo test --release bench_parallel_marker -- --ignored --nocapture'
+    #[test]
+    #[ignore]
+    fn bench_parallel_marker() {
+        use std::time::Instant;
+
+        let stream = random_stream(256_000_000, 13, 0x2022) + "abcdefghijklmn";
+        let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+
+        let start = Instant::now();
+        let sequential = get_start_marker(&stream, 14);
+        let sequential_time = start.elapsed();
+
+        let start = Instant::now();
+        let parallel = find_marker_parallel(stream.as_bytes(), 14, threads);
+        let parallel_time = start.elapsed();
+
+        assert_eq!(sequential, parallel);
+        assert_eq!(parallel, Some(stream.len()));
+        println!("sequential: {sequential_time:?}, parallel on {threads} threads: {parallel_time:?}");
+    }
+
+    // Times the lowercase bitmask against the general rolling window, on a 4MB stream with its only 14 character marker at the end
+    // Run with 'cargo test --release bench_lowercase_marker -- --ignored --nocapture'
+    #[test]
+    #[ignore]
+    fn bench_lowercase_marker() {
+        use std::time::Instant;
+
+        let stream = random_stream(4_000_000, 13, 0x2022) + "abcdefghijklmn";
+
+        let start = Instant::now();
+        let rolling = get_start_marker(&stream, 14);
+        let rolling_time = start.elapsed();
+
+        let start = Instant::now();
+        let bitmask = find_marker_lowercase(stream.as_bytes(), 14);
+        let bitmask_time = start.elapsed();
+
+        assert_eq!(rolling, bitmask);
+        assert_eq!(bitmask, Some(stream.len()));
+        println!("rolling: {rolling_time:?}, bitmask: {bitmask_time:?}");
+    }
+
+    // Times the rolling window against checking each whole window, on a 4MB stream with its only 14 character marker at the end
+    // Run with 'cargo test --release bench_start_marker -- --ignored --nocapture'
+    #[test]
+    #[ignore]
+    fn bench_start_marker() {
+        use std::time::Instant;
+
+        let stream = random_stream(4_000_000, 13, 0x2022) + "abcdefghijklmn";
+
+        let start = Instant::now();
+        let checked = get_start_marker_by_checking(&stream, 14);
+        let checked_time = start.elapsed();
+
+        let start = Instant::now();
+        let rolling = get_start_marker(&stream, 14);
+        let rolling_time = start.elapsed();
+
+        assert_eq!(checked, rolling);
+        assert_eq!(rolling, Some(stream.len()));
+        println!("checking: {checked_time:?}, rolling: {rolling_time:?}");
+    }
+
+}
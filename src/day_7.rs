@@ -5,22 +5,82 @@
 // In part 2, find the smallest directory to delete that frees up a total of 30000000 units.
 
 use super::*;
-use std::{collections::{HashMap, hash_map::Entry}, rc::{Rc, Weak}, cell::RefCell, error, fmt};
-use regex::Regex;
-use lazy_static::lazy_static;
-
-// A DirectoryNode is one instance of a node in the folder chain. (Follows Newtype pattern)
-// It's a wrapper around a DirectoryEntry, which has  shared ownership and has interior mutability, 
-// so its parent folder can be accessed  and it can be modified while keeping its structure. 
-// Root directory should be kept in scope so that Weak reference to parents are not dropped.
-// DirectoryNode semantically represents a tree of files and folders, mimicking the structure of a hard drive.
-struct DirectoryNode (Rc<RefCell<DirectoryEntry>>);
-type ParentAlias = Weak<RefCell<DirectoryEntry>>;
+use std::{collections::{HashMap, VecDeque}, error, fmt};
+
+// A FileSystem is a tree of files and folders, mimicking the structure of a hard drive.
+// Its nodes are kept in one arena, and refer to each other (and are referred to) by their NodeId index,
+// so the tree can be walked up and down and changed through a plain '&mut FileSystem'.
+// Removed entries are detached from their parent rather than taken out of the arena, so a NodeId stays valid for as
+// long as its FileSystem (though a removed one is no longer reachable from the root).
+// Each node also keeps its total size, which is updated as files are added and removed, so sizes never need counting up again.
+struct FileSystem {
+    nodes: Vec<Node>, // every node in the tree, with the root first
+}
+
+// Handle to one node in a FileSystem, by its index in the arena
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct NodeId(usize);
+
+// One node in a FileSystem
+struct Node {
+    parent: Option<NodeId>, // parent folder, or None for the root
+    name: String, // name the node is kept under in its parent, or "/" for the root
+    size: u64, // size of a file, or total size of every file within a folder and its subfolders
+    listed: bool, // whether 'ls' has been run in this folder
+    entry: DirectoryEntry,
+}
 
 // a DirectoryEntry, which is either a Folder or a File
 enum DirectoryEntry {
-    Folder(Option<ParentAlias>, HashMap<String, DirectoryNode>), // Weak ref to parent node, and HashMap of chldren nodes
-    File(Option<ParentAlias>, u32) // Weak ref to parent node, and file size
+    Folder(HashMap<String, NodeId>), // HashMap of children nodes, by name
+    File(u64) // file size
+}
+
+// Whether an entry in the tree is a folder or a file, as yielded by 'iter_dfs' and 'iter_bfs'
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EntryKind {
+    Folder,
+    File,
+}
+
+// An entry looked up by name in a folder, telling files apart from folders (see 'NodeId::get_entry')
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EntryRef {
+    Folder(NodeId),
+    File(NodeId, u64), // the file, and its size
+}
+
+// What 'find' knows about an entry it is checking
+#[derive(Clone, Debug, PartialEq)]
+struct EntryInfo {
+    kind: EntryKind,
+    size: u64, // size of a file, or total size of a folder
+    depth: usize, // number of folders down from the node searched from, which is at depth 0
+}
+
+// Iterator over a node and every entry beneath it, as (absolute path, kind, size), in one of two orders
+// Depth first visits each folder's entries before moving on to its next sibling, and breadth first visits every entry
+// at one depth before the next. Entries in the same folder are visited in order of name either way.
+struct TreeEntries<'a> {
+    fs: &'a FileSystem,
+    pending: VecDeque<(NodeId, String, usize)>, // entries still to visit, with their paths and depths, next first
+    depth_first: bool,
+}
+
+// How the lines of a folder size report are ordered (see 'NodeId::report')
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum SortBy {
+    #[default]
+    Path, // alphabetically by path, so each folder comes just before the folders within it
+    Size, // largest first, then alphabetically by path for folders of the same size
+}
+
+// How 'cd' into a folder that has not been listed is handled when applying commands
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum CdPolicy {
+    #[default]
+    Strict, // fail, as the folder does not exist
+    CreateMissing, // create the folder, as a log may 'cd' into a folder before (or without) listing it
 }
 
 // A type of file navigation command
@@ -28,16 +88,54 @@ enum ParsedCommand {
     CdIntoFolder(String), // Navigate into subfolder (by String representing the folder name)
     CdOutOfFolder, // navigate to parent
     CdToRoot, // Navigate back to root
-    Ls(Vec::<String>), // Add listed entries (in Vec) to structure
+    Ls(Vec::<LsEntry>), // Add listed entries (in Vec) to structure
+}
+
+// An entry listed by 'ls', as parsed from a line of its output
+#[derive(Clone, Debug, PartialEq)]
+enum LsEntry {
+    Dir(String), // folder, by name
+    File(String, u64), // file, by name and size
 }
 
 // Simulated computer information
-const TOTAL_SPACE : u32 = 70000000; 
-const SPACE_REQUIRED_FOR_UPDATE : u32 = 30000000; 
+const TOTAL_SPACE : u64 = 70000000; 
+const SPACE_REQUIRED_FOR_UPDATE : u64 = 30000000; 
+
+// Number of files listed by the verbose report
+const LARGEST_FILES_SHOWN : usize = 5;
+
+// Number of files, and of folders, listed as space hogs by the verbose report
+const SPACE_HOGS_SHOWN : usize = 10;
+
+// How many levels of folders below the root the verbose report lists the sizes of
+const REPORT_DEPTH : usize = 2;
+
+// Gets the answer to part 1 (or part 2, with 'part_2') for the filesystem 'fs'
+// Part 2 fails if the files don't fit on the drive, or if no directory is large enough to delete.
+fn solve(fs: &FileSystem, part_2: bool) -> Result<u64, SpaceError> {
+    let root = fs.root();
+    if part_2 {
+        // Part 2:
+        // Calculate minimum folder deletion size to free up enough space for update
+        // (if there is already enough, any folder will do)
+        let used_space = root.calculate_size(fs);
+        let free_space = TOTAL_SPACE.checked_sub(used_space).ok_or(SpaceError::OverCapacity { used_space })?;
+        let min_deletion_size = SPACE_REQUIRED_FOR_UPDATE.saturating_sub(free_space);
+
+        // Fetch size of smallest directory over minimum deletion size
+        root.smallest_directory_size_over_min(fs, min_deletion_size).ok_or(SpaceError::NoFolderLargeEnough { min_deletion_size })
+    } else {
+        // Part 1:
+        // Fetch sum of directory sizes for directories under 100000 units
+        Ok(root.sum_directory_sizes_under_max(fs, 100000))
+    }
+}
 
 // Run challenge.
 // Main entry point to day 7 challenge.
-pub fn run(part_2 : bool) -> Result<(),Box<dyn error::Error>>{
+// If 'options.dump_fs' is set, the filesystem is also written there as JSON (see 'FileSystem::to_json').
+pub fn run(part_2 : bool, options: &Options) -> Result<(),Box<dyn error::Error>>{
     
     // Extract input into string (newlines kept)
     let f = File::open("input/day7input.txt")?;
@@ -46,93 +144,254 @@ pub fn run(part_2 : bool) -> Result<(),Box<dyn error::Error>>{
     let mut input = String::new();
     buf.read_to_string(&mut input)?;
 
-    // Split input into commands along the '$' marker
-    let commands : Vec<Result<ParsedCommand, regex::Error>> = input.trim().split('$').filter(|l| l.len() > 0).map(
-        |l| {
-            ParsedCommand::from_line(l)
-    }).collect();
+    // Create file structure by performing the commands in the input, starting from its root
+    // In lenient mode, 'cd'ing into a folder that has not been listed creates it rather than failing
+    let policy = if options.lenient { CdPolicy::CreateMissing } else { CdPolicy::Strict };
+    let (mut fs, created) = FileSystem::from_commands_with(&input, policy)?;
 
-    // Create file structure root
-    let root = DirectoryNode::new();
+    // Both parts build the same filesystem, so it is only written once
+    if let (Some(path), false) = (&options.dump_fs, part_2) {
+        File::create(path)?.write_all(fs.to_json().as_bytes())?;
+    }
 
-    // Create Rc strong reference to root to perform commands on (keep original root
-    // in scope so parent references don't get dropped)
-    let mut current_node = root.rc_clone();
+    let part = if part_2 {2} else {1};
+    let size_val = solve(&fs, part_2)?;
+    println!("Result for day 7-{part} = {size_val}");
 
-    // Iterate over each command and apply it to the current node
-    for command in commands {
-        let command = command?;
-        current_node = current_node.command(command)?;
+    // The report covers the whole filesystem, so it is only printed once, alongside part 1
+    if options.verbose && !part_2 {
+        let created = (policy == CdPolicy::CreateMissing).then_some(created);
+        for line in verbose_report(&fs, created) {
+            println!("{line}");
+        }
     }
 
-    let part = if part_2 {2} else {1};
+    // In verbose mode, part 2 also deletes the folder it chose, to show that the update then fits
+    if options.verbose && part_2 {
+        println!("{}", delete_for_update(&mut fs, size_val)?);
+    }
+    Ok(())
+}
 
-    let size_val;
-    if part_2 {
+// Deletes a folder of 'size' units (the answer to part 2) from 'fs', as part 2 suggests, and describes the space left
+// The root can't be deleted, so only folders beneath it are considered.
+// eg: "Deleting /d frees 24933642 units, leaving 46552477 free for the 30000000 unit update"
+fn delete_for_update(fs: &mut FileSystem, size: u64) -> Result<String, Box<dyn error::Error>> {
+    let root = fs.root();
+    let Some((path, _)) = root.find(fs, |_, info| info.kind == EntryKind::Folder && info.depth > 0 && info.size == size).into_iter().next() else {
+        return Ok(format!("No folder of {size} units beneath / to delete"));
+    };
+    let freed = root.remove_by_path(fs, &path)?;
+    let free_space = TOTAL_SPACE.saturating_sub(root.calculate_size(fs));
+    Ok(format!("Deleting {path} frees {freed} units, leaving {free_space} free for the {SPACE_REQUIRED_FOR_UPDATE} unit update"))
+}
 
-        // Part 2:
-        // Calculate minimum folder deletion size to free up enough space for update
-        let free_space = TOTAL_SPACE - root.calculate_size();
-        let min_deletion_size = SPACE_REQUIRED_FOR_UPDATE - free_space;
+// Lines of the verbose report on 'fs': how many folders 'cd' created (if 'created' is given), the largest .css files
+// (see 'find_glob'), the largest files and folders of any kind, and a du-style report of the folders nearest the root
+fn verbose_report(fs: &FileSystem, created: Option<usize>) -> Vec<String> {
+    let root = fs.root();
+    let mut lines = Vec::new();
+    if let Some(created) = created {
+        lines.push(format!("Folders created by cd before being listed: {created}"));
+    }
 
-        // Fetch size of smallest directory over minimum deletion size
-        size_val = root.smallest_directory_size_over_min(min_deletion_size).unwrap();
-    } else  {
-        // Part 1:
-        // Fetch sum of directory sizes for directories under 100000 units
-        size_val = root.sum_directory_sizes_under_max(100000);
+    let mut css_files = root.find_glob(fs, "*.css");
+    css_files.sort_by_key(|(_, info)| std::cmp::Reverse(info.size));
+    lines.push("Largest .css files:".to_string());
+    lines.extend(css_files.iter().take(LARGEST_FILES_SHOWN).map(|(path, info)| format!("  {path} (size={})", info.size)));
+
+    lines.push(format!("Top {SPACE_HOGS_SHOWN} space hogs:"));
+    for (kind, entries) in [("files", root.largest_files(fs, SPACE_HOGS_SHOWN)), ("folders", root.largest_dirs(fs, SPACE_HOGS_SHOWN))] {
+        lines.push(format!("  {kind}:"));
+        lines.extend(entries.iter().map(|(path, size)| format!("    {path} (size={size})")));
     }
 
-    println!("Result for day 7-{part} = {size_val}");
-    Ok(())
+    lines.push(format!("Folder sizes, {REPORT_DEPTH} levels deep:"));
+    lines.extend(root.report(fs, REPORT_DEPTH, SortBy::Size, true).lines().map(|line| format!("  {line}")));
+    lines
 }
 
 
 
 
-impl DirectoryNode {
+impl FileSystem {
 
-    // Create new empty root node. This should be kept in scope to ensure no nodes are dropped.
-    fn new() -> DirectoryNode {
-        DirectoryNode(Rc::new(RefCell::new(DirectoryEntry::Folder(None, HashMap::new()))))
+    // Create new FileSystem holding just an empty root folder
+    fn new() -> FileSystem {
+        FileSystem {
+            nodes: vec![Node { parent: None, name: "/".to_string(), size: 0, listed: false, entry: DirectoryEntry::Folder(HashMap::new()) }],
+        }
     }
 
-    // Add subfile to node, accessible via key 'name' and of of name String and size 'size'
-    fn add_subfile(&self, name: String, size: u32) {
+    // Gets the root folder
+    fn root(&self) -> NodeId {
+        NodeId(0)
+    }
 
-        // Get weak reference to parent node
-        let weak_parent = Rc::downgrade( &Rc::clone(&self.0));
-        
-        // Get shared reference to current entry
-        let entry = &Rc::clone(&self.0);
-        let mut entry = entry.borrow_mut();
+    fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
 
-        // Insert subfile as child of current entry
-        if let DirectoryEntry::Folder(_,ref mut children) = *entry {
-            children.entry(name).or_insert(DirectoryNode(Rc::new(RefCell::new(DirectoryEntry::File(Some(weak_parent), size)))));
+    // Applies each of 'commands' in turn, starting from the root, with 'cd' into unlisted folders handled by 'policy'
+    // Returns how many folders were created by 'cd'ing into them, which is always 0 for CdPolicy::Strict.
+    #[cfg(test)] // the runner applies each command as it is parsed, in 'from_commands_with'
+    fn apply_commands(&mut self, commands: Vec<ParsedCommand>, policy: CdPolicy) -> Result<usize, Box<dyn error::Error>> {
+        let mut created = 0;
+        let mut node = self.root();
+        for command in commands {
+            node = self.apply_command(node, command, policy, &mut created)?;
         }
+        Ok(created)
     }
 
-    // Add subfolder to node, accessible via key 'name' and with empty children HashMap
-    fn add_subfolder(&self, name: String) {
-        // Get weak reference to parent node
-        let weak_parent = Rc::downgrade( &Rc::clone(&self.0));
-        
-        // Get shared reference to current entry
-        let entry = &Rc::clone(&self.0);
-        let mut entry = entry.borrow_mut();
+    // Applies 'command' at 'node' (see 'apply_commands'), returning the node the next command applies at
+    // Adds one to 'created' if it creates a folder by 'cd'ing into it.
+    fn apply_command(&mut self, node: NodeId, command: ParsedCommand, policy: CdPolicy, created: &mut usize) -> Result<NodeId, Box<dyn error::Error>> {
+        if let (CdPolicy::CreateMissing, ParsedCommand::CdIntoFolder(name)) = (policy, &command) {
+            if node.get_entry(self, name).err().is_some_and(|e| e.is::<DirectoryEntryNotExistError>()) {
+                node.add_subfolder(self, name.clone())?;
+                *created += 1;
+            }
+        }
+        node.command(self, command)
+    }
+
+    // Builds the FileSystem recorded by the terminal log 'input', where each command starts with '$'
+    // Fails on the first command that can't be parsed or applied, with the line it starts on.
+    // eg: FileSystem::from_commands("$ cd /\n$ ls\ndir a\n14848514 b.txt")
+    #[cfg(test)] // the runner always picks a 'cd' policy
+    fn from_commands(input: &str) -> Result<FileSystem, Box<dyn error::Error>> {
+        Ok(FileSystem::from_commands_with(input, CdPolicy::Strict)?.0)
+    }
+
+    // Builds the FileSystem recorded by the terminal log 'input' as 'from_commands' does, with 'cd' into unlisted folders
+    // handled by 'policy', alongside how many folders were created by 'cd'ing into them (see 'apply_commands')
+    fn from_commands_with(input: &str, policy: CdPolicy) -> Result<(FileSystem, usize), Box<dyn error::Error>> {
+        let mut fs = FileSystem::new();
+        let mut created = 0;
+        let mut node = fs.root();
+
+        // Split input into commands along the '$' marker, counting commands and lines to know where each starts
+        let commands = input.trim_start();
+        let mut line = 1 + input[..input.len() - commands.len()].matches('\n').count();
+        let mut index = 0;
+        for command in commands.trim_end().split('$') {
+            let command_line = line + command.len() - command.trim_start_matches('\n').len();
+            line += command.matches('\n').count();
+            if command.trim().is_empty() {
+                continue;
+            }
+            index += 1;
+
+            let in_context = |error| CommandError { index, line: command_line, error };
+            let command = ParsedCommand::from_line(command)
+                .map_err(|e| in_context(Box::new(CommandParseError { command_index: Some(index), ..e })))?;
+            node = fs.apply_command(node, command, policy, &mut created).map_err(in_context)?;
+        }
+        Ok((fs, created))
+    }
+
+    // Serializes the tree as nested JSON objects keyed by name, with keys in sorted order so the same tree always gives the same JSON
+    // eg: {"a":{"type":"dir","children":{"f.txt":{"type":"file","size":123}}},"b":{"type":"file","size":5}}
+    fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.root().write_json(self, &mut json).expect("writing to a String cannot fail");
+        json
+    }
 
-        // Insert subfolder as child of current entry
-        if let DirectoryEntry::Folder(_, ref mut children) = *entry {
-            children.entry(name).or_insert(DirectoryNode(Rc::new(RefCell::new(DirectoryEntry::Folder(Some(weak_parent), HashMap::new())))));
+    // Builds the FileSystem serialized by 'to_json' from 'json'
+    #[cfg(test)] // only tests read JSON back in, to check '--dump-fs' round-trips
+    fn from_json(json: &str) -> Result<FileSystem, JsonError> {
+        let entries = JsonParser { json, position: 0 }.parse()?;
+        let mut fs = FileSystem::new();
+        fs.root().add_json_entries(&mut fs, entries)?;
+        Ok(fs)
+    }
+
+    // Adds 'entry' as a child of folder 'parent' kept under 'name', unless 'parent' already has a child by that name
+    // Nothing is added to a file. A file's size is added to the total of every folder above it, failing with a
+    // SizeOutOfRange (and adding nothing) if any of those totals would overflow.
+    fn add_child(&mut self, parent: NodeId, name: String, entry: DirectoryEntry) -> Result<(), SpaceError> {
+        let size = match entry {
+            DirectoryEntry::File(size) => size,
+            DirectoryEntry::Folder(_) => 0,
+        };
+        let DirectoryEntry::Folder(ref children) = self.nodes[parent.0].entry else {
+            return Ok(());
+        };
+        if children.contains_key(&name) {
+            return Ok(());
+        }
+
+        self.update_ancestors(parent, |total| total.checked_add(size))?;
+        let id = NodeId(self.nodes.len());
+        if let DirectoryEntry::Folder(ref mut children) = self.nodes[parent.0].entry {
+            children.insert(name.clone(), id);
         }
+        self.nodes.push(Node { parent: Some(parent), name, size, listed: false, entry });
+        Ok(())
     }
 
-    // Calculates node total size. 
+    // Detaches the child kept under 'name' in folder 'parent' from the tree, returning its size
+    // Its size is taken from the total of every folder above it, and it becomes the root of its own (unreachable) tree.
+    // Fails with a SizeOutOfRange, removing nothing, if a total is smaller than the child's size.
+    fn remove_child(&mut self, parent: NodeId, name: &str) -> Result<u64, Box<dyn error::Error>> {
+        let DirectoryEntry::Folder(ref mut children) = self.nodes[parent.0].entry else {
+            return Err(Box::new(DirectoryEntryTypeError { path: parent.path_of(self), expected: EntryKind::Folder })); // a file has no entries to remove
+        };
+        let Some(child) = children.remove(name) else {
+            return Err(Box::new(DirectoryEntryNotExistError { name: name.to_string(), folder: parent.path_of(self) }));
+        };
+
+        let size = self.nodes[child.0].size;
+        if let Err(e) = self.update_ancestors(parent, |total| total.checked_sub(size)) {
+            // Put the child back, so a failed removal leaves the tree as it was
+            if let DirectoryEntry::Folder(ref mut children) = self.nodes[parent.0].entry {
+                children.insert(name.to_string(), child);
+            }
+            return Err(Box::new(e));
+        }
+        self.nodes[child.0].parent = None;
+        Ok(size)
+    }
+
+    // Replaces the total size of 'folder' and every folder above it with 'update' of that total
+    // Every total is updated or none are: if 'update' gives None for any of them, this fails with a SizeOutOfRange for
+    // the first such folder, and changes nothing.
+    fn update_ancestors(&mut self, folder: NodeId, update: impl Fn(u64) -> Option<u64>) -> Result<(), SpaceError> {
+        let mut totals = Vec::new();
+        let mut ancestor = Some(folder);
+        while let Some(folder) = ancestor {
+            let node = self.node(folder);
+            let total = update(node.size).ok_or_else(|| SpaceError::SizeOutOfRange { path: folder.path_of(self) })?;
+            totals.push((folder, total));
+            ancestor = node.parent;
+        }
+        for (folder, total) in totals {
+            self.nodes[folder.0].size = total;
+        }
+        Ok(())
+    }
+}
+
+impl NodeId {
+
+    // Add subfile to node, accessible via key 'name' and of of name String and size 'size'
+    // Fails if the file would take a folder's total size out of range (see 'FileSystem::add_child').
+    fn add_subfile(self, fs: &mut FileSystem, name: String, size: u64) -> Result<(), SpaceError> {
+        fs.add_child(self, name, DirectoryEntry::File(size))
+    }
+
+    // Add subfolder to node, accessible via key 'name' and with empty children HashMap
+    fn add_subfolder(self, fs: &mut FileSystem, name: String) -> Result<(), SpaceError> {
+        fs.add_child(self, name, DirectoryEntry::Folder(HashMap::new()))
+    }
+
+    // Gets node total size. 
     // If a file, returns file size, and if a folder, returns all file sizes within folder and subfolderes recursively.
-    fn calculate_size(&self) -> u32 {
-        let (_,size) = self.get_all_directory_sizes();
-        size
+    // Totals are kept up to date as files are added, so this does not walk the tree.
+    fn calculate_size(self, fs: &FileSystem) -> u64 {
+        fs.node(self).size
     }
 
 
@@ -140,159 +399,396 @@ impl DirectoryNode {
     // - a Vector of of all directory sizes
     // - the size of this topmost directory or file
     // (This does not include file sizes as elements, only directories, but directory sizes are recursive sum of all files within)
-    fn get_all_directory_sizes(&self) -> (Vec<u32>, u32) {
-
-        // Get shared reference to current entry
-        let entry = &Rc::clone(&self.0);
-        let mut entry = entry.borrow_mut();
-
-        match *entry {
-            // If a file, return base case of current file size
-            DirectoryEntry::File(_,i) => (Vec::new(),i),
-
-            // If folder, get a Vec of all subdirectory sizes contained within
-            DirectoryEntry::Folder(_,ref mut subfolders) => {
-                let (mut subfolders_vec, folder_size) = subfolders.iter_mut().map(
-                        |(_,b)| 
-                        b.get_all_directory_sizes()).fold(
-                            (Vec::<u32>::new(),0), 
-                        |(acc_vec, acc_size), (new_vec, folder_size)| ([acc_vec, new_vec].concat(),acc_size + folder_size));
-                
-                // Append current size to list, and return
-                subfolders_vec.push(folder_size);
-                (subfolders_vec, folder_size)
-
+    // Each folder within is visited once, reading its kept total rather than adding up what is inside it.
+    fn get_all_directory_sizes(self, fs: &FileSystem) -> (Vec<u64>, u64) {
+        let mut sizes = Vec::new();
+        let mut folders = vec![self];
+        while let Some(folder) = folders.pop() {
+            if let DirectoryEntry::Folder(ref children) = fs.node(folder).entry {
+                sizes.push(folder.calculate_size(fs));
+                folders.extend(children.values());
             }
         }
+        (sizes, self.calculate_size(fs))
     }
 
     // Gets the smallest directory or subdirectory within that is at least 'minimum_size'
-    fn smallest_directory_size_over_min(&self, minimum_size: u32) -> Option<u32> {
-        let (size_list, _) = self.get_all_directory_sizes();
+    fn smallest_directory_size_over_min(self, fs: &FileSystem, minimum_size: u64) -> Option<u64> {
+        let (size_list, _) = self.get_all_directory_sizes(fs);
         size_list.iter().filter(|x| **x > minimum_size).copied().min()
     }
 
     // Gets sum of all directory sizes with size under 'maximum_size' 
     // (directories and their subdirectories are counted, meaning files can be counted many times)
-    fn sum_directory_sizes_under_max(&self, maximum_size : u32) -> u32 {
-        let (size_list, _) = self.get_all_directory_sizes();
-        size_list.iter().filter(|x| **x < maximum_size).copied().sum()
+    fn sum_directory_sizes_under_max(self, fs: &FileSystem, maximum_size : u64) -> u64 {
+        self.iter_dfs(fs)
+            .filter(|&(_, kind, size)| kind == EntryKind::Folder && size < maximum_size)
+            .map(|(_, _, size)| size)
+            .sum()
     }
 
-    // Creates a new DirectoryNode instance with shared ownership of member DirectoryEntry
-    fn rc_clone(&self) -> DirectoryNode {
-        DirectoryNode(Rc::clone(&self.0))
+    // Gets whether this node is a folder or a file
+    fn kind(self, fs: &FileSystem) -> EntryKind {
+        match fs.node(self).entry {
+            DirectoryEntry::Folder(_) => EntryKind::Folder,
+            DirectoryEntry::File(_) => EntryKind::File,
+        }
     }
 
-    // Retrieves new DirectoryNode of child folder by key 'name'
-    // New DirectoryNode has shared ownership of internal DirectoryEntry
-    fn get_subfolder(&self, name : String) -> Result<DirectoryNode,Box<dyn error::Error>> {
+    // Finds this node and every entry beneath it for which 'predicate' is true, given the entry's name and EntryInfo
+    // Returns the absolute path of each match alongside its EntryInfo, in depth first order (see 'iter_dfs').
+    // eg: node.find(&fs, |_, info| info.kind == EntryKind::File && info.size > 100000)
+    fn find(self, fs: &FileSystem, predicate: impl Fn(&str, &EntryInfo) -> bool) -> Vec<(String, EntryInfo)> {
+        let mut found = Vec::new();
+        let mut entries = TreeEntries::new(fs, self, true);
+        while let Some((node, path, depth)) = entries.next_entry() {
+            let info = EntryInfo { kind: node.kind(fs), size: node.calculate_size(fs), depth };
+            if predicate(node.name(fs), &info) {
+                found.push((path, info));
+            }
+        }
+        found
+    }
 
-        // Get shared reference to current entry
-        let entry = &Rc::clone(&self.0);
-        let mut entry = entry.borrow_mut();
+    // Gets the paths and sizes of the 'n' largest files in the tree beneath this node, largest first
+    // Files of the same size are ordered by path, and all of them are returned if there are fewer than 'n'.
+    fn largest_files(self, fs: &FileSystem, n: usize) -> Vec<(String, u64)> {
+        self.largest_entries(fs, EntryKind::File, n)
+    }
 
+    // Gets the paths and sizes of the 'n' largest folders in the tree beneath this node (including itself), as 'largest_files' does
+    fn largest_dirs(self, fs: &FileSystem, n: usize) -> Vec<(String, u64)> {
+        self.largest_entries(fs, EntryKind::Folder, n)
+    }
+
+    // Gets the paths and sizes of the 'n' largest entries of kind 'kind' in the tree beneath this node (see 'largest_files')
+    fn largest_entries(self, fs: &FileSystem, kind: EntryKind, n: usize) -> Vec<(String, u64)> {
+        let mut entries: Vec<(String, u64)> = self.iter_dfs(fs)
+            .filter(|&(_, entry_kind, _)| entry_kind == kind)
+            .map(|(path, _, size)| (path, size))
+            .collect();
+        entries.sort_by(|(path_a, size_a), (path_b, size_b)| size_b.cmp(size_a).then_with(|| path_a.cmp(path_b)));
+        entries.truncate(n);
+        entries
+    }
+
+    // Lists this folder and the folders beneath it, up to 'max_depth' levels down, with their total sizes, as 'du --max-depth' does
+    // Each line is the size then the absolute path, separated by a tab and ordered by 'sort'.
+    // With 'human_readable', sizes of 1024 or more are given in K, M, G... as 'du -h' does (see 'human_readable_size').
+    // eg: node.report(&fs, 1, SortBy::Path, false) gives "48381165\t/\n94853\t/a\n24933642\t/d"
+    fn report(self, fs: &FileSystem, max_depth: usize, sort: SortBy, human_readable: bool) -> String {
+        let mut folders = self.find(fs, |_, info| info.kind == EntryKind::Folder && info.depth <= max_depth);
+        match sort {
+            SortBy::Path => folders.sort_by(|(path_a, _), (path_b, _)| path_a.cmp(path_b)),
+            SortBy::Size => folders.sort_by(|(path_a, a), (path_b, b)| b.size.cmp(&a.size).then_with(|| path_a.cmp(path_b))),
+        }
+
+        folders.iter()
+            .map(|(path, info)| {
+                let size = if human_readable { human_readable_size(info.size) } else { info.size.to_string() };
+                format!("{size}\t{path}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Finds this node and every entry beneath it whose name matches 'pattern' (see 'matches_glob'), as 'find' does
+    // eg: node.find_glob(&fs, "*.css")
+    fn find_glob(self, fs: &FileSystem, pattern: &str) -> Vec<(String, EntryInfo)> {
+        self.find(fs, |name, _| matches_glob(pattern, name))
+    }
+
+    // Iterates over this node and every entry beneath it, depth first (see 'TreeEntries')
+    // eg: for the puzzle's example, "/", "/a", "/a/e", "/a/e/i", "/a/f", ...
+    fn iter_dfs(self, fs: &FileSystem) -> TreeEntries<'_> {
+        TreeEntries::new(fs, self, true)
+    }
+
+    // Iterates over this node and every entry beneath it, breadth first (see 'TreeEntries')
+    // eg: for the puzzle's example, "/", "/a", "/b.txt", "/c.dat", "/d", "/a/e", ...
+    #[cfg(test)] // only tests walk the tree breadth first
+    fn iter_bfs(self, fs: &FileSystem) -> TreeEntries<'_> {
+        TreeEntries::new(fs, self, false)
+    }
+
+    // Retrieves the entry of this folder kept under 'name', whether it is a file or a folder
+    // Fails with DirectoryEntryNotExistError if there is no such entry, and DirectoryEntryTypeError if this is a file.
+    fn get_entry(self, fs: &FileSystem, name: &str) -> Result<EntryRef, Box<dyn error::Error>> {
 
         // Confirms this is a folder with subfiles/subfolders and gets reference to 'children' hashmap
-        if let DirectoryEntry::Folder(_, ref mut children) = *entry {
+        let DirectoryEntry::Folder(ref children) = fs.node(self).entry else {
+            return Err(Box::new(DirectoryEntryTypeError { path: self.path_of(fs), expected: EntryKind::Folder })); // cannot search entries of a file
+        };
 
-            // Searches 'children' for child by name 'name'
-            if let Entry::Occupied(subfolder) = children.entry(name) {
-                Ok(subfolder.get().rc_clone())
-            } else {
-                Err(Box::new(DirectoryEntryNotExistError)) // could not find child by that name
-            }
-        } else {
-            Err(Box::new(DirectoryEntryTypeError)) // cannot search for subfolders of a file
+        // Searches 'children' for child by name 'name'
+        let Some(&child) = children.get(name) else {
+            return Err(Box::new(DirectoryEntryNotExistError { name: name.to_string(), folder: self.path_of(fs) })); // could not find child by that name
+        };
+        match fs.node(child).entry {
+            DirectoryEntry::File(size) => Ok(EntryRef::File(child, size)),
+            DirectoryEntry::Folder(_) => Ok(EntryRef::Folder(child)),
         }
     }
 
-    // Retrieves new DirectoryNode of child folder by key 'name'
-    // New DirectoryNode has shared ownership of internal DirectoryEntry
-    fn get_parent(&self) -> Option<DirectoryNode> {
+    // Retrieves the subfolder of this folder kept under 'name' (see 'get_entry')
+    // Fails with DirectoryEntryTypeError if the entry is a file.
+    fn get_subfolder(self, fs: &FileSystem, name: &str) -> Result<NodeId, Box<dyn error::Error>> {
+        match self.get_entry(fs, name)? {
+            EntryRef::Folder(folder) => Ok(folder),
+            EntryRef::File(file, _) => Err(Box::new(DirectoryEntryTypeError { path: file.path_of(fs), expected: EntryKind::Folder })),
+        }
+    }
 
-        // Get shared reference to current entry
-        let entry = &Rc::clone(&self.0);
-        let mut entry = entry.borrow_mut();
+    // Retrieves the size of the file in this folder kept under 'name' (see 'get_entry')
+    // Fails with DirectoryEntryTypeError if the entry is a folder.
+    #[cfg(test)] // only tests look up files, which 'cd' never does
+    fn get_file_size(self, fs: &FileSystem, name: &str) -> Result<u64, Box<dyn error::Error>> {
+        match self.get_entry(fs, name)? {
+            EntryRef::File(_, size) => Ok(size),
+            EntryRef::Folder(folder) => Err(Box::new(DirectoryEntryTypeError { path: folder.path_of(fs), expected: EntryKind::File })),
+        }
+    }
 
-        // Retrieves reference to parent from current entry
-        let (DirectoryEntry::Folder(ref mut parent, _) | DirectoryEntry::File(ref mut parent, _)) =  *entry;
-        
-        // If parent exists and has not been dropped, get parent as node
-        if let Some(p) = parent {
-            if let Some(p) = p.upgrade() {
-                return Some(DirectoryNode(p))
-            }
+    // Retrieves parent folder of this node, or None for the root
+    fn get_parent(self, fs: &FileSystem) -> Option<NodeId> {
+        fs.node(self).parent
+    }
+
+    // Removes the file or folder kept under 'name' in this folder, and everything within it, returning the space freed
+    // The sizes of this folder and every folder above it shrink to match.
+    fn remove(self, fs: &mut FileSystem, name: &str) -> Result<u64, Box<dyn error::Error>> {
+        fs.remove_child(self, name)
+    }
+
+    // Removes the file or folder at 'path' (see 'get_node_by_path'), returning the space freed
+    // eg: node.remove_by_path(&mut fs, "/a/e")
+    fn remove_by_path(self, fs: &mut FileSystem, path: &str) -> Result<u64, Box<dyn error::Error>> {
+        let node = self.get_node_by_path(fs, path)?;
+        let name = node.name(fs).to_string();
+        match node.get_parent(fs) {
+            Some(parent) => parent.remove(fs, &name),
+            None => Err(Box::new(RootRemovalError)),
         }
-        None
     }
 
-    // Retrieves new DirectoryNode of child folder by key 'name'
-    // New DirectoryNode has shared ownership of internal DirectoryEntry
-    fn get_root(&self) -> DirectoryNode {
-        let mut root : DirectoryNode = self.rc_clone();
-        while let Some(r) = root.get_parent() {
+    // Retrieves root folder of the tree this node is in
+    fn get_root(self, fs: &FileSystem) -> NodeId {
+        let mut root = self;
+        while let Some(r) = root.get_parent(fs) {
             root = r;
         }
         root
     }
 
-    // Creates a folder or file within Node based on line 'line'
-    // Line is of one of two formats:
-    // "dir name" where name is the name, representing a folder/directory
-    // "filesize name", where filesize is the size and name is the name, representing a file.
-    fn parse_line_to_directoryentry(& self, line: &str) -> Result<(), regex::Error> {
+    // Retrieves node at 'path', which is separated by '/'
+    // An absolute path (starting with '/') starts at the root, and a relative path at this node.
+    // '.' stays at the current folder, and '..' goes to its parent, staying at the root if there is none.
+    // eg: node.get_node_by_path(&fs, "/a/e/i") or node.get_node_by_path(&fs, "../d")
+    fn get_node_by_path(self, fs: &FileSystem, path: &str) -> Result<NodeId, Box<dyn error::Error>> {
+        let mut node = if path.starts_with('/') { self.get_root(fs) } else { self };
+        for segment in path.split('/') {
+            node = match segment {
+                "" | "." => node,
+                ".." => node.get_parent(fs).unwrap_or(node),
+                name => node.get_entry(fs, name)?.id(),
+            };
+        }
+        Ok(node)
+    }
 
-        let line = line.trim();
+    // Gets the absolute path of this node, by walking up through its parents to the root
+    // The root's path is "/", and others are the names from the root down, such as "/a/e/i".
+    fn path_of(self, fs: &FileSystem) -> String {
+        let mut names = Vec::new();
+        let mut node = self;
+        while let Some(parent) = node.get_parent(fs) {
+            names.push(node.name(fs));
+            node = parent;
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
 
-        // Create directory from:
-        // "dir name" (ie: dir filedir)
-        lazy_static! {
-            static ref REGEX_DIRECTORYENTRY_FOLDER: Regex = Regex::new(r"^dir\s([\w\.]+)$").unwrap();
+    // Gets the name of this node, which is "/" for the root
+    fn name(self, fs: &FileSystem) -> &str {
+        &fs.node(self).name
+    }
+
+    // Renders the tree beneath this node as the puzzle's example does, one entry per line with two spaces of indent per level
+    // Entries are ordered by name, and with 'with_sizes' each folder's line also has its total size.
+    // eg: "- / (dir)\n  - a (dir)\n    - f (file, size=29116)"
+    #[cfg(test)] // only tests print folder sizes, which Display leaves out
+    fn render_tree(self, fs: &FileSystem, with_sizes: bool) -> String {
+        let mut tree = String::new();
+        self.write_tree(fs, &mut tree, 0, with_sizes).expect("writing to a String cannot fail");
+        tree
+    }
+
+    // Writes the line for this node (at depth 'depth'), then each of its entries' (see 'render_tree')
+    fn write_tree(self, fs: &FileSystem, f: &mut impl fmt::Write, depth: usize, with_sizes: bool) -> fmt::Result {
+        if depth > 0 {
+            writeln!(f)?;
         }
-        if let Some(matches) = REGEX_DIRECTORYENTRY_FOLDER.captures(line)  {
-            if let Some(name) = matches.get(1) {
-                self.add_subfolder(name.as_str().to_string());
-                return Ok(());
-            } 
+        let indent = "  ".repeat(depth);
+        let name = self.name(fs);
+
+        match fs.node(self).entry {
+            DirectoryEntry::File(size) => write!(f, "{indent}- {name} (file, size={size})"),
+            DirectoryEntry::Folder(ref children) => {
+                if with_sizes {
+                    write!(f, "{indent}- {name} (dir, size={})", self.calculate_size(fs))?;
+                } else {
+                    write!(f, "{indent}- {name} (dir)")?;
+                }
+
+                // Children are kept in a HashMap, so are sorted by name to always render the same way
+                let mut names: Vec<&String> = children.keys().collect();
+                names.sort();
+                for child in names {
+                    children[child].write_tree(fs, f, depth + 1, with_sizes)?;
+                }
+                Ok(())
+            }
         }
-        // Create file from:
-        // "filesize name" (ie: 231232 filetxt)
-        lazy_static! {
-            static ref REGEX_DIRECTORYENTRY_FILE: Regex = Regex::new(r"^(\d+)\s([\w\.]+)$").unwrap();
+    }
+
+    // Writes this folder's entries as a JSON object, keyed by name in sorted order (see 'FileSystem::to_json')
+    fn write_json(self, fs: &FileSystem, f: &mut impl fmt::Write) -> fmt::Result {
+        let DirectoryEntry::Folder(ref children) = fs.node(self).entry else {
+            return write!(f, "{{}}");
+        };
+        let mut names: Vec<&String> = children.keys().collect();
+        names.sort();
+
+        write!(f, "{{")?;
+        for (i, name) in names.into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write_json_string(f, name)?;
+            match fs.node(children[name]).entry {
+                DirectoryEntry::File(size) => write!(f, r#":{{"type":"file","size":{size}}}"#)?,
+                DirectoryEntry::Folder(_) => {
+                    write!(f, r#":{{"type":"dir","children":"#)?;
+                    children[name].write_json(fs, f)?;
+                    write!(f, "}}")?;
+                }
+            }
         }
-        if let Some(matches) = REGEX_DIRECTORYENTRY_FILE.captures(line)  {
-            if let (Some(size), Some(name) )= (matches.get(1), matches.get(2)) {
-                self.add_subfile(name.as_str().to_string(), size.as_str().parse().unwrap()); // unwrap here as it must be digits
-                return Ok(());
-            } 
+        write!(f, "}}")
+    }
+
+    // Adds each entry of the JSON object 'entries' to this folder, and the entries of any folders among them in turn
+    // Entries are in the format written by 'write_json', and each name may only appear once in a folder.
+    #[cfg(test)]
+    fn add_json_entries(self, fs: &mut FileSystem, entries: JsonValue) -> Result<(), JsonError> {
+        let bad_entry = |fs: &FileSystem, name: &str, reason| JsonError::BadEntry { path: path_join(&self.path_of(fs), name), reason };
+        let JsonValue::Object(entries) = entries else {
+            return Err(JsonError::BadEntry { path: self.path_of(fs), reason: "its children should be an object of entries" });
+        };
+
+        for (name, entry) in entries {
+            if name.is_empty() || name.contains('/') {
+                return Err(bad_entry(fs, &name, "names can't be empty or contain '/'"));
+            }
+            if self.get_entry(fs, &name).is_ok() {
+                return Err(bad_entry(fs, &name, "it appears twice in the same folder"));
+            }
+
+            // Entries are either {"type":"dir","children":{...}} or {"type":"file","size":123}, in any order
+            let fields = match entry {
+                JsonValue::Object(fields) => fields,
+                _ => Vec::new(),
+            };
+            let mut fields: HashMap<String, JsonValue> = fields.into_iter().collect();
+            let (kind, children, size) = (fields.remove("type"), fields.remove("children"), fields.remove("size"));
+            match (kind, children, size) {
+                (Some(JsonValue::String(kind)), Some(children), None) if kind == "dir" && fields.is_empty() => {
+                    self.add_subfolder(fs, name.clone()).map_err(|_| bad_entry(fs, &name, "its size is out of range"))?;
+                    let folder = self.get_subfolder(fs, &name).expect("folder was just added");
+                    folder.add_json_entries(fs, children)?;
+                }
+                (Some(JsonValue::String(kind)), None, Some(JsonValue::Number(size))) if kind == "file" && fields.is_empty() => {
+                    self.add_subfile(fs, name.clone(), size).map_err(|_| bad_entry(fs, &name, "its size is out of range"))?;
+                }
+                _ => return Err(bad_entry(fs, &name, r#"entries should be {"type":"dir","children":{...}} or {"type":"file","size":<size>}"#)),
+            }
         }
+        Ok(())
+    }
 
-        // Could not match command to file format or folder format
-        Err(regex::Error::Syntax(format!("could not match DirectoryEntry to any regex syntax: {}",line)))
-        
+    // Gets whether 'ls' has been run in this folder
+    #[cfg(test)] // only tests check which folders a log has listed
+    fn is_listed(self, fs: &FileSystem) -> bool {
+        fs.node(self).listed
+    }
+
+    // Checks that listing an entry 'name' in this folder, of file size 'size' (or None for a folder), agrees with what
+    // is already known about it. An entry listed again the same way (or one not known yet) is fine, but one listed as a file
+    // of a different size, or as the other kind of entry, is a ConflictingEntryError.
+    fn check_listing(self, fs: &FileSystem, name: &str, size: Option<u64>) -> Result<(), ConflictingEntryError> {
+        let DirectoryEntry::Folder(ref children) = fs.node(self).entry else {
+            return Ok(()); // nothing is added to a file
+        };
+        let Some(&existing) = children.get(name) else {
+            return Ok(());
+        };
+        let old_size = match fs.node(existing).entry {
+            DirectoryEntry::File(size) => Some(size),
+            DirectoryEntry::Folder(_) => None,
+        };
+        if old_size == size {
+            Ok(())
+        } else {
+            Err(ConflictingEntryError { path: existing.path_of(fs), old_size, new_size: size })
+        }
+    }
+
+    // Creates a folder or file within Node based on line 'line' (see 'LsEntry::from_line')
+    #[cfg(test)] // only tests add entries a line at a time, rather than a whole 'ls' at once
+    fn parse_line_to_directoryentry(self, fs: &mut FileSystem, line: &str) -> Result<(), Box<dyn error::Error>> {
+        self.add_ls_entry(fs, LsEntry::from_line(line)?)?;
+        Ok(())
+    }
+
+    // Creates the folder or file 'entry' within Node
+    // An entry that already exists changes nothing, unless it conflicts (see 'check_listing'), and a file whose size
+    // takes a folder's total out of range is a SpaceError.
+    fn add_ls_entry(self, fs: &mut FileSystem, entry: LsEntry) -> Result<(), Box<dyn error::Error>> {
+        match entry {
+            LsEntry::Dir(name) => {
+                self.check_listing(fs, &name, None)?;
+                self.add_subfolder(fs, name)?;
+            }
+            LsEntry::File(name, size) => {
+                self.check_listing(fs, &name, Some(size))?;
+                self.add_subfile(fs, name, size)?;
+            }
+        }
+        Ok(())
     }
 
     // Run a ParsedCommand on the current node
-    // Returns the new DirectoryNode (or current one if applicable) or an Error
-    // let node = node.command(command); 
-    fn command(&self, command : ParsedCommand) -> Result<DirectoryNode,Box<dyn error::Error>> {
-        let node = self.rc_clone();
+    // Returns the new node (or current one if applicable) or an Error
+    // let node = node.command(&mut fs, command); 
+    fn command(self, fs: &mut FileSystem, command : ParsedCommand) -> Result<NodeId,Box<dyn error::Error>> {
+        let node = self;
         let node = match command {
             // Return subfolder
-            ParsedCommand::CdIntoFolder(folder_name) => node.get_subfolder(folder_name)?,
+            ParsedCommand::CdIntoFolder(folder_name) => node.get_subfolder(fs, &folder_name)?,
 
             // Return parent folder
-            ParsedCommand::CdOutOfFolder => if let Some(p) = node.get_parent() {p} else {node} ,
+            ParsedCommand::CdOutOfFolder => if let Some(p) = node.get_parent(fs) {p} else {node} ,
 
             // Return root folder
-            ParsedCommand::CdToRoot => node.get_root(),
+            ParsedCommand::CdToRoot => node.get_root(fs),
 
             // Return same folder, but add directoryentries based on associated Vector
-            ParsedCommand::Ls(files) => {
-                for line in files {
-                    node.parse_line_to_directoryentry(&line.trim())?;        
+            ParsedCommand::Ls(entries) => {
+                for entry in entries {
+                    node.add_ls_entry(fs, entry)?;
                 }
+                fs.nodes[node.0].listed = true;
                 node
             }
         };
@@ -303,6 +799,86 @@ impl DirectoryNode {
 }
 
 
+impl TreeEntries<'_> {
+    fn new(fs: &FileSystem, start: NodeId, depth_first: bool) -> TreeEntries<'_> {
+        TreeEntries { fs, pending: VecDeque::from([(start, start.path_of(fs), 0)]), depth_first }
+    }
+
+    // Gets the next entry to visit, with its path and depth below the node iterated from
+    fn next_entry(&mut self) -> Option<(NodeId, String, usize)> {
+        let (node, path, depth) = self.pending.pop_front()?;
+        if let DirectoryEntry::Folder(ref children) = self.fs.node(node).entry {
+            let mut names: Vec<&String> = children.keys().collect();
+            names.sort();
+            let separator = if path == "/" { "" } else { "/" };
+            let children = names.into_iter().map(|name| (children[name], format!("{path}{separator}{name}"), depth + 1));
+
+            // Depth first visits the children next, and breadth first after everything already pending
+            if self.depth_first {
+                for child in children.rev() {
+                    self.pending.push_front(child);
+                }
+            } else {
+                self.pending.extend(children);
+            }
+        }
+        Some((node, path, depth))
+    }
+}
+
+impl EntryRef {
+
+    // Gets the node of the file or folder
+    fn id(self) -> NodeId {
+        match self {
+            EntryRef::Folder(id) | EntryRef::File(id, _) => id,
+        }
+    }
+}
+
+impl Iterator for TreeEntries<'_> {
+    type Item = (String, EntryKind, u64);
+
+    fn next(&mut self) -> Option<(String, EntryKind, u64)> {
+        let (node, path, _) = self.next_entry()?;
+        Some((path, node.kind(self.fs), node.calculate_size(self.fs)))
+    }
+}
+
+// Whether 'name' matches 'pattern', where '*' matches any run of characters (including none) and '?' any one character
+// eg: matches_glob("*.css", "fsjwz.css") and matches_glob("d?.*", "d1.log")
+fn matches_glob(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None; // position of the last '*' in 'pattern', and where in 'name' it currently stops matching
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            },
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            },
+            // On a mismatch, let the last '*' take one more character and try again from there
+            _ => match backtrack {
+                Some((star, stop)) => {
+                    backtrack = Some((star, stop + 1));
+                    p = star + 1;
+                    n = stop + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+
+    // Any pattern left over must be '*'s, which can match nothing
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 impl ParsedCommand {
 
     // Convert a string slice input to a Parsed Command
@@ -311,64 +887,381 @@ impl ParsedCommand {
     // cd ..
     // cd somename
     // ls <- (and then several directory entry strings separated by newlines)
-    fn from_line(l : &str) -> Result<ParsedCommand, regex::Error> {
+    // Folder names are taken verbatim from the rest of the line, so they may hold spaces, dashes and the like.
+    // The listing of an 'ls' is parsed here too (see 'LsEntry::from_line'), so a bad line fails the command it belongs to.
+    fn from_line(l : &str) -> Result<ParsedCommand, CommandParseError> {
         let l = l.trim();
-        // cd /
-        lazy_static! {
-            static ref REGEX_COMMAND_CDROOT: Regex = Regex::new(r"^cd /").unwrap();
-        }
-        if REGEX_COMMAND_CDROOT.is_match(l) {
-            return Ok(ParsedCommand::CdToRoot);
-        }
+        let (first_line, listing) = l.split_once('\n').unwrap_or((l, ""));
+        let (command, argument) = first_line.split_once(char::is_whitespace).unwrap_or((first_line, ""));
+
+        match (command, argument.trim()) {
+            // cd /
+            ("cd", "/") => Ok(ParsedCommand::CdToRoot),
+
+            // cd ..
+            ("cd", "..") => Ok(ParsedCommand::CdOutOfFolder),
+
+            // cd into folder:
+            // cd foldername
+            ("cd", name) if !name.is_empty() => Ok(ParsedCommand::CdIntoFolder(name.to_string())),
+
+            // ls
+            // found file name
+            // found file name
+            // found file name
+            ("ls", _) => {
+                let entries = listing.lines().filter(|s| !s.trim().is_empty()).map(LsEntry::from_line);
+                Ok(ParsedCommand::Ls(entries.collect::<Result<_, _>>()?))
+            }
 
-        // cd ..
-        lazy_static! {
-            static ref REGEX_COMMAND_CDPARENT: Regex = Regex::new(r"^cd \.\.").unwrap();
-        }
-        if REGEX_COMMAND_CDPARENT.is_match(l) {
-            return Ok(ParsedCommand::CdOutOfFolder);
+            _ => Err(CommandParseError { command_index: None, text: first_line.to_string(), reason: "expected \"cd <name>\" or \"ls\"" }),
         }
+    }
+}
+
+impl LsEntry {
+
+    // Convert a line of 'ls' output to an LsEntry
+    // Lines are of one of two formats:
+    // "dir name" where name is the name, representing a folder/directory
+    // "filesize name", where filesize is the size and name is the name, representing a file.
+    // The first word is "dir" or a size, and everything after it is the name, which may hold spaces of its own.
+    fn from_line(line: &str) -> Result<LsEntry, CommandParseError> {
+        let line = line.trim();
+        let format_error = |reason| CommandParseError { command_index: None, text: line.to_string(), reason };
+        let (kind, name) = line.split_once(char::is_whitespace).ok_or_else(|| format_error("expected \"dir <name>\" or \"<size> <name>\""))?;
+        let name = name.trim().to_string();
 
-        // cd into folder:
-        // cd foldername
-        lazy_static! {
-            static ref REGEX_COMMAND_CDINTO: Regex = Regex::new(r"^cd\s(\w+)").unwrap();
+        // Create directory from:
+        // "dir name" (ie: dir filedir)
+        if kind == "dir" {
+            return Ok(LsEntry::Dir(name));
         }
-        if REGEX_COMMAND_CDINTO.is_match(l) {
-            return Ok(ParsedCommand::CdIntoFolder(l[3..].trim().to_string()));
+
+        // Create file from:
+        // "filesize name" (ie: 231232 filetxt)
+        let size = kind.parse().map_err(|_| format_error("expected \"dir\" or a size in whole units before the name"))?;
+        Ok(LsEntry::File(name, size))
+    }
+}
+
+
+
+// Renders the whole tree from the root, without folder sizes (see 'render_tree')
+impl fmt::Display for FileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.root().write_tree(self, f, 0, false)
+    }
+}
+
+// Error for an operation on 'path' that needs it to be of kind 'expected', such as looking up an entry in a file
+#[derive(Clone, Debug, PartialEq)]
+struct DirectoryEntryTypeError {
+    path: String,
+    expected: EntryKind,
+}
+impl error::Error for DirectoryEntryTypeError {}
+impl fmt::Display for DirectoryEntryTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self.expected {
+            EntryKind::Folder => write!(f, "expected '{}' to be a folder, but it is a file", self.path),
+            EntryKind::File => write!(f, "expected '{}' to be a file, but it is a folder", self.path),
         }
-        // ls
-        // found file name
-        // found file name
-        // found file name
-        lazy_static! {
-            static ref REGEX_COMMAND_LS: Regex = Regex::new(r"^ls.*").unwrap();
+    }
+}
+
+// Error for JSON that doesn't hold a FileSystem (see 'FileSystem::from_json')
+#[cfg(test)]
+#[derive(Debug, PartialEq)]
+enum JsonError {
+    Syntax { position: usize, expected: &'static str }, // not valid JSON (or JSON beyond what the format uses), at byte 'position'
+    BadEntry { path: String, reason: &'static str }, // valid JSON, but not a valid entry at 'path'
+}
+#[cfg(test)]
+impl error::Error for JsonError {}
+#[cfg(test)]
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            JsonError::Syntax { position, expected } => write!(f, "invalid JSON at byte {position}: expected {expected}"),
+            JsonError::BadEntry { path, reason } => write!(f, "invalid entry '{path}': {reason}"),
         }
-        if REGEX_COMMAND_LS.is_match(l) {
-            return Ok(ParsedCommand::Ls(l[3..].trim().split('\n').map(|s| s.trim().to_string()).collect()));
+    }
+}
+
+// Error for a drive that can't make room for the update, or whose sizes can't be added up
+#[derive(Debug, PartialEq)]
+enum SpaceError {
+    OverCapacity { used_space: u64 }, // files take up more than the whole drive
+    NoFolderLargeEnough { min_deletion_size: u64 }, // no one folder frees up enough space
+    SizeOutOfRange { path: String }, // the total size of the folder at 'path' would not fit in a u64
+}
+impl error::Error for SpaceError {}
+impl fmt::Display for SpaceError {
+    fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            SpaceError::OverCapacity { used_space } =>
+                write!(f, "files take up {used_space} units, more than the {TOTAL_SPACE} units of the drive"),
+            SpaceError::NoFolderLargeEnough { min_deletion_size } =>
+                write!(f, "no folder is large enough to free up the {min_deletion_size} units needed for the update"),
+            SpaceError::SizeOutOfRange { path } =>
+                write!(f, "the total size of '{path}' is out of range"),
         }
+    }
+}
 
-        Err(regex::Error::Syntax(format!("could not match command to any regex syntax: \"{}\"",l)))
+// Error for a command in a terminal log, or a line of its output, that is not in any format it should be
+#[derive(Clone, Debug, PartialEq)]
+struct CommandParseError {
+    command_index: Option<usize>, // which command of the log it is (one-indexed), when parsed from a whole log
+    text: String, // the line that could not be parsed
+    reason: &'static str,
+}
+impl error::Error for CommandParseError {}
+impl fmt::Display for CommandParseError {
+    fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!(f, "could not parse \"{}\": {}", self.text, self.reason)
     }
 }
 
+// Error from a command in a terminal log, with which command it is and the line it starts on (both one-indexed)
+#[derive(Debug)]
+struct CommandError {
+    index: usize,
+    line: usize,
+    error: Box<dyn error::Error>,
+}
+impl error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!(f, "command {} (on line {}): {}", self.index, self.line, self.error)
+    }
+}
 
+// Error for an entry listed in a way that conflicts with an earlier listing
+#[derive(Clone, Debug, PartialEq)]
+struct ConflictingEntryError {
+    path: String, // path of the entry
+    old_size: Option<u64>, // size the entry was first listed with, or None if it was a folder
+    new_size: Option<u64>, // size the entry was listed with again, or None if it was a folder
+}
+impl error::Error for ConflictingEntryError {}
+impl fmt::Display for ConflictingEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
+        let describe = |size: Option<u64>| match size {
+            Some(size) => format!("a file of size {size}"),
+            None => "a folder".to_string(),
+        };
+        write!(f, "'{}' was listed as {}, but then as {}", self.path, describe(self.old_size), describe(self.new_size))
+    }
+}
 
 #[derive(Clone, Debug)]
-struct DirectoryEntryTypeError;
-impl error::Error for DirectoryEntryTypeError {}
-impl fmt::Display for DirectoryEntryTypeError {
+struct RootRemovalError;
+impl error::Error for RootRemovalError {}
+impl fmt::Display for RootRemovalError {
     fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
-        write!(f, "performed operation on wrong type of DirectoryEntry (file vs folder)")
+        write!(f, "cannot remove the root folder")
     }
 }
 
-#[derive(Clone, Debug)]
-struct DirectoryEntryNotExistError;
+#[derive(Clone, Debug, PartialEq)]
+struct DirectoryEntryNotExistError {
+    name: String, // name of the entry that was not found
+    folder: String, // path of the folder it was looked for in
+}
 impl error::Error for DirectoryEntryNotExistError {}
 impl fmt::Display for DirectoryEntryNotExistError {
     fn fmt(&self, f: &mut fmt::Formatter ) -> fmt::Result {
-        write!(f, "attempted to access non-existent entry")
+        write!(f, "attempted to access non-existent entry '{}' in '{}'", self.name, self.folder)
+    }
+}
+
+// Writes 's' as a quoted JSON string, escaping quotes, backslashes and control characters
+fn write_json_string(f: &mut impl fmt::Write, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+// Formats 'size' as 'du -h' does: sizes under 1024 as they are, and larger ones in the largest unit (K, M, G...) that
+// leaves at least 1, rounded up to one decimal place below 10 and to a whole number otherwise
+// eg: human_readable_size(1536) == "1.5K", human_readable_size(48381165) == "47M"
+fn human_readable_size(size: u64) -> String {
+    const UNITS: [char; 6] = ['K', 'M', 'G', 'T', 'P', 'E'];
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let mut scaled = size as f64 / 1024.0;
+    let mut unit = 0;
+    while scaled >= 1024.0 && unit < UNITS.len() - 1 {
+        scaled /= 1024.0;
+        unit += 1;
+    }
+    let tenths = (scaled * 10.0).ceil();
+    if tenths < 100.0 {
+        format!("{:.1}{}", tenths / 10.0, UNITS[unit])
+    } else {
+        format!("{}{}", scaled.ceil(), UNITS[unit])
+    }
+}
+
+// Joins 'name' onto the folder path 'folder'
+#[cfg(test)]
+fn path_join(folder: &str, name: &str) -> String {
+    if folder.ends_with('/') { format!("{folder}{name}") } else { format!("{folder}/{name}") }
+}
+
+// The subset of JSON used to serialize a FileSystem: objects (with their keys in order), strings and whole numbers
+#[cfg(test)]
+#[derive(Debug)]
+enum JsonValue {
+    Object(Vec<(String, JsonValue)>),
+    String(String),
+    Number(u64),
+}
+
+// Parser for the JSON subset in JsonValue, reading 'json' from byte 'position' onwards
+#[cfg(test)]
+struct JsonParser<'a> {
+    json: &'a str,
+    position: usize,
+}
+#[cfg(test)]
+impl JsonParser<'_> {
+
+    // Parses the whole of 'json' as a single value
+    fn parse(mut self) -> Result<JsonValue, JsonError> {
+        let value = self.value()?;
+        match self.peek() {
+            None => Ok(value),
+            Some(_) => Err(self.error("end of input")),
+        }
+    }
+
+    // Skips whitespace, then gets the next character without consuming it
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.json[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        rest.trim_start().chars().next()
+    }
+
+    // Consumes the next character, which has already been peeked at
+    fn advance(&mut self) -> Option<char> {
+        let c = self.json[self.position..].chars().next()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    // Consumes the next non-whitespace character if it is 'expected', and fails otherwise
+    fn expect(&mut self, expected: char, description: &'static str) -> Result<(), JsonError> {
+        if self.peek() == Some(expected) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.error(description))
+        }
+    }
+
+    fn error(&self, expected: &'static str) -> JsonError {
+        JsonError::Syntax { position: self.position, expected }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, JsonError> {
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('"') => Ok(JsonValue::String(self.string()?)),
+            Some('0'..='9') => self.number(),
+            _ => Err(self.error("an object, string or whole number")),
+        }
+    }
+
+    fn object(&mut self) -> Result<JsonValue, JsonError> {
+        self.expect('{', "'{'")?;
+        let mut entries = Vec::new();
+        if self.peek() == Some('}') {
+            self.advance();
+            return Ok(JsonValue::Object(entries));
+        }
+        loop {
+            let key = self.string()?;
+            self.expect(':', "':'")?;
+            entries.push((key, self.value()?));
+            match self.peek() {
+                Some(',') => { self.advance(); },
+                Some('}') => { self.advance(); return Ok(JsonValue::Object(entries)); },
+                _ => return Err(self.error("',' or '}'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect('"', "a string")?;
+        let mut string = String::new();
+        loop {
+            match self.advance() {
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let c = match self.advance() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(self.error("a valid escape")),
+                    };
+                    string.push(c);
+                }
+                Some(c) if (c as u32) >= 0x20 => string.push(c),
+                _ => return Err(self.error("a closing '\"'")),
+            }
+        }
+    }
+
+    // Reads the hex digits of a "\u" escape, and of the second half of a surrogate pair if the first is one
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let code = match self.hex_digits() {
+            Some(high @ 0xd800..=0xdbff) if self.json[self.position..].starts_with("\\u") => {
+                self.position += 2;
+                match self.hex_digits() {
+                    Some(low @ 0xdc00..=0xdfff) => 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00),
+                    _ => u32::MAX,
+                }
+            }
+            code => code.unwrap_or(u32::MAX),
+        };
+        char::from_u32(code).ok_or(self.error("a valid unicode escape"))
+    }
+
+    // Reads the four hex digits of a "\u" escape
+    fn hex_digits(&mut self) -> Option<u32> {
+        let digits = self.json.get(self.position..self.position + 4).filter(|d| d.bytes().all(|b| b.is_ascii_hexdigit()))?;
+        self.position += 4;
+        Some(u32::from_str_radix(digits, 16).expect("checked to be hex digits"))
+    }
+
+    fn number(&mut self) -> Result<JsonValue, JsonError> {
+        let rest = &self.json[self.position..];
+        let digits = &rest[..rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len()];
+        let number = digits.parse().map_err(|_| self.error("a whole number that fits in 64 bits"))?;
+        self.position += digits.len();
+        Ok(JsonValue::Number(number))
     }
 }
 
@@ -393,35 +1286,64 @@ mod tests {
         // ------- file_3_1 5
         // ------- file_3_2 5
 
-        let root = DirectoryNode::new();
-        root.add_subfile("file_1".to_string(), 500);
-        root.add_subfile("file_2".to_string(), 250);
-        root.add_subfolder("folder_1".to_string());
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        root.add_subfile(&mut fs, "file_1".to_string(), 500).unwrap();
+        root.add_subfile(&mut fs, "file_2".to_string(), 250).unwrap();
+        root.add_subfolder(&mut fs, "folder_1".to_string()).unwrap();
 
-        let folder_1 = root.get_subfolder("folder_1".to_string()).unwrap();
-        folder_1.add_subfile("file_1_1".to_string(), 100);
-        folder_1.add_subfile("file_1_2".to_string(), 350);
-        folder_1.add_subfolder("folder_2".to_string());
-        folder_1.add_subfolder("folder_3".to_string());
+        let folder_1 = root.get_subfolder(&fs, "folder_1").unwrap();
+        folder_1.add_subfile(&mut fs, "file_1_1".to_string(), 100).unwrap();
+        folder_1.add_subfile(&mut fs, "file_1_2".to_string(), 350).unwrap();
+        folder_1.add_subfolder(&mut fs, "folder_2".to_string()).unwrap();
+        folder_1.add_subfolder(&mut fs, "folder_3".to_string()).unwrap();
 
-        let folder_2 = folder_1.get_subfolder("folder_2".to_string()).unwrap();
-        folder_2.add_subfile("file_2_1".to_string(), 425);
-        folder_2.add_subfile("file_2_2".to_string(), 600);
+        let folder_2 = folder_1.get_subfolder(&fs, "folder_2").unwrap();
+        folder_2.add_subfile(&mut fs, "file_2_1".to_string(), 425).unwrap();
+        folder_2.add_subfile(&mut fs, "file_2_2".to_string(), 600).unwrap();
 
-        let folder_3 = folder_1.get_subfolder("folder_3".to_string()).unwrap();
-        folder_3.add_subfile("file_3_1".to_string(), 5);
-        folder_3.add_subfile("file_3_2".to_string(), 5);
+        let folder_3 = folder_1.get_subfolder(&fs, "folder_3").unwrap();
+        folder_3.add_subfile(&mut fs, "file_3_1".to_string(), 5).unwrap();
+        folder_3.add_subfile(&mut fs, "file_3_2".to_string(), 5).unwrap();
 
 
         // Sanity traits about sample directory
-        assert_eq!(root.calculate_size(), 2235); // size is 2235
-        assert_eq!(root.sum_directory_sizes_under_max(650), 10); // Total size under 650 is 10
-        assert_eq!(root.sum_directory_sizes_under_max(1500), 10 + 1025 + 10+1025+100+350); 
-        assert_eq!(root.sum_directory_sizes_under_max(99), 10); 
-        assert_eq!(root.smallest_directory_size_over_min(6).unwrap(), 10); // Smallest diretory over minimum 6 is 10
-        assert_eq!(root.smallest_directory_size_over_min(400).unwrap(), 1025);
-        assert_eq!(root.smallest_directory_size_over_min(4).unwrap(), 10);
+        assert_eq!(root.calculate_size(&fs), 2235); // size is 2235
+        assert_eq!(root.sum_directory_sizes_under_max(&fs, 650), 10); // Total size under 650 is 10
+        assert_eq!(root.sum_directory_sizes_under_max(&fs, 1500), 10 + 1025 + 10+1025+100+350); 
+        assert_eq!(root.sum_directory_sizes_under_max(&fs, 99), 10); 
+        assert_eq!(root.smallest_directory_size_over_min(&fs, 6).unwrap(), 10); // Smallest diretory over minimum 6 is 10
+        assert_eq!(root.smallest_directory_size_over_min(&fs, 400).unwrap(), 1025);
+        assert_eq!(root.smallest_directory_size_over_min(&fs, 4).unwrap(), 10);
+
+    }
 
+    #[test]
+    fn look_up_entries() {
+        // Advent of Code challenge-provided example
+        let fs = example_filesystem();
+        let root = fs.root();
+        let a = root.get_subfolder(&fs, "a").unwrap();
+        assert_eq!(root.get_file_size(&fs, "b.txt").unwrap(), 14848514);
+        assert_eq!(a.get_file_size(&fs, "h.lst").unwrap(), 62596);
+        assert_eq!(root.get_entry(&fs, "a").unwrap(), EntryRef::Folder(a));
+        let b = root.get_node_by_path(&fs, "/b.txt").unwrap();
+        assert_eq!(root.get_entry(&fs, "b.txt").unwrap(), EntryRef::File(b, 14848514));
+        assert_eq!(root.get_entry(&fs, "c.dat").unwrap().id().name(&fs), "c.dat");
+
+        // Missing entries are told apart from entries of the wrong kind
+        let error = root.get_file_size(&fs, "e").err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&DirectoryEntryNotExistError { name: "e".to_string(), folder: "/".to_string() }));
+        let error = root.get_file_size(&fs, "d").err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&DirectoryEntryTypeError { path: "/d".to_string(), expected: EntryKind::File }));
+        assert_eq!(error.to_string(), "expected '/d' to be a file, but it is a folder");
+        let error = a.get_subfolder(&fs, "f").err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&DirectoryEntryTypeError { path: "/a/f".to_string(), expected: EntryKind::Folder }));
+        assert!(root.get_entry(&fs, "A").err().unwrap().is::<DirectoryEntryNotExistError>());
+
+        // Files have no entries of their own
+        let error = b.get_entry(&fs, "x").err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&DirectoryEntryTypeError { path: "/b.txt".to_string(), expected: EntryKind::Folder }));
     }
 
     #[test]
@@ -431,19 +1353,20 @@ mod tests {
         // root
         // -- file_1 500
         // -- file_2 250
-        let root = DirectoryNode::new();
-        root.add_subfile("file_1".to_string(), 500);
-        root.add_subfile("file_2".to_string(), 250);
-        assert_eq!(root.calculate_size(), 750);
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        root.add_subfile(&mut fs, "file_1".to_string(), 500).unwrap();
+        root.add_subfile(&mut fs, "file_2".to_string(), 250).unwrap();
+        assert_eq!(root.calculate_size(&fs), 750);
 
         // Create folder_1 directory with two folders in it
         // folder_1
         // -- file_1_1 100
-        root.add_subfolder("folder_1".to_string());
-        let folder_1 = root.get_subfolder("folder_1".to_string()).unwrap();
-        folder_1.add_subfile("file_1_1".to_string(), 100);
-        assert_eq!(folder_1.calculate_size(), 100);
-        assert_eq!(root.calculate_size(), 850);
+        root.add_subfolder(&mut fs, "folder_1".to_string()).unwrap();
+        let folder_1 = root.get_subfolder(&fs, "folder_1").unwrap();
+        folder_1.add_subfile(&mut fs, "file_1_1".to_string(), 100).unwrap();
+        assert_eq!(folder_1.calculate_size(&fs), 100);
+        assert_eq!(root.calculate_size(&fs), 850);
 
         // Add a second, empty folder
         // root
@@ -452,24 +1375,64 @@ mod tests {
         // -- folder_1
         // ---- file_1_1 100
         // -- folder_2
-        root.add_subfolder("folder_2".to_string());
-        assert_eq!(folder_1.calculate_size(), 100);
+        root.add_subfolder(&mut fs, "folder_2".to_string()).unwrap();
+        assert_eq!(folder_1.calculate_size(&fs), 100);
 
     }
 
     #[test]
     fn parse_input_into_directory() {
         // Create root directory with two example files in it from challenge
-        let root = DirectoryNode::new();
-        root.parse_line_to_directoryentry("290229 dsm").unwrap();
-        root.parse_line_to_directoryentry("273438 fsjwz.css").unwrap();
-        assert_eq!(root.calculate_size(), 290229+273438);
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        root.parse_line_to_directoryentry(&mut fs, "290229 dsm").unwrap();
+        root.parse_line_to_directoryentry(&mut fs, "273438 fsjwz.css").unwrap();
+        assert_eq!(root.calculate_size(&fs), 290229+273438);
 
         // Create subfolder, and put file in it
-        root.parse_line_to_directoryentry("dir test_folder").unwrap();
-        let test_folder = root.get_subfolder("test_folder".to_string()).unwrap();
-        test_folder.parse_line_to_directoryentry("100000 fsjwz.css").unwrap();
-        assert_eq!(root.calculate_size(), 290229+273438 + 100000);
+        root.parse_line_to_directoryentry(&mut fs, "dir test_folder").unwrap();
+        let test_folder = root.get_subfolder(&fs, "test_folder").unwrap();
+        test_folder.parse_line_to_directoryentry(&mut fs, "100000 fsjwz.css").unwrap();
+        assert_eq!(root.calculate_size(&fs), 290229+273438 + 100000);
+    }
+
+    #[test]
+    fn parse_unusual_names() {
+        // Names are everything after the first word, kept as they are
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        root.parse_line_to_directoryentry(&mut fs, "dir my-dir").unwrap();
+        root.parse_line_to_directoryentry(&mut fs, "123 some file.txt").unwrap();
+        root.parse_line_to_directoryentry(&mut fs, "45 archive.tar.gz").unwrap();
+        root.parse_line_to_directoryentry(&mut fs, "dir  folder with  spaces ").unwrap();
+        let mut children = root.iter_bfs(&fs).skip(1).collect::<Vec<_>>();
+        children.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(children, vec![
+            ("/archive.tar.gz".to_string(), EntryKind::File, 45),
+            ("/folder with  spaces".to_string(), EntryKind::Folder, 0),
+            ("/my-dir".to_string(), EntryKind::Folder, 0),
+            ("/some file.txt".to_string(), EntryKind::File, 123),
+        ]);
+
+        // 'cd' takes the same names
+        for (line, name) in [("cd my-dir", "my-dir"), ("cd .config\n", ".config"), (" cd folder with  spaces", "folder with  spaces")] {
+            assert!(matches!(ParsedCommand::from_line(line), Ok(ParsedCommand::CdIntoFolder(n)) if n == name), "{line}");
+        }
+        assert!(matches!(ParsedCommand::from_line("cd /"), Ok(ParsedCommand::CdToRoot)));
+        assert!(matches!(ParsedCommand::from_line("cd .."), Ok(ParsedCommand::CdOutOfFolder)));
+        let entries = vec![LsEntry::File("a b".to_string(), 1), LsEntry::Dir("c-d".to_string())];
+        assert!(matches!(ParsedCommand::from_line("ls\n1 a b\ndir c-d"), Ok(ParsedCommand::Ls(l)) if l == entries));
+        let log = "$ cd /\n$ ls\ndir my-dir\n$ cd my-dir\n$ ls\n10 some file.txt\n$ cd ..";
+        let log_fs = FileSystem::from_commands(log).unwrap();
+        assert_eq!(log_fs.root().get_node_by_path(&log_fs, "/my-dir/some file.txt").unwrap().calculate_size(&log_fs), 10);
+
+        // Lines in neither format are rejected
+        for line in ["dir", "12", "12a name", "-5 name", "file name"] {
+            assert!(root.parse_line_to_directoryentry(&mut fs, line).err().unwrap().is::<CommandParseError>(), "{line}");
+        }
+        for line in ["cd", "pwd", "lsof"] {
+            assert_eq!(ParsedCommand::from_line(line).err().unwrap().text, line);
+        }
     }
 
     #[test]
@@ -478,40 +1441,779 @@ mod tests {
         // recreateable from string commands.
 
         // Create root directory with two example files in it from challenge
-        let root_original = DirectoryNode::new();
+        let mut fs = FileSystem::new();
+        let root_original = fs.root();
 
         // Run simple ls command to create file and a subfolder
-        let node = root_original.command(ParsedCommand::from_line(
+        let node = root_original.command(&mut fs, ParsedCommand::from_line(
             "ls 
             290229 dsm
             dir folder1
             273438 fsjwz12321.css").unwrap()).unwrap();
-        assert_eq!(node.calculate_size(), 290229+273438);
+        assert_eq!(node.calculate_size(&fs), 290229+273438);
 
         // Enter subfolder and create further subentries
-        let node = node.command( ParsedCommand::from_line(
+        let node = node.command(&mut fs, ParsedCommand::from_line(
             "cd folder1").unwrap()).unwrap();
-        let node = node.command( ParsedCommand::from_line(
+        let node = node.command(&mut fs, ParsedCommand::from_line(
             "ls 
             dir folder2
             100000 fsjwz.css").unwrap()).unwrap();
-        assert_eq!(node.calculate_size(), 100000);
+        assert_eq!(node.calculate_size(&fs), 100000);
 
         // Return to parent
-        let node = node.command( ParsedCommand::from_line(
+        let node = node.command(&mut fs, ParsedCommand::from_line(
             "cd ..").unwrap()).unwrap();
-        assert_eq!(node.calculate_size(), 290229+273438+100000);
+        assert_eq!(node.calculate_size(&fs), 290229+273438+100000);
 
         // Enter fodler all the way in, then resset to root
-        let node = node.command(ParsedCommand::from_line(
+        let node = node.command(&mut fs, ParsedCommand::from_line(
             "cd folder1").unwrap()).unwrap();
-            let node = node.command(ParsedCommand::from_line(
+            let node = node.command(&mut fs, ParsedCommand::from_line(
                 "cd folder2").unwrap()).unwrap();
-            assert_eq!(node.calculate_size(), 0);
+            assert_eq!(node.calculate_size(&fs), 0);
 
-        let node = node.command( ParsedCommand::from_line(
+        let node = node.command(&mut fs, ParsedCommand::from_line(
             "cd /").unwrap()).unwrap();
-        assert_eq!(node.calculate_size(), 290229+273438+100000);
+        assert_eq!(node.calculate_size(&fs), 290229+273438+100000);
         
     }
-}
\ No newline at end of file
+    #[test]
+    fn navigate_by_path() {
+        let fs = example_filesystem();
+        let root = fs.root();
+
+        // Absolute paths start at the root, wherever they are followed from
+        let e = root.get_node_by_path(&fs, "/a/e").unwrap();
+        assert_eq!(e.calculate_size(&fs), 584);
+        assert_eq!(e.get_node_by_path(&fs, "/a/e/i").unwrap().calculate_size(&fs), 584);
+        assert_eq!(e.get_node_by_path(&fs, "/d").unwrap().calculate_size(&fs), 24933642);
+        assert_eq!(e.get_node_by_path(&fs, "/").unwrap().calculate_size(&fs), 48381165);
+
+        // Relative paths start at the current folder, with '.' and '..'
+        assert_eq!(e.get_node_by_path(&fs, "i").unwrap().calculate_size(&fs), 584);
+        assert_eq!(e.get_node_by_path(&fs, "./i").unwrap().calculate_size(&fs), 584);
+        assert_eq!(e.get_node_by_path(&fs, "..").unwrap().calculate_size(&fs), 94853);
+        assert_eq!(e.get_node_by_path(&fs, "../f").unwrap().calculate_size(&fs), 29116);
+        assert_eq!(e.get_node_by_path(&fs, "../../d/k").unwrap().calculate_size(&fs), 7214296);
+        assert_eq!(root.get_node_by_path(&fs, "a//e/./").unwrap().calculate_size(&fs), 584);
+
+        // Going up past the root stays at the root
+        assert_eq!(e.get_node_by_path(&fs, "../../../../a/e").unwrap().path_of(&fs), "/a/e");
+        assert_eq!(root.get_node_by_path(&fs, "..").unwrap().path_of(&fs), "/");
+
+        // A missing segment reports which entry was not found, and where
+        let error = root.get_node_by_path(&fs, "/a/x/i").err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&DirectoryEntryNotExistError { name: "x".to_string(), folder: "/a".to_string() }));
+        assert_eq!(error.to_string(), "attempted to access non-existent entry 'x' in '/a'");
+        let error = e.get_node_by_path(&fs, "../../e").err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&DirectoryEntryNotExistError { name: "e".to_string(), folder: "/".to_string() }));
+
+        // Files have no entries beneath them
+        assert!(root.get_node_by_path(&fs, "/a/e/i/j").err().unwrap().is::<DirectoryEntryTypeError>());
+    }
+
+    #[test]
+    fn node_paths() {
+        let fs = example_filesystem();
+        let root = fs.root();
+        assert_eq!(root.path_of(&fs), "/");
+        assert_eq!(root.get_node_by_path(&fs, "/a").unwrap().path_of(&fs), "/a");
+        assert_eq!(root.get_node_by_path(&fs, "/a/e/i").unwrap().path_of(&fs), "/a/e/i");
+        assert_eq!(root.get_node_by_path(&fs, "/d/k").unwrap().path_of(&fs), "/d/k");
+
+        // Every path leads back to the same node
+        for path in ["/a/e/i", "/a/h.lst", "/b.txt", "/d/d.ext"] {
+            let node = root.get_node_by_path(&fs, path).unwrap();
+            assert_eq!(root.get_node_by_path(&fs, &node.path_of(&fs)).unwrap().path_of(&fs), path);
+        }
+    }
+
+    #[test]
+    fn render_tree() {
+        // Advent of Code challenge-provided example
+        let fs = example_filesystem();
+        let root = fs.root();
+        assert_eq!(fs.to_string(), "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)");
+
+        // Subtrees are rendered under their own name, with folder sizes if asked for
+        let a = root.get_node_by_path(&fs, "/a").unwrap();
+        assert_eq!(a.render_tree(&fs, true), "\
+- a (dir, size=94853)
+  - e (dir, size=584)
+    - i (file, size=584)
+  - f (file, size=29116)
+  - g (file, size=2557)
+  - h.lst (file, size=62596)");
+        assert_eq!(root.get_node_by_path(&fs, "/a/e/i").unwrap().render_tree(&fs, true), "- i (file, size=584)");
+        assert_eq!(FileSystem::new().to_string(), "- / (dir)");
+    }
+
+    #[test]
+    fn filesystem_is_send() {
+        // A FileSystem holds no shared references, so can be moved to another thread
+        let fs = example_filesystem();
+        let size = std::thread::spawn(move || fs.root().calculate_size(&fs)).join().unwrap();
+        assert_eq!(size, 48381165);
+    }
+
+    #[test]
+    fn tree_iterators() {
+        let fs = example_filesystem();
+        let root = fs.root();
+        let folder = |path: &str, size| (path.to_string(), EntryKind::Folder, size);
+        let file = |path: &str, size| (path.to_string(), EntryKind::File, size);
+
+        // Advent of Code challenge-provided example
+        assert_eq!(root.iter_dfs(&fs).collect::<Vec<_>>(), [
+            folder("/", 48381165),
+            folder("/a", 94853),
+            folder("/a/e", 584),
+            file("/a/e/i", 584),
+            file("/a/f", 29116),
+            file("/a/g", 2557),
+            file("/a/h.lst", 62596),
+            file("/b.txt", 14848514),
+            file("/c.dat", 8504156),
+            folder("/d", 24933642),
+            file("/d/d.ext", 5626152),
+            file("/d/d.log", 8033020),
+            file("/d/j", 4060174),
+            file("/d/k", 7214296),
+        ]);
+        assert_eq!(root.iter_bfs(&fs).collect::<Vec<_>>(), [
+            folder("/", 48381165),
+            folder("/a", 94853),
+            file("/b.txt", 14848514),
+            file("/c.dat", 8504156),
+            folder("/d", 24933642),
+            folder("/a/e", 584),
+            file("/a/f", 29116),
+            file("/a/g", 2557),
+            file("/a/h.lst", 62596),
+            file("/d/d.ext", 5626152),
+            file("/d/d.log", 8033020),
+            file("/d/j", 4060174),
+            file("/d/k", 7214296),
+            file("/a/e/i", 584),
+        ]);
+
+        // Iterating from a node covers just the entries beneath it, and a file is alone
+        let a = root.get_node_by_path(&fs, "/a").unwrap();
+        let paths: Vec<String> = a.iter_bfs(&fs).map(|(path, _, _)| path).collect();
+        assert_eq!(paths, ["/a", "/a/e", "/a/f", "/a/g", "/a/h.lst", "/a/e/i"]);
+        let i = root.get_node_by_path(&fs, "/a/e/i").unwrap();
+        assert_eq!(i.iter_dfs(&fs).collect::<Vec<_>>(), [file("/a/e/i", 584)]);
+
+        // The puzzle's example answer for part 1, from the entries
+        assert_eq!(root.sum_directory_sizes_under_max(&fs, 100000), 95437);
+    }
+
+    #[test]
+    fn remove_entries() {
+        // Delete the folder the puzzle's example chooses for part 2, which frees enough space for the update
+        let mut fs = example_filesystem();
+        let root = fs.root();
+        let free_space = TOTAL_SPACE - root.calculate_size(&fs);
+        let to_delete = root.smallest_directory_size_over_min(&fs, SPACE_REQUIRED_FOR_UPDATE - free_space).unwrap();
+        assert_eq!(to_delete, 24933642);
+        assert_eq!(root.remove_by_path(&mut fs, "/d").unwrap(), to_delete);
+        assert_eq!(root.calculate_size(&fs), 48381165 - 24933642);
+        assert!(TOTAL_SPACE - root.calculate_size(&fs) >= SPACE_REQUIRED_FOR_UPDATE);
+        assert!(root.get_node_by_path(&fs, "/d").is_err());
+        assert_eq!(root.iter_dfs(&fs).filter(|(path, _, _)| path.starts_with("/d")).count(), 0);
+
+        // Removing a file shrinks every folder above it
+        let a = root.get_node_by_path(&fs, "/a").unwrap();
+        let e = root.get_node_by_path(&fs, "/a/e").unwrap();
+        assert_eq!(e.remove(&mut fs, "i").unwrap(), 584);
+        assert_eq!((e.calculate_size(&fs), a.calculate_size(&fs)), (0, 94853 - 584));
+        assert_eq!(root.calculate_size(&fs), 48381165 - 24933642 - 584);
+        assert_eq!(root.sum_directory_sizes_under_max(&fs, 100000), 94853 - 584);
+
+        // Removed entries can be added again
+        e.add_subfile(&mut fs, "i".to_string(), 10).unwrap();
+        assert_eq!(a.calculate_size(&fs), 94853 - 584 + 10);
+
+        // Missing entries, entries of a file and the root can't be removed
+        let error = a.remove(&mut fs, "x").err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&DirectoryEntryNotExistError { name: "x".to_string(), folder: "/a".to_string() }));
+        assert!(root.remove_by_path(&mut fs, "/d").err().unwrap().is::<DirectoryEntryNotExistError>());
+        let f = root.get_node_by_path(&fs, "/a/f").unwrap();
+        assert!(f.remove(&mut fs, "g").err().unwrap().is::<DirectoryEntryTypeError>());
+        assert!(a.remove_by_path(&mut fs, "..").err().unwrap().is::<RootRemovalError>());
+        assert_eq!(a.calculate_size(&fs), 94853 - 584 + 10);
+
+        // A file past the i64 range is removed like any other
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        root.add_subfile(&mut fs, "big.bin".to_string(), u64::MAX).unwrap();
+        assert_eq!(root.remove(&mut fs, "big.bin").unwrap(), u64::MAX);
+        assert_eq!(root.calculate_size(&fs), 0);
+
+        // A total too small to take a child's size from fails, rather than wrapping, and the child stays
+        root.add_subfile(&mut fs, "x".to_string(), 5).unwrap();
+        fs.nodes[0].size = 4;
+        let error = root.remove(&mut fs, "x").err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&SpaceError::SizeOutOfRange { path: "/".to_string() }));
+        assert_eq!((root.get_file_size(&fs, "x").unwrap(), root.calculate_size(&fs)), (5, 4));
+    }
+
+    #[test]
+    fn glob_matching() {
+        // '*' at the start, middle and end
+        assert!(matches_glob("*.css", "fsjwz.css"));
+        assert!(matches_glob("*.css", ".css"));
+        assert!(!matches_glob("*.css", "fsjwz.css.bak"));
+        assert!(matches_glob("d*.log", "d.log"));
+        assert!(matches_glob("d*.log", "dir.of.log"));
+        assert!(!matches_glob("d*.log", "d.lo"));
+        assert!(matches_glob("h.*", "h.lst"));
+        assert!(matches_glob("h.*", "h."));
+        assert!(!matches_glob("h.*", "h"));
+
+        // Several '*'s, which have to backtrack to match
+        assert!(matches_glob("*a*b*", "xxaxxbxx"));
+        assert!(matches_glob("*ab", "aab"));
+        assert!(matches_glob("a*a*a", "aaa"));
+        assert!(!matches_glob("a*a*a", "aa"));
+        assert!(matches_glob("**", ""));
+        assert!(matches_glob("*", "anything"));
+
+        // '?' matches exactly one character
+        assert!(matches_glob("?", "i"));
+        assert!(!matches_glob("?", ""));
+        assert!(!matches_glob("?", "ab"));
+        assert!(matches_glob("d?.*", "d1.log"));
+        assert!(matches_glob("b.t?t", "b.txt"));
+
+        // Without wildcards, names match exactly
+        assert!(matches_glob("k", "k"));
+        assert!(!matches_glob("k", "K"));
+        assert!(matches_glob("", ""));
+        assert!(!matches_glob("", "k"));
+    }
+
+    #[test]
+    fn find_entries() {
+        let fs = example_filesystem();
+        let root = fs.root();
+        let file = |path: &str, size, depth| (path.to_string(), EntryInfo { kind: EntryKind::File, size, depth });
+
+        // Advent of Code challenge-provided example
+        assert_eq!(root.find_glob(&fs, "*.*"), [
+            file("/a/h.lst", 62596, 2),
+            file("/b.txt", 14848514, 1),
+            file("/c.dat", 8504156, 1),
+            file("/d/d.ext", 5626152, 2),
+            file("/d/d.log", 8033020, 2),
+        ]);
+        // The root is named "/", so is one character long
+        assert_eq!(root.find_glob(&fs, "?"), [
+            ("/".to_string(), EntryInfo { kind: EntryKind::Folder, size: 48381165, depth: 0 }),
+            ("/a".to_string(), EntryInfo { kind: EntryKind::Folder, size: 94853, depth: 1 }),
+            ("/a/e".to_string(), EntryInfo { kind: EntryKind::Folder, size: 584, depth: 2 }),
+            file("/a/e/i", 584, 3),
+            file("/a/f", 29116, 2),
+            file("/a/g", 2557, 2),
+            ("/d".to_string(), EntryInfo { kind: EntryKind::Folder, size: 24933642, depth: 1 }),
+            file("/d/j", 4060174, 2),
+            file("/d/k", 7214296, 2),
+        ]);
+
+        // Files over a size, and folders under one (the puzzle's part 1)
+        let large = root.find(&fs, |_, info| info.kind == EntryKind::File && info.size > 8000000);
+        assert_eq!(large, [file("/b.txt", 14848514, 1), file("/c.dat", 8504156, 1), file("/d/d.log", 8033020, 2)]);
+        let small: u64 = root.find(&fs, |_, info| info.kind == EntryKind::Folder && info.size <= 100000).iter().map(|(_, info)| info.size).sum();
+        assert_eq!(small, 95437);
+
+        // Depth counts from the node searched from
+        let a = root.get_node_by_path(&fs, "/a").unwrap();
+        assert_eq!(a.find(&fs, |_, info| info.depth == 0).len(), 1);
+        assert_eq!(a.find_glob(&fs, "i"), [file("/a/e/i", 584, 2)]);
+        assert_eq!(a.find_glob(&fs, "*.css"), []);
+    }
+
+    #[test]
+    fn cd_into_unlisted_folder() {
+        // 'b' is entered before it is listed, and 'c' is never listed
+        let log = ["cd /", "ls\n100 x\ndir a", "cd b", "ls\n20 y", "cd c", "ls\n3 z", "cd ..", "cd ..", "cd a", "ls\n4 w"];
+        let commands = || log.iter().map(|l| ParsedCommand::from_line(l).unwrap()).collect::<Vec<_>>();
+
+        // Strict mode fails on the first folder that does not exist
+        let mut fs = FileSystem::new();
+        let error = fs.apply_commands(commands(), CdPolicy::Strict).err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&DirectoryEntryNotExistError { name: "b".to_string(), folder: "/".to_string() }));
+        assert_eq!(CdPolicy::default(), CdPolicy::Strict);
+
+        // Creating missing folders gives the whole tree, counting the folders created
+        let mut fs = FileSystem::new();
+        assert_eq!(fs.apply_commands(commands(), CdPolicy::CreateMissing).unwrap(), 2);
+        assert_eq!(fs.to_string(), "\
+- / (dir)
+  - a (dir)
+    - w (file, size=4)
+  - b (dir)
+    - c (dir)
+      - z (file, size=3)
+    - y (file, size=20)
+  - x (file, size=100)");
+        assert_eq!(fs.root().calculate_size(&fs), 127);
+
+        // Folders that were listed are entered as before, without being counted
+        let mut fs = FileSystem::new();
+        let listed = ["ls\ndir a", "cd a", "ls\n5 f"].iter().map(|l| ParsedCommand::from_line(l).unwrap()).collect();
+        assert_eq!(fs.apply_commands(listed, CdPolicy::CreateMissing).unwrap(), 0);
+        assert_eq!(fs.root().calculate_size(&fs), 5);
+    }
+
+    #[test]
+    fn repeated_listings() {
+        let commands = |log: &[&str]| log.iter().map(|l| ParsedCommand::from_line(l).unwrap()).collect::<Vec<_>>();
+
+        // Listing a folder again with the same contents changes nothing
+        let mut fs = FileSystem::new();
+        let log = ["cd /", "ls\n100 x\ndir a", "cd a", "ls\n5 y", "cd ..", "ls\n100 x\ndir a", "cd a", "ls\n5 y"];
+        fs.apply_commands(commands(&log), CdPolicy::Strict).unwrap();
+        assert_eq!(fs.root().calculate_size(&fs), 105);
+        assert_eq!(fs.to_string(), "- / (dir)\n  - a (dir)\n    - y (file, size=5)\n  - x (file, size=100)");
+        assert!(fs.root().is_listed(&fs));
+
+        // A file listed again with another size is a conflict
+        let mut fs = FileSystem::new();
+        let log = ["cd /", "ls\n100 x\ndir a", "cd a", "ls\n5 y", "cd ..", "ls\n100 x\ndir a", "cd a", "ls\n6 y"];
+        let error = fs.apply_commands(commands(&log), CdPolicy::Strict).err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&ConflictingEntryError { path: "/a/y".to_string(), old_size: Some(5), new_size: Some(6) }));
+        assert_eq!(error.to_string(), "'/a/y' was listed as a file of size 5, but then as a file of size 6");
+
+        // As is an entry listed as the other kind of entry
+        let mut fs = FileSystem::new();
+        let error = fs.apply_commands(commands(&["ls\ndir a", "ls\n7 a"]), CdPolicy::Strict).err().unwrap();
+        assert_eq!(error.downcast_ref(), Some(&ConflictingEntryError { path: "/a".to_string(), old_size: None, new_size: Some(7) }));
+        assert_eq!(error.to_string(), "'/a' was listed as a folder, but then as a file of size 7");
+
+        // Including a folder that was created by entering it
+        let mut fs = FileSystem::new();
+        let error = fs.apply_commands(commands(&["cd b", "cd ..", "ls\n3 b"]), CdPolicy::CreateMissing).err().unwrap();
+        assert!(error.is::<ConflictingEntryError>());
+
+        // Only folders that 'ls' has run in are listed
+        let mut fs = FileSystem::new();
+        fs.apply_commands(commands(&["ls\ndir a\ndir b", "cd a", "ls\n1 c"]), CdPolicy::Strict).unwrap();
+        let root = fs.root();
+        let listed: Vec<bool> = ["/", "/a", "/b"].iter().map(|path| root.get_node_by_path(&fs, path).unwrap().is_listed(&fs)).collect();
+        assert_eq!(listed, [true, true, false]);
+    }
+
+    #[test]
+    fn example_answers() {
+        // Advent of Code challenge-provided example
+        let fs = FileSystem::from_commands(EXAMPLE).unwrap();
+        assert_eq!(solve(&fs, false), Ok(95437));
+        assert_eq!(solve(&fs, true), Ok(24933642));
+
+        // Surrounding whitespace and a trailing newline make no difference
+        let fs = FileSystem::from_commands(&format!("\n\n{EXAMPLE}\n")).unwrap();
+        assert_eq!((solve(&fs, false), solve(&fs, true)), (Ok(95437), Ok(24933642)));
+    }
+
+    #[test]
+    fn example_report() {
+        let fs = FileSystem::from_commands(EXAMPLE).unwrap();
+        assert_eq!(verbose_report(&fs, None).join("\n"), "Largest .css files:
+Top 10 space hogs:
+  files:
+    /b.txt (size=14848514)
+    /c.dat (size=8504156)
+    /d/d.log (size=8033020)
+    /d/k (size=7214296)
+    /d/d.ext (size=5626152)
+    /d/j (size=4060174)
+    /a/h.lst (size=62596)
+    /a/f (size=29116)
+    /a/g (size=2557)
+    /a/e/i (size=584)
+  folders:
+    / (size=48381165)
+    /d (size=24933642)
+    /a (size=94853)
+    /a/e (size=584)
+Folder sizes, 2 levels deep:
+  47M\t/
+  24M\t/d
+  93K\t/a
+  584\t/a/e");
+
+        // The count of created folders is only reported when 'cd' may create them
+        assert_eq!(verbose_report(&fs, Some(0))[0], "Folders created by cd before being listed: 0");
+        assert_eq!(verbose_report(&fs, Some(0)).len(), verbose_report(&fs, None).len() + 1);
+    }
+
+    #[test]
+    fn example_deletion() {
+        // Deleting the folder part 2 chose leaves room for the update
+        let mut fs = FileSystem::from_commands(EXAMPLE).unwrap();
+        assert_eq!(delete_for_update(&mut fs, 24933642).unwrap(), "Deleting /d frees 24933642 units, leaving 46552477 free for the 30000000 unit update");
+        assert_eq!(fs.root().calculate_size(&fs), 48381165 - 24933642);
+
+        // Only folders beneath the root are deleted
+        let mut fs = FileSystem::from_commands("$ cd /\n$ ls\n70000000 a").unwrap();
+        assert_eq!(delete_for_update(&mut fs, 70000000).unwrap(), "No folder of 70000000 units beneath / to delete");
+        assert_eq!(fs.root().calculate_size(&fs), 70000000);
+    }
+
+    #[test]
+    fn large_sizes() {
+        // Sizes add up past the u32 range without wrapping
+        let fs = FileSystem::from_commands("$ cd /\n$ ls\n3000000000 a\ndir b\n$ cd b\n$ ls\n3000000000 c\n$ cd ..").unwrap();
+        assert_eq!(fs.root().calculate_size(&fs), 6000000000);
+        assert_eq!(solve(&fs, false), Ok(0));
+
+        // A filesystem larger than the drive can't make room for the update
+        let error = solve(&fs, true).unwrap_err();
+        assert_eq!(error, SpaceError::OverCapacity { used_space: 6000000000 });
+        assert_eq!(error.to_string(), "files take up 6000000000 units, more than the 70000000 units of the drive");
+        let fs = FileSystem::from_commands("$ cd /\n$ ls\n70000001 a").unwrap();
+        assert_eq!(solve(&fs, true), Err(SpaceError::OverCapacity { used_space: 70000001 }));
+
+        // A full drive with only files at the root has no folder to delete but the root itself
+        let fs = FileSystem::from_commands("$ cd /\n$ ls\n70000000 a").unwrap();
+        assert_eq!(solve(&fs, true), Ok(70000000));
+        let fs = FileSystem::from_commands("$ cd /\n$ ls\n50000000 a\ndir b\n$ cd b\n$ ls\n5 c").unwrap();
+        assert_eq!(fs.root().smallest_directory_size_over_min(&fs, 50000006), None);
+
+        // With enough free space already, the smallest folder will do
+        let fs = FileSystem::from_commands("$ cd /\n$ ls\n100 a\ndir b\n$ cd b\n$ ls\n5 c").unwrap();
+        assert_eq!(solve(&fs, true), Ok(5));
+
+        // A file past the i64 range still fits, as sizes are only ever added as u64s
+        let fs = FileSystem::from_commands("$ cd /\n$ ls\n10000000000000000000 big.bin\n").unwrap();
+        assert_eq!(fs.root().calculate_size(&fs), 10000000000000000000);
+        assert_eq!(solve(&fs, true), Err(SpaceError::OverCapacity { used_space: 10000000000000000000 }));
+
+        // But a listing that overflows a folder's total fails, naming the folder and leaving its total as it was
+        let log = "$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\n9000000000000000000 x\n9000000000000000000 y\n9000000000000000000 z\n";
+        let error = FileSystem::from_commands(log).err().unwrap();
+        assert_eq!(error.to_string(), "command 4 (on line 5): the total size of '/a' is out of range");
+        assert_eq!(error.source().unwrap().downcast_ref(), Some(&SpaceError::SizeOutOfRange { path: "/a".to_string() }));
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        root.add_subfile(&mut fs, "x".to_string(), u64::MAX).unwrap();
+        assert_eq!(root.add_subfile(&mut fs, "y".to_string(), 1), Err(SpaceError::SizeOutOfRange { path: "/".to_string() }));
+        assert_eq!((root.calculate_size(&fs), root.get_entry(&fs, "y").is_err()), (u64::MAX, true));
+    }
+
+    #[test]
+    fn largest_entries() {
+        // Advent of Code challenge-provided example
+        let fs = example_filesystem();
+        let root = fs.root();
+        let entries = |list: &[(&str, u64)]| list.iter().map(|&(path, size)| (path.to_string(), size)).collect::<Vec<_>>();
+        assert_eq!(root.largest_files(&fs, 4), entries(&[("/b.txt", 14848514), ("/c.dat", 8504156), ("/d/d.log", 8033020), ("/d/k", 7214296)]));
+        assert_eq!(root.largest_dirs(&fs, 2), entries(&[("/", 48381165), ("/d", 24933642)]));
+        assert_eq!(root.largest_files(&fs, 0), vec![]);
+
+        // Asking for more than there are gives all of them
+        assert_eq!(root.largest_dirs(&fs, 100), entries(&[("/", 48381165), ("/d", 24933642), ("/a", 94853), ("/a/e", 584)]));
+        assert_eq!(root.largest_files(&fs, 100).len(), 10);
+        assert_eq!(root.largest_files(&fs, 100).last(), Some(&("/a/e/i".to_string(), 584)));
+        let a = root.get_subfolder(&fs, "a").unwrap();
+        assert_eq!(a.largest_files(&fs, 100), entries(&[("/a/h.lst", 62596), ("/a/f", 29116), ("/a/g", 2557), ("/a/e/i", 584)]));
+
+        // Ties are broken by path
+        let fs = FileSystem::from_commands("$ cd /\n$ ls\n5 b\ndir c\n5 a\n$ cd c\n$ ls\n5 a\ndir d\n$ cd d\n$ ls\n1 z").unwrap();
+        assert_eq!(fs.root().largest_files(&fs, 3), entries(&[("/a", 5), ("/b", 5), ("/c/a", 5)]));
+        assert_eq!(fs.root().largest_dirs(&fs, 3), entries(&[("/", 16), ("/c", 6), ("/c/d", 1)]));
+    }
+
+    #[test]
+    fn size_report() {
+        // Advent of Code challenge-provided example
+        let fs = example_filesystem();
+        let root = fs.root();
+        assert_eq!(root.report(&fs, 0, SortBy::Path, false), "48381165\t/");
+        assert_eq!(root.report(&fs, 1, SortBy::Path, false), "\
+48381165\t/
+94853\t/a
+24933642\t/d");
+        assert_eq!(root.report(&fs, 1, SortBy::Size, true), "\
+47M\t/
+24M\t/d
+93K\t/a");
+        assert_eq!(root.report(&fs, 2, SortBy::Path, true), "\
+47M\t/
+93K\t/a
+584\t/a/e
+24M\t/d");
+        assert_eq!(root.report(&fs, 2, SortBy::Size, false), "\
+48381165\t/
+24933642\t/d
+94853\t/a
+584\t/a/e");
+
+        // Depths are counted from the folder reported on
+        let a = root.get_subfolder(&fs, "a").unwrap();
+        assert_eq!(a.report(&fs, 1, SortBy::Path, false), "94853\t/a\n584\t/a/e");
+
+        // Sizes are rounded up as 'du -h' does
+        let sizes = [0, 1023, 1024, 1536, 1537, 10239, 10240, 10241, 1048576, 5 << 30, u64::MAX];
+        assert_eq!(sizes.map(human_readable_size), ["0", "1023", "1.0K", "1.5K", "1.6K", "10K", "10K", "11K", "1.0M", "5.0G", "16E"]);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let sorted_sizes = |fs: &FileSystem| {
+            let mut sizes = fs.root().get_all_directory_sizes(fs).0;
+            sizes.sort();
+            sizes
+        };
+
+        // Advent of Code challenge-provided example
+        let fs = example_filesystem();
+        let json = fs.to_json();
+        assert_eq!(json, concat!(
+            r#"{"a":{"type":"dir","children":{"e":{"type":"dir","children":{"i":{"type":"file","size":584}}},"#,
+            r#""f":{"type":"file","size":29116},"g":{"type":"file","size":2557},"h.lst":{"type":"file","size":62596}}},"#,
+            r#""b.txt":{"type":"file","size":14848514},"c.dat":{"type":"file","size":8504156},"#,
+            r#""d":{"type":"dir","children":{"d.ext":{"type":"file","size":5626152},"d.log":{"type":"file","size":8033020},"#,
+            r#""j":{"type":"file","size":4060174},"k":{"type":"file","size":7214296}}}}"#));
+
+        // The parsed tree matches the original, down to the sizes and answers computed from it
+        let parsed = FileSystem::from_json(&json).unwrap();
+        assert_eq!(parsed.to_string(), fs.to_string());
+        assert_eq!(parsed.to_json(), json);
+        assert_eq!(sorted_sizes(&parsed), sorted_sizes(&fs));
+        assert_eq!((solve(&parsed, false), solve(&parsed, true)), (Ok(95437), Ok(24933642)));
+
+        // So do larger trees, and names that need escaping
+        let fs = synthetic_filesystem(3, 3, 4);
+        let parsed = FileSystem::from_json(&fs.to_json()).unwrap();
+        assert_eq!(sorted_sizes(&parsed), sorted_sizes(&fs));
+        let fs = FileSystem::from_commands("$ cd /\n$ ls\n1 say \"hi\"\n2 back\\slash\ndir é ☃").unwrap();
+        let parsed = FileSystem::from_json(&fs.to_json()).unwrap();
+        assert_eq!(parsed.to_string(), fs.to_string());
+        assert_eq!(FileSystem::new().to_json(), "{}");
+
+        // Whitespace, other key orders and escapes written by other tools are accepted
+        let json = r#" { "a" : { "size" : 7 , "type" : "file" } , "é😀\n" : { "type" : "dir" , "children" : { } } } "#;
+        let parsed = FileSystem::from_json(json).unwrap();
+        assert_eq!(parsed.root().calculate_size(&parsed), 7);
+        assert!(parsed.root().get_subfolder(&parsed, "é😀\n").is_ok());
+    }
+
+    #[test]
+    fn json_errors() {
+        let syntax = |json| match FileSystem::from_json(json).err().unwrap() {
+            JsonError::Syntax { position, .. } => position,
+            e => panic!("{e}"),
+        };
+        assert_eq!(syntax(""), 0);
+        assert_eq!(syntax(r#"{"a":{"type":"file","size":1}"#), 29);
+        assert_eq!(syntax(r#"{"a":{"type":"file","size":-1}}"#), 27);
+        assert_eq!(syntax(r#"{"a":{"type":"file","size":1.5}}"#), 28);
+        assert_eq!(syntax(r#"{"a":{"type":"file","size":99999999999999999999}}"#), 27);
+        assert_eq!(syntax(r#"{"a\q":{}}"#), 5);
+        assert_eq!(syntax(r#"{"\ud83d":{}}"#), 8);
+        assert_eq!(syntax(r#"{} {}"#), 3);
+        assert_eq!(FileSystem::from_json("[]").err().unwrap().to_string(), "invalid JSON at byte 0: expected an object, string or whole number");
+
+        let bad_entry = |json| match FileSystem::from_json(json).err().unwrap() {
+            JsonError::BadEntry { path, .. } => path,
+            e => panic!("{e}"),
+        };
+        assert_eq!(bad_entry(r#""a""#), "/");
+        assert_eq!(bad_entry(r#"{"a":{"type":"dir","children":{"b":{"type":"file"}}}}"#), "/a/b");
+        assert_eq!(bad_entry(r#"{"a":{"type":"file","size":1,"children":{}}}"#), "/a");
+        assert_eq!(bad_entry(r#"{"a":{"type":"folder","children":{}}}"#), "/a");
+        assert_eq!(bad_entry(r#"{"a":{"type":"dir","children":"b"}}"#), "/a");
+        assert_eq!(bad_entry(r#"{"a":{"type":"file","size":1,"colour":"red"}}"#), "/a");
+        assert_eq!(bad_entry(r#"{"a":{"type":"file","size":1},"a":{"type":"file","size":1}}"#), "/a");
+        assert_eq!(bad_entry(r#"{"a/b":{"type":"file","size":1}}"#), "/a/b");
+        assert_eq!(FileSystem::from_json(r#"{"":{"type":"file","size":1}}"#).err().unwrap().to_string(), "invalid entry '/': names can't be empty or contain '/'");
+    }
+
+    #[test]
+    fn command_errors() {
+        // Errors are reported with the line of the command that caused them
+        let error = FileSystem::from_commands("$ cd /\n$ ls\ndir a\n$ cd b").err().unwrap();
+        let error = error.downcast_ref::<CommandError>().unwrap();
+        assert_eq!((error.index, error.line), (3, 4));
+        assert_eq!(error.error.downcast_ref(), Some(&DirectoryEntryNotExistError { name: "b".to_string(), folder: "/".to_string() }));
+        assert_eq!(error.to_string(), "command 3 (on line 4): attempted to access non-existent entry 'b' in '/'");
+
+        let error = FileSystem::from_commands("\n$ cd /\n$ ls\ndir a\n10 b\nbad line\n$ cd a").err().unwrap();
+        assert_eq!(error.downcast_ref::<CommandError>().unwrap().line, 3);
+        let error = FileSystem::from_commands("$ cd /\n$ pwd").err().unwrap();
+        assert_eq!(error.downcast_ref::<CommandError>().unwrap().line, 2);
+
+        // A typo three folders deep is reported with where in the tree it happened
+        let log = "$ cd /\n$ ls\ndir alpha\n$ cd alpha\n$ ls\ndir beta\n1 x\n$ cd beta\n$ ls\ndir gamma\n$ cd gamma\n$ ls\ndir delta\n$ cd detla";
+        let error = FileSystem::from_commands(log).err().unwrap();
+        let error = error.downcast_ref::<CommandError>().unwrap();
+        assert_eq!((error.index, error.line), (9, 14));
+        assert_eq!(error.error.downcast_ref(), Some(&DirectoryEntryNotExistError { name: "detla".to_string(), folder: "/alpha/beta/gamma".to_string() }));
+        assert_eq!(error.to_string(), "command 9 (on line 14): attempted to access non-existent entry 'detla' in '/alpha/beta/gamma'");
+
+        // As is going into a file as if it were a folder
+        let error = FileSystem::from_commands("$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\n1 x\n$ cd x\n$ cd y").err().unwrap();
+        let error = error.downcast_ref::<CommandError>().unwrap();
+        assert_eq!((error.index, error.line), (5, 7));
+        assert_eq!(error.error.downcast_ref(), Some(&DirectoryEntryTypeError { path: "/a/x".to_string(), expected: EntryKind::Folder }));
+        assert_eq!(error.to_string(), "command 5 (on line 7): expected '/a/x' to be a folder, but it is a file");
+
+        // A corrupt line in the output of 'ls' fails the command it was listed by, before anything is applied
+        let log = "$ cd /\n$ ls\ndir a\n1 b\n$ cd a\n$ ls\ndir c\n$ cd c\n$ ls\n2 d\n$ ls\n3 e\n12x f\n4 g\n$ cd c";
+        let error = FileSystem::from_commands(log).err().unwrap();
+        let error = error.downcast_ref::<CommandError>().unwrap();
+        assert_eq!((error.index, error.line), (7, 11));
+        let reason = "expected \"dir\" or a size in whole units before the name";
+        let parse_error = CommandParseError { command_index: Some(7), text: "12x f".to_string(), reason };
+        assert_eq!(error.error.downcast_ref(), Some(&parse_error));
+        assert_eq!(error.to_string(), format!("command 7 (on line 11): could not parse \"12x f\": {reason}"));
+        assert!(matches!(ParsedCommand::from_line("ls\ndir a\nb"), Err(CommandParseError { command_index: None, text, .. }) if text == "b"));
+
+        // Unlisted folders are only created when asked for
+        let (fs, created) = FileSystem::from_commands_with("$ cd /\n$ cd b\n$ ls\n5 c", CdPolicy::CreateMissing).unwrap();
+        assert_eq!((fs.root().calculate_size(&fs), created), (5, 1));
+    }
+
+    #[test]
+    fn cached_sizes() {
+        // Totals stay right as files and folders are added between queries
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        root.add_subfolder(&mut fs, "a".to_string()).unwrap();
+        let a = root.get_subfolder(&fs, "a").unwrap();
+        assert_eq!(root.calculate_size(&fs), 0);
+
+        a.add_subfile(&mut fs, "x".to_string(), 10).unwrap();
+        assert_eq!((a.calculate_size(&fs), root.calculate_size(&fs)), (10, 10));
+
+        a.add_subfolder(&mut fs, "b".to_string()).unwrap();
+        let b = a.get_subfolder(&fs, "b").unwrap();
+        b.add_subfile(&mut fs, "y".to_string(), 5).unwrap();
+        root.add_subfile(&mut fs, "z".to_string(), 100).unwrap();
+        assert_eq!((b.calculate_size(&fs), a.calculate_size(&fs), root.calculate_size(&fs)), (5, 15, 115));
+        assert_eq!(root.sum_directory_sizes_under_max(&fs, 100), 20);
+
+        // Adding an entry that already exists changes nothing
+        a.add_subfile(&mut fs, "x".to_string(), 1000).unwrap();
+        b.add_subfolder(&mut fs, "y".to_string()).unwrap();
+        assert_eq!((b.calculate_size(&fs), a.calculate_size(&fs), root.calculate_size(&fs)), (5, 15, 115));
+        assert_eq!(a.get_file_size(&fs, "x").unwrap(), 10);
+
+        // The kept totals match adding up every file, on a generated tree and the example
+        for fs in [synthetic_filesystem(4, 3, 7), example_filesystem()] {
+            let root = fs.root();
+            let (mut sizes, size) = root.get_all_directory_sizes(&fs);
+            let (mut counted, counted_size) = count_directory_sizes(&fs, root);
+            sizes.sort();
+            counted.sort();
+            assert_eq!((sizes, size), (counted, counted_size));
+        }
+    }
+
+    // Times the directory size queries from kept totals against adding up every folder, on a deep and wide generated tree
+    // Run with 'cargo test --release bench_directory_sizes -- --ignored --nocapture'
+    #[test]
+    #[ignore]
+    fn bench_directory_sizes() {
+        use std::time::Instant;
+
+        let fs = synthetic_filesystem(7, 6, 4);
+        let root = fs.root();
+
+        // The runner's queries for part 2, each of which counted every folder up again
+        let start = Instant::now();
+        let free_space = TOTAL_SPACE - count_directory_sizes(&fs, root).1;
+        let (sizes, _) = count_directory_sizes(&fs, root);
+        let counted = sizes.iter().filter(|x| **x > free_space / 2).min().copied();
+        let counted_time = start.elapsed();
+
+        let start = Instant::now();
+        let free_space = TOTAL_SPACE - root.calculate_size(&fs);
+        let cached = root.smallest_directory_size_over_min(&fs, free_space / 2);
+        let cached_time = start.elapsed();
+
+        assert_eq!(counted, cached);
+        println!("{} nodes, counting: {counted_time:?}, kept totals: {cached_time:?}", fs.nodes.len());
+    }
+
+    // Gets all directory sizes beneath 'node', and its size, by adding up every file as the tree did before totals were kept
+    fn count_directory_sizes(fs: &FileSystem, node: NodeId) -> (Vec<u64>, u64) {
+        match fs.node(node).entry {
+            DirectoryEntry::File(i) => (Vec::new(), i),
+            DirectoryEntry::Folder(ref subfolders) => {
+                let (mut subfolders_vec, folder_size) = subfolders.values().map(|b| count_directory_sizes(fs, *b)).fold(
+                    (Vec::<u64>::new(), 0),
+                    |(acc_vec, acc_size), (new_vec, folder_size)| ([acc_vec, new_vec].concat(), acc_size + folder_size));
+                subfolders_vec.push(folder_size);
+                (subfolders_vec, folder_size)
+            }
+        }
+    }
+
+    // Builds a tree 'depth' folders deep, where every folder has 'width' subfolders and 'files' files of sizes 1, 2, 3...
+    fn synthetic_filesystem(depth: usize, width: usize, files: u64) -> FileSystem {
+        let mut fs = FileSystem::new();
+        let mut level = vec![fs.root()];
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for folder in level {
+                for size in 1..=files {
+                    folder.add_subfile(&mut fs, format!("file_{size}"), size).unwrap();
+                }
+                for i in 0..width {
+                    folder.add_subfolder(&mut fs, format!("folder_{i}")).unwrap();
+                    next.push(folder.get_subfolder(&fs, &format!("folder_{i}")).unwrap());
+                }
+            }
+            level = next;
+        }
+        fs
+    }
+
+    // Builds the Advent of Code challenge-provided example filesystem
+    fn example_filesystem() -> FileSystem {
+        FileSystem::from_commands(EXAMPLE).unwrap()
+    }
+
+    // Advent of Code challenge-provided example terminal log
+    const EXAMPLE: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+}
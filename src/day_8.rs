@@ -25,7 +25,7 @@ struct VantageTracker {
 
 // Run challenge.
 // Main entry point to day 8 challenge.
-pub fn run(part_2 : bool) -> Result<(), Box<dyn error::Error> > {
+pub fn run(part_2 : bool, _options: &Options) -> Result<(), Box<dyn error::Error> > {
 
     // Loads matrix from file and reads to string
     let f = File::open("input/day8input.txt")?;
@@ -28,7 +28,7 @@ enum Direction {
 
 // Run challenge.
 // Main entry point to day 9 challenge.
-pub fn run(part_2 : bool) -> Result<(), Box<dyn error::Error>> {
+pub fn run(part_2 : bool, _options: &Options) -> Result<(), Box<dyn error::Error>> {
 
     // Load input text into file buffer
     let f = File::open("input/day9input.txt").unwrap();
@@ -1,10 +1,10 @@
 
 mod day_1;
 mod day_2;
-mod day_3;
+pub mod day_3; // its part1 and part2 can be called on any rucksack list
 mod day_4;
 mod day_5;
-mod day_6;
+pub mod day_6; // its marker searches are also a library for other signals
 mod day_7;
 mod day_8;
 mod day_9;
@@ -19,40 +19,98 @@ use std::io::BufReader;
 use std::io::{Error, ErrorKind};
 use std::fs::File;
 
+// Command line options passed through to each day's challenge
+#[derive(Default, Debug)]
+pub struct Options {
+    pub verbose: bool, // print additional detail alongside results
+    pub dump: Option<String>, // path to write detailed output to, for days that support it
+    pub dump_fs: Option<String>, // path to write the filesystem rebuilt by day 7 to, as JSON
+    pub group_size: Option<usize>, // number of rucksacks per group in day 3 part 2, instead of the puzzle's 3
+    pub split: Option<String>, // how day 3 splits rucksacks with an odd number of items, instead of failing
+    pub marker_len: Option<usize>, // length of the start marker searched for in both parts of day 6, instead of the puzzle's 4 and 14
+    pub validate: bool, // check the whole input and report every problem found, for days that support it
+    pub lenient: bool, // accept loosely written input, such as odd spacing or comments, for days that support it
+    pub check: bool, // check invariants while solving, failing as soon as one is broken, for days that support it
+}
+
+// Answers to both parts of a day's challenge, and any extra lines to print alongside them
+#[derive(Debug, PartialEq)]
+struct Answers {
+    part_1: String,
+    part_2: String,
+    notes: Vec<String>, // lines always printed after the answers
+    details: Vec<String>, // lines printed after the notes in verbose mode
+}
+
+// A day's challenge entry point, taking a 'part_2' flag and the command line options, which prints its own result
+type ChallengeFn = dyn Fn(bool, &Options) -> Result<(), Box<dyn error::Error>>;
+
+// A day's challenge entry point, which reads its input once and returns the answers to both parts for the runner to print
+type SolveFn = dyn Fn(&Options) -> Result<Answers, Box<dyn error::Error>>;
+
+// A day's challenge, by the kind of entry point it has
+enum Challenge {
+    Parts(&'static ChallengeFn), // run once for each part
+    Solve(&'static SolveFn), // run once for both parts
+}
+
 // Run all challenge files up to the current date
 // 'specific_challenge' - index of specific challenge to run
-pub fn run_challenges(specific_challenge: usize) -> Result<(), Box<dyn error::Error>> {
-    let functions: Vec<&dyn Fn(bool) -> Result<(), Box<dyn error::Error>>> =  vec![
-        &day_1::run,
-        &day_2::run,
-        &day_3::run,
-        &day_4::run,
-        &day_5::run,
-        &day_6::run,
-        &day_7::run,
-        &day_8::run,
-        &day_9::run,
-        &day_10::run
+// 'options' - command line options passed through to each challenge
+pub fn run_challenges(specific_challenge: usize, options: &Options) -> Result<(), Box<dyn error::Error>> {
+    let challenges: Vec<Challenge> =  vec![
+        Challenge::Parts(&day_1::run),
+        Challenge::Solve(&day_2::run),
+        Challenge::Solve(&day_3::run),
+        Challenge::Solve(&day_4::run),
+        Challenge::Parts(&day_5::run),
+        Challenge::Solve(&day_6::run),
+        Challenge::Parts(&day_7::run),
+        Challenge::Parts(&day_8::run),
+        Challenge::Parts(&day_9::run),
+        Challenge::Parts(&day_10::run)
     ];
     
     if specific_challenge > 0 {
-        run_challenge_parts(functions[specific_challenge])?;
+        run_challenge(specific_challenge + 1, &challenges[specific_challenge], options)?;
     } else {
-        for f in functions {
-            run_challenge_parts(f)?;
+        for (i, challenge) in challenges.iter().enumerate() {
+            run_challenge(i + 1, challenge, options)?;
         }
     }
     Ok(())
 }
 
+// Runs both part_1 and part_2 of provided challenge, printing results that the challenge returns
+// 'day' - the day number of the challenge
+fn run_challenge(day: usize, challenge: &Challenge, options: &Options) -> Result<(),Box<dyn error::Error>> {
+    match challenge {
+        Challenge::Parts(f) => run_challenge_parts(*f, options),
+        Challenge::Solve(f) => {
+            let answers = f(options)?;
+            println!("Result for day {day}-1 = {}", answers.part_1);
+            println!("Result for day {day}-2 = {}", answers.part_2);
+            for line in answers.notes {
+                println!("{line}");
+            }
+            if options.verbose {
+                for line in answers.details {
+                    println!("{line}");
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
 // Runs both part_1 and part_2 of provided challenge function
-// 'f' - function that accepts a boolean (for 'part_2') that corresponds to the day's challengs
-fn run_challenge_parts(f : &dyn Fn(bool) -> Result<(), Box<dyn error::Error>>) -> Result<(),Box<dyn error::Error>> {
+// 'f' - function that accepts a boolean (for 'part_2') and the command line options that corresponds to the day's challengs
+fn run_challenge_parts(f : &ChallengeFn, options: &Options) -> Result<(),Box<dyn error::Error>> {
     for part in [false, true] {
-        match f(part) {
+        match f(part, options) {
             Ok(()) => (),
             Err(e) => return Err(e)
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
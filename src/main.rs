@@ -4,10 +4,12 @@ use std::process;
 use std::error;
 use std::io;
 
+use advent_of_code::Options;
+
 fn main() {
     let args = env::args();
      
-    let specific_challenge = match parse_arguments(args) {
+    let (specific_challenge, options) = match parse_arguments(args) {
        Ok(s) => s,
        Err(e) => {
             println!("Failed with error: {e}");
@@ -15,7 +17,7 @@ fn main() {
         }
     };
 
-    match advent_of_code::run_challenges(specific_challenge) {
+    match advent_of_code::run_challenges(specific_challenge, &options) {
         Ok(()) => process::exit(0),
         Err(e) => {
             println!("Failed with error: {e}");
@@ -26,20 +28,54 @@ fn main() {
 }
 
 
-fn parse_arguments(mut args : Args) -> Result<usize, Box<dyn error::Error>> {
+// Parses command line arguments into the challenge to run, and the options to run it with
+// Accepts an optional challenge number, and in any position:
+// '-v'/'--verbose' - print additional detail alongside results
+// '--dump <path>' - write detailed output to 'path', for days that support it
+// '--dump-fs <path>' - write the filesystem rebuilt by day 7 to 'path', as JSON
+// '--group-size <n>' - group rucksacks in 'n's rather than 3s for day 3 part 2
+// '--split <policy>' - split odd-length rucksacks in day 3 by 'policy' ('strict', 'drop-middle' or 'left-biased')
+// '--marker-len <n>' - search for start markers of 'n' characters in both parts of day 6, rather than 4 and 14
+// '--validate' - check the whole input and report every problem found, for days that support it
+// '--lenient' - accept loosely written input, such as odd spacing or comments, for days that support it
+// '--check' - check invariants while solving, failing as soon as one is broken, for days that support it
+fn parse_arguments(mut args : Args) -> Result<(usize, Options), Box<dyn error::Error>> {
     args.next(); // drop first file name argument
-    let args : Vec<String> = args.collect();
 
-    if args.len() > 1 {
-        let e = io::Error::new(io::ErrorKind::Other, "Unsupported number of arguments (0 or 1).");
+    // Separate out options from positional arguments
+    let mut options = Options::default();
+    let mut positional : Vec<String> = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-v" | "--verbose" => options.verbose = true,
+            "--dump" => options.dump = Some(args.next().ok_or_else(|| io::Error::other("Missing path after --dump."))?),
+            "--dump-fs" => options.dump_fs = Some(args.next().ok_or_else(|| io::Error::other("Missing path after --dump-fs."))?),
+            "--validate" => options.validate = true,
+            "--lenient" => options.lenient = true,
+            "--check" => options.check = true,
+            "--group-size" => {
+                let n = args.next().ok_or_else(|| io::Error::other("Missing number after --group-size."))?;
+                options.group_size = Some(n.parse::<usize>()?);
+            },
+            "--split" => options.split = Some(args.next().ok_or_else(|| io::Error::other("Missing policy after --split."))?),
+            "--marker-len" => {
+                let n = args.next().ok_or_else(|| io::Error::other("Missing number after --marker-len."))?;
+                options.marker_len = Some(n.parse::<usize>()?);
+            },
+            _ => positional.push(arg),
+        }
+    }
+
+    if positional.len() > 1 {
+        let e = io::Error::other("Unsupported number of arguments (0 or 1, plus optional -v/--verbose, --dump <path>, --dump-fs <path>, --group-size <n>, --split <policy>, --marker-len <n>, --validate, --lenient and --check).");
         return Err(Box::new(e));
     }
 
     // If no argument, specific_challenge = 0 as default (which is used by 'run_challenges' to mean 'all')
     // If there is an argument, interpret it as a usize
-    if args.len() == 0 {
-        Ok(0)
+    if positional.is_empty() {
+        Ok((0, options))
     } else {
-        Ok(args[0].parse::<usize>()? - 1)
+        Ok((positional[0].parse::<usize>()? - 1, options))
     }
-}
\ No newline at end of file
+}